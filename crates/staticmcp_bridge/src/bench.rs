@@ -0,0 +1,303 @@
+//! `staticmcp-bridge bench`: load-tests a running bridge with a mix of MCP
+//! requests and reports throughput and latency.
+
+use crate::validate::sample_arguments;
+use clap::Args;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde_json::{Value, json};
+use staticmcp_sse_lib::ToolDef;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Replay initialize, list, read and call requests against a bridge and report latency.
+#[derive(Debug, Clone, Args)]
+pub struct BenchArgs {
+    /// The bridge's message endpoint, e.g. `http://localhost:3000/sse`
+    #[arg(long, value_name = "URL")]
+    pub target: String,
+
+    /// How many requests to keep in flight
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub concurrency: usize,
+
+    /// How long to send requests for
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub duration: Duration,
+
+    /// Relative weight of each request kind, as `KIND=WEIGHT` for the kinds
+    /// `initialize`, `list`, `read` and `call`
+    #[arg(
+        long,
+        value_name = "KIND=WEIGHT",
+        value_delimiter = ',',
+        value_parser = parse_weight,
+        default_value = "initialize=1,list=1,read=4,call=4"
+    )]
+    pub mix: Vec<(Kind, usize)>,
+
+    /// Header sent with every request, as `NAME: VALUE`, e.g. for a bearer
+    /// token
+    #[arg(long, value_name = "NAME: VALUE", value_parser = parse_header)]
+    pub header: Vec<(HeaderName, HeaderValue)>,
+
+    /// How long one request may take before it counts as failed
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Initialize,
+    List,
+    Read,
+    Call,
+}
+
+impl Kind {
+    const ALL: [Kind; 4] = [Kind::Initialize, Kind::List, Kind::Read, Kind::Call];
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Initialize => "initialize",
+            Kind::List => "list",
+            Kind::Read => "read",
+            Kind::Call => "call",
+        }
+    }
+}
+
+fn parse_weight(value: &str) -> Result<(Kind, usize), String> {
+    let (kind, weight) = value.split_once('=').ok_or("expected KIND=WEIGHT")?;
+    let kind = Kind::ALL
+        .into_iter()
+        .find(|candidate| candidate.name() == kind)
+        .ok_or_else(|| format!("unknown kind `{kind}`; expected initialize, list, read or call"))?;
+    let weight = weight
+        .parse()
+        .map_err(|_| format!("`{weight}` is not a whole number"))?;
+    Ok((kind, weight))
+}
+
+fn parse_header(value: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = value.split_once(':').ok_or("expected NAME: VALUE")?;
+    let name = HeaderName::try_from(name.trim()).map_err(|e| e.to_string())?;
+    let value = HeaderValue::try_from(value.trim()).map_err(|e| e.to_string())?;
+    Ok((name, value))
+}
+
+/// Latencies of one request kind, successes and failures apart.
+#[derive(Default)]
+struct Samples {
+    ok: Vec<Duration>,
+    failed: usize,
+}
+
+pub async fn run(args: BenchArgs) -> anyhow::Result<()> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.extend(
+        args.header
+            .iter()
+            .cloned()
+            .map(|(name, value)| (Some(name), value)),
+    );
+    let client = reqwest::Client::builder()
+        .timeout(args.timeout)
+        .default_headers(headers)
+        .build()?;
+
+    let schedule = Arc::new(schedule(&client, &args).await?);
+    if schedule.kinds.is_empty() {
+        anyhow::bail!("no request kind in --mix has both a weight and requests to send");
+    }
+    eprintln!(
+        "benchmarking {} with {} connections for {}",
+        args.target,
+        args.concurrency,
+        humantime::format_duration(args.duration)
+    );
+
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let deadline = started + args.duration;
+    let workers: Vec<_> = (0..args.concurrency.max(1))
+        .map(|_| {
+            let (client, target) = (client.clone(), args.target.clone());
+            let (schedule, next) = (schedule.clone(), next.clone());
+            tokio::spawn(async move {
+                let mut samples: Vec<Samples> = Kind::ALL.map(|_| Samples::default()).into();
+                while Instant::now() < deadline {
+                    let (kind, body) = schedule.get(next.fetch_add(1, Ordering::Relaxed));
+                    let sent = Instant::now();
+                    let ok = send(&client, &target, body).await.is_ok();
+                    let samples = &mut samples[kind as usize];
+                    if ok {
+                        samples.ok.push(sent.elapsed());
+                    } else {
+                        samples.failed += 1;
+                    }
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut totals: Vec<Samples> = Kind::ALL.map(|_| Samples::default()).into();
+    for worker in workers {
+        for (total, samples) in totals.iter_mut().zip(worker.await?) {
+            total.ok.extend(samples.ok);
+            total.failed += samples.failed;
+        }
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "{:<12}{:>10}{:>8}{:>10}{:>10}{:>10}{:>10}",
+        "kind", "requests", "failed", "p50", "p90", "p99", "max"
+    );
+    let mut all = Samples::default();
+    for (kind, samples) in Kind::ALL.iter().zip(&mut totals) {
+        if samples.ok.is_empty() && samples.failed == 0 {
+            continue;
+        }
+        print_row(kind.name(), samples);
+        all.ok.extend(&samples.ok);
+        all.failed += samples.failed;
+    }
+    print_row("all", &mut all);
+    let completed = all.ok.len() + all.failed;
+    println!(
+        "\n{completed} requests in {:.1}s: {:.1} requests/s, {:.1}% failed",
+        elapsed.as_secs_f64(),
+        completed as f64 / elapsed.as_secs_f64(),
+        100.0 * all.failed as f64 / completed.max(1) as f64
+    );
+    Ok(())
+}
+
+fn print_row(name: &str, samples: &mut Samples) {
+    samples.ok.sort();
+    let percentile = |p: f64| {
+        let index = ((p * samples.ok.len() as f64).ceil() as usize).saturating_sub(1);
+        samples.ok.get(index).map_or("-".to_string(), |latency| {
+            format!("{:.1}ms", latency.as_secs_f64() * 1e3)
+        })
+    };
+    println!(
+        "{name:<12}{:>10}{:>8}{:>10}{:>10}{:>10}{:>10}",
+        samples.ok.len() + samples.failed,
+        samples.failed,
+        percentile(0.5),
+        percentile(0.9),
+        percentile(0.99),
+        percentile(1.0)
+    );
+}
+
+/// One request: HTTP success and a JSON-RPC result without `isError`.
+async fn send(client: &reqwest::Client, target: &str, body: &Value) -> anyhow::Result<Value> {
+    let response: Value = client
+        .post(target)
+        .json(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        anyhow::bail!("{error}");
+    }
+    if response.pointer("/result/isError") == Some(&Value::Bool(true)) {
+        anyhow::bail!("tool call failed");
+    }
+    Ok(response["result"].clone())
+}
+
+/// The requests the workers cycle through.
+struct Schedule {
+    /// Each kind repeated by its weight.
+    kinds: Vec<Kind>,
+    /// Every request of each kind, indexed by [`Kind`].
+    bodies: [Vec<Value>; 4],
+}
+
+impl Schedule {
+    /// The `n`th request sent: its kind by weight, then that kind's
+    /// requests in turn.
+    fn get(&self, n: usize) -> (Kind, &Value) {
+        let kind = self.kinds[n % self.kinds.len()];
+        let bodies = &self.bodies[kind as usize];
+        (kind, &bodies[n / self.kinds.len() % bodies.len()])
+    }
+}
+
+/// Reads and calls are sampled from the bridge's own listings; kinds the
+/// site offers nothing for are dropped with a warning.
+async fn schedule(client: &reqwest::Client, args: &BenchArgs) -> anyhow::Result<Schedule> {
+    let rpc = |method: &str, params: Value| json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let initialize = rpc(
+        "initialize",
+        json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "staticmcp-bridge bench", "version": env!("CARGO_PKG_VERSION") }
+        }),
+    );
+    send(client, &args.target, &initialize)
+        .await
+        .map_err(|e| anyhow::anyhow!("initialize against {} failed: {e}", args.target))?;
+
+    let lists = vec![
+        rpc("tools/list", json!({})),
+        rpc("resources/list", json!({})),
+    ];
+    let resources = send(client, &args.target, &lists[1]).await?;
+    let tools = send(client, &args.target, &lists[0]).await?;
+    let reads: Vec<Value> = resources["resources"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|resource| resource.get("uri"))
+        .map(|uri| rpc("resources/read", json!({ "uri": uri })))
+        .collect();
+    let calls: Vec<Value> = tools["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| {
+            let tool: ToolDef = serde_json::from_value(tool.clone()).ok()?;
+            let arguments = sample_arguments(&tool).ok()?;
+            Some(rpc(
+                "tools/call",
+                json!({ "name": tool.name, "arguments": arguments }),
+            ))
+        })
+        .collect();
+
+    let bodies = [vec![initialize], lists, reads, calls];
+    let mut kinds = Vec::new();
+    for &(kind, weight) in &args.mix {
+        if weight == 0 {
+            continue;
+        }
+        if bodies[kind as usize].is_empty() {
+            eprintln!(
+                "warning: no {} requests to sample, skipping them",
+                kind.name()
+            );
+            continue;
+        }
+        kinds.extend(std::iter::repeat_n(kind, weight));
+    }
+    Ok(Schedule { kinds, bodies })
+}
@@ -0,0 +1,84 @@
+//! `staticmcp-bridge call`: invokes one tool of a StaticMCP site and prints
+//! its result, without an MCP client.
+
+use clap::Args;
+use serde_json::{Value, json};
+use staticmcp_sse_lib::server::PathArgs;
+use staticmcp_sse_lib::{MCPBridge, MCPRequest, create_data_source};
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+/// Call a tool of a StaticMCP directory or URL and print the result.
+#[derive(Debug, Clone, Args)]
+pub struct CallArgs {
+    /// StaticMCP directory or URL
+    pub source: String,
+
+    /// Tool to call
+    pub tool: String,
+
+    /// Argument as `KEY=VALUE`; values that parse as JSON, such as `3` or
+    /// `true`, are passed as such, the rest as strings
+    #[arg(long = "arg", value_name = "KEY=VALUE", value_parser = parse_arg)]
+    pub args: Vec<(String, Value)>,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+fn parse_arg(value: &str) -> Result<(String, Value), String> {
+    let (key, value) = value
+        .split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or("expected KEY=VALUE")?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+pub async fn run(args: CallArgs) -> anyhow::Result<ExitCode> {
+    let bridge = load(&args.source, &args.paths).await?;
+    let arguments: HashMap<String, Value> = args.args.into_iter().collect();
+    eprintln!("path: {}", bridge.tool_to_path(&args.tool, &arguments)?);
+    let params = json!({ "name": args.tool, "arguments": arguments });
+    send(&bridge, "tools/call", params).await
+}
+
+/// The initialized bridge for `source`.
+pub(crate) async fn load(source: &str, paths: &PathArgs) -> anyhow::Result<MCPBridge> {
+    let mut bridge = paths
+        .apply(MCPBridge::builder(create_data_source(source.to_string())))
+        .build();
+    bridge
+        .initialize()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to load the manifest of {source}: {e}"))?;
+    Ok(bridge)
+}
+
+/// Runs one request through the bridge, printing the result on stdout or
+/// the error on stderr, and exits `1` on an error or a tool result marked
+/// `isError`.
+pub(crate) async fn send(
+    bridge: &MCPBridge,
+    method: &str,
+    params: Value,
+) -> anyhow::Result<ExitCode> {
+    let request = MCPRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: method.to_string(),
+        params: Some(params),
+    };
+    let response = bridge.handle_request(request).await;
+    if let Some(error) = response.error {
+        eprintln!("error {}: {}", error.code, error.message);
+        return Ok(ExitCode::FAILURE);
+    }
+    let result = response.result.unwrap_or(Value::Null);
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(if result["isError"] == true {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
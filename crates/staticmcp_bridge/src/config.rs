@@ -0,0 +1,165 @@
+//! `--config`: settings from a TOML or YAML file.
+//!
+//! Keys are flag names with `_` or `-`, and tables join their names onto the
+//! keys inside them, so `[auth] issuer = "…"` sets `--auth-issuer`. A table
+//! given for a `KEY=VALUE` flag such as `mount` or `vhost` lists its entries:
+//!
+//! ```toml
+//! port = 8080
+//! cache_max_age = "5m"
+//!
+//! [mount]
+//! "/docs" = "./docs-mcp"
+//!
+//! [log]
+//! format = "json"
+//! ```
+//!
+//! File values become the flags' defaults, so environment variables and
+//! command-line flags still override them.
+
+use clap::error::ErrorKind;
+use clap::{ArgAction, Command, CommandFactory, FromArgMatches};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_ENV: &str = "STATICMCP_CONFIG";
+
+/// Parses the process arguments into `T`, taking defaults from the
+/// `--config` file when one is given.
+pub fn parse<T: CommandFactory + FromArgMatches>() -> T {
+    let mut command = T::command();
+    if let Some(path) = config_path(std::env::args_os()) {
+        command = match load(&path).and_then(|settings| apply(command.clone(), settings)) {
+            Ok(command) => command,
+            Err(message) => command
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("{}: {message}", path.display()),
+                )
+                .exit(),
+        };
+    }
+    let matches = command.clone().get_matches();
+    T::from_arg_matches(&matches).unwrap_or_else(|e| e.format(&mut command).exit())
+}
+
+/// The `--config` argument, or `STATICMCP_CONFIG`, found ahead of the real
+/// parse since it decides the defaults that parse uses.
+fn config_path(args: impl IntoIterator<Item = OsString>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    std::env::var_os(CONFIG_ENV).map(PathBuf::from)
+}
+
+/// Reads the file as YAML when its extension says so, otherwise as TOML.
+fn load(path: &Path) -> Result<Value, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+        _ => toml::from_str(&content).map_err(|e| e.to_string()),
+    }
+}
+
+/// Installs `settings` as defaults on `command` and each of its
+/// subcommands, failing on keys no flag anywhere accepts.
+fn apply(command: Command, settings: Value) -> Result<Command, String> {
+    let mut known = HashMap::new();
+    collect_ids(&command, &mut known);
+
+    let mut flattened = BTreeMap::new();
+    flatten("", settings, &known, &mut flattened)?;
+    flattened.remove("config");
+    if let Some(unknown) = flattened.keys().find(|key| !known.contains_key(*key)) {
+        return Err(format!(
+            "unknown setting `{unknown}`; settings are the flag names shown by --help"
+        ));
+    }
+    Ok(set_defaults(command, &flattened))
+}
+
+/// Every flag id in the tree, and whether the flag takes a list.
+fn collect_ids(command: &Command, ids: &mut HashMap<String, bool>) {
+    ids.extend(command.get_arguments().map(|arg| {
+        let list = matches!(arg.get_action(), ArgAction::Append);
+        (arg.get_id().to_string(), list)
+    }));
+    for subcommand in command.get_subcommands() {
+        collect_ids(subcommand, ids);
+    }
+}
+
+fn set_defaults(mut command: Command, settings: &BTreeMap<String, Vec<String>>) -> Command {
+    let ids: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .filter(|id| settings.contains_key(id))
+        .collect();
+    for id in ids {
+        let values = settings[&id].clone();
+        command = command.mut_arg(&id, |arg| arg.default_values(values).required(false));
+    }
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(&name, |subcommand| set_defaults(subcommand, settings));
+    }
+    command
+}
+
+/// Turns the nested document into flag ids and their values.
+fn flatten(
+    prefix: &str,
+    value: Value,
+    known: &HashMap<String, bool>,
+    out: &mut BTreeMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let values = match value {
+        Value::Null => return Ok(()),
+        Value::Object(table) if known.get(prefix) != Some(&true) => {
+            for (key, value) in table {
+                let key = key.replace('-', "_");
+                let key = match prefix {
+                    "" => key,
+                    prefix => format!("{prefix}_{key}"),
+                };
+                flatten(&key, value, known, out)?;
+            }
+            return Ok(());
+        }
+        Value::Object(table) => table
+            .into_iter()
+            .map(|(key, value)| Ok(format!("{key}={}", scalar(prefix, value)?)))
+            .collect::<Result<_, String>>()?,
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| scalar(prefix, item))
+            .collect::<Result<_, String>>()?,
+        value => vec![scalar(prefix, value)?],
+    };
+    out.insert(prefix.to_string(), values);
+    Ok(())
+}
+
+fn scalar(key: &str, value: Value) -> Result<String, String> {
+    match value {
+        Value::String(value) => Ok(value),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        _ => Err(format!("`{key}` must be a string, number or boolean")),
+    }
+}
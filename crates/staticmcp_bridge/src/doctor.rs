@@ -0,0 +1,400 @@
+//! `staticmcp-bridge doctor`: diagnoses why a remote StaticMCP site can't be
+//! reached or served, one layer at a time.
+
+use crate::validate::sample_arguments;
+use clap::Args;
+use reqwest::{Method, Url, header};
+use serde_json::{Value, json};
+use staticmcp_sse_lib::server::PathArgs;
+use staticmcp_sse_lib::{MCPBridge, MCPRequest, create_data_source};
+use std::error::Error;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Diagnose DNS, TLS, manifest, CORS and sample requests against a StaticMCP URL.
+#[derive(Debug, Clone, Args)]
+pub struct DoctorArgs {
+    /// StaticMCP URL, the directory holding `mcp.json`
+    pub url: String,
+
+    /// Origin sent when checking CORS headers
+    #[arg(long, value_name = "ORIGIN", default_value = "https://example.com")]
+    pub origin: String,
+
+    /// How long each check may take
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    pub timeout: Duration,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+#[derive(Default)]
+struct Report {
+    failed: bool,
+}
+
+impl Report {
+    fn ok(&mut self, check: &str, message: impl std::fmt::Display) {
+        println!("ok:      {check}: {message}");
+    }
+
+    fn skip(&mut self, check: &str, message: impl std::fmt::Display) {
+        println!("skipped: {check}: {message}");
+    }
+
+    fn warning(&mut self, check: &str, message: impl std::fmt::Display, hint: &str) {
+        println!("warning: {check}: {message}");
+        println!("         {hint}");
+    }
+
+    fn error(&mut self, check: &str, message: impl std::fmt::Display, hint: &str) {
+        self.failed = true;
+        println!("error:   {check}: {message}");
+        println!("         {hint}");
+    }
+
+    fn exit_code(&self) -> ExitCode {
+        if self.failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Runs each check in turn, stopping at the first failure the later ones
+/// depend on, and exits `1` when any check failed.
+pub async fn run(args: DoctorArgs) -> anyhow::Result<ExitCode> {
+    let mut report = Report::default();
+    let base = match Url::parse(&args.url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => url,
+        _ => {
+            report.error(
+                "url",
+                format!("`{}` is not an http or https URL", args.url),
+                "give the site's full URL, e.g. https://example.com/mcp",
+            );
+            return Ok(report.exit_code());
+        }
+    };
+    let host = base.host_str().unwrap_or_default().to_string();
+    let manifest_url = format!("{}/mcp.json", args.url.trim_end_matches('/'));
+
+    if !resolve(&base, &host, &mut report).await {
+        return Ok(report.exit_code());
+    }
+
+    let client = reqwest::Client::builder().timeout(args.timeout).build()?;
+    let response = match client
+        .get(&manifest_url)
+        .header(header::ORIGIN, &args.origin)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            connection_failed(&base, &host, &e, args.timeout, &mut report);
+            return Ok(report.exit_code());
+        }
+    };
+    if base.scheme() == "https" {
+        report.ok("tls", format!("certificate for {host} is trusted"));
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let hint = if status == reqwest::StatusCode::NOT_FOUND {
+            "the URL should name the directory holding mcp.json, not the file itself"
+        } else {
+            "the host must serve mcp.json to anonymous GET requests"
+        };
+        report.error(
+            "manifest",
+            format!("GET {manifest_url} answered {status}"),
+            hint,
+        );
+        return Ok(report.exit_code());
+    }
+    check_cors(
+        &client,
+        &manifest_url,
+        &args.origin,
+        response.headers(),
+        &mut report,
+    )
+    .await;
+
+    let mut bridge = args
+        .paths
+        .apply(MCPBridge::builder(create_data_source(args.url.clone())))
+        .build();
+    match tokio::time::timeout(args.timeout, bridge.initialize()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            report.error(
+                "manifest",
+                e,
+                "mcp.json must be a JSON object with `serverInfo` and `capabilities`",
+            );
+            return Ok(report.exit_code());
+        }
+        Err(_) => {
+            report.error(
+                "manifest",
+                format!(
+                    "timed out after {}",
+                    humantime::format_duration(args.timeout)
+                ),
+                "the host is reachable but slow to answer",
+            );
+            return Ok(report.exit_code());
+        }
+    }
+    let manifest = bridge.get_manifest().expect("initialized");
+    let (resources, tools) = (bridge.resources(), bridge.tools());
+    report.ok(
+        "manifest",
+        format!(
+            "{} {} declares {} resources and {} tools",
+            manifest
+                .server_info
+                .as_ref()
+                .map_or("(unnamed)", |info| info.name.as_str()),
+            manifest
+                .server_info
+                .as_ref()
+                .map_or("", |info| info.version.as_str()),
+            resources.len(),
+            tools.len()
+        ),
+    );
+
+    let resource = resources.first().map(|resource| resource.uri.as_str());
+    match resource {
+        Some(uri) => {
+            let params = json!({ "uri": uri });
+            let path = bridge.uri_to_path(uri);
+            sample(
+                "read",
+                &bridge,
+                "resources/read",
+                params,
+                &path,
+                args.timeout,
+                &mut report,
+            )
+            .await;
+        }
+        None => report.skip("read", "the manifest declares no resources"),
+    }
+
+    let call = tools
+        .iter()
+        .find_map(|tool| Some((tool.name.as_str(), sample_arguments(tool).ok()?)));
+    match call {
+        Some((name, arguments)) => {
+            let path = bridge.tool_to_path(name, &arguments)?;
+            let params = json!({ "name": name, "arguments": arguments });
+            sample(
+                "call",
+                &bridge,
+                "tools/call",
+                params,
+                &path,
+                args.timeout,
+                &mut report,
+            )
+            .await;
+        }
+        None if tools.is_empty() => report.skip("call", "the manifest declares no tools"),
+        None => report.skip(
+            "call",
+            "no tool's required arguments have an enum, examples or default to sample",
+        ),
+    }
+
+    Ok(report.exit_code())
+}
+
+async fn resolve(base: &Url, host: &str, report: &mut Report) -> bool {
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    if literal.parse::<std::net::IpAddr>().is_ok() {
+        report.skip("dns", format!("{host} is an address"));
+        return true;
+    }
+    let port = base.port_or_known_default().unwrap_or(443);
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(addresses) => {
+            let addresses: Vec<String> = addresses.map(|addr| addr.ip().to_string()).collect();
+            report.ok(
+                "dns",
+                format!("{host} resolves to {}", addresses.join(", ")),
+            );
+            true
+        }
+        Err(e) => {
+            report.error(
+                "dns",
+                format!("{host} doesn't resolve: {e}"),
+                "check the host name, and that this machine's resolver can see it",
+            );
+            false
+        }
+    }
+}
+
+/// Tells a refused or timed-out connection apart from a TLS failure, which
+/// reqwest only reveals in the messages of the error's sources.
+fn connection_failed(
+    base: &Url,
+    host: &str,
+    error: &reqwest::Error,
+    timeout: Duration,
+    report: &mut Report,
+) {
+    let mut causes = Vec::new();
+    let mut source: Option<&dyn Error> = Some(error);
+    while let Some(cause) = source {
+        causes.push(cause.to_string().to_lowercase());
+        source = cause.source();
+    }
+    let tls = causes.iter().any(|cause| {
+        ["certificate", "tls", "ssl", "handshake"]
+            .iter()
+            .any(|word| cause.contains(word))
+    });
+    // Each layer repeats the ones below it; the innermost says what happened.
+    let mut root: &dyn Error = error;
+    while let Some(cause) = root.source() {
+        root = cause;
+    }
+    let message = root.to_string();
+
+    if error.is_timeout() {
+        report.error(
+            "connect",
+            format!("no answer within {}", humantime::format_duration(timeout)),
+            "a firewall may be dropping connections to this port",
+        );
+    } else if base.scheme() == "https" && tls {
+        report.error(
+            "tls",
+            message,
+            &format!("the certificate must be valid for {host} and issued by a trusted CA"),
+        );
+    } else {
+        report.error(
+            "connect",
+            message,
+            "check that the host is up and listening on this port",
+        );
+    }
+}
+
+/// Reports whether browsers on `origin` may fetch the site directly. Bridges
+/// fetch server-side and don't need CORS, so its absence is only a warning.
+async fn check_cors(
+    client: &reqwest::Client,
+    url: &str,
+    origin: &str,
+    headers: &header::HeaderMap,
+    report: &mut Report,
+) {
+    const HINT: &str = "browser-based MCP clients can't fetch the site directly; bridges are \
+                        unaffected";
+    let allowed = |headers: &header::HeaderMap| {
+        headers
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == "*" || value == origin)
+    };
+    if !allowed(headers) {
+        report.warning(
+            "cors",
+            format!("GET has no Access-Control-Allow-Origin for {origin}"),
+            HINT,
+        );
+        return;
+    }
+
+    let preflight = client
+        .request(Method::OPTIONS, url)
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .send()
+        .await;
+    match preflight {
+        Ok(response) if response.status().is_success() && allowed(response.headers()) => {
+            report.ok("cors", format!("{origin} may fetch the site"));
+        }
+        Ok(response) => report.warning(
+            "cors",
+            format!(
+                "preflight answered {} without allowing {origin}",
+                response.status()
+            ),
+            HINT,
+        ),
+        Err(e) => report.warning("cors", format!("preflight failed: {e}"), HINT),
+    }
+}
+
+/// Sends one request through the bridge, as a client would, and reports the
+/// file it mapped to when it fails.
+async fn sample(
+    check: &str,
+    bridge: &MCPBridge,
+    method: &str,
+    params: Value,
+    path: &str,
+    timeout: Duration,
+    report: &mut Report,
+) {
+    let request = MCPRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: method.to_string(),
+        params: Some(params.clone()),
+    };
+    let started = Instant::now();
+    let Ok(response) = tokio::time::timeout(timeout, bridge.handle_request(request)).await else {
+        report.error(
+            check,
+            format!("{method} {params} timed out"),
+            "the host is reachable but slow to answer",
+        );
+        return;
+    };
+    // Failed tool calls are results flagged `isError`, not JSON-RPC errors.
+    let failure = match (response.error, response.result) {
+        (Some(error), _) => Some(error.message),
+        (None, Some(result)) if result["isError"] == true => Some(
+            result
+                .pointer("/content/0/text")
+                .and_then(Value::as_str)
+                .unwrap_or("isError")
+                .to_string(),
+        ),
+        (None, _) => None,
+    };
+    match failure {
+        None => report.ok(
+            check,
+            format!(
+                "{method} {params} answered in {}ms",
+                started.elapsed().as_millis()
+            ),
+        ),
+        Some(message) => report.error(
+            check,
+            format!("{method} {params} failed: {message}"),
+            &format!("the site should serve {path}"),
+        ),
+    }
+}
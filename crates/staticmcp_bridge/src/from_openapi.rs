@@ -0,0 +1,426 @@
+//! `staticmcp-bridge from-openapi`: turns an OpenAPI 3 document into a
+//! StaticMCP site, for teams whose API is already described by one.
+//!
+//! - each `GET` operation becomes a read-only tool named by its
+//!   `operationId`, taking its path and query parameters as arguments
+//! - the example of its `200` (or first `2xx`) JSON response becomes the
+//!   tool's file for a call with each required parameter's example, written
+//!   where the bridge looks for that call
+//! - the response's schema, when it describes an object, becomes the tool's
+//!   `outputSchema`, and the example its `structuredContent`
+//!
+//! Local `$ref`s are inlined, so the manifest stands on its own. Operations
+//! other than `GET` change things, which static files can't, and are left
+//! out, as are those without an example to answer with.
+
+use clap::Args;
+use serde_json::{Map, Value, json};
+use staticmcp_sse_lib::server::PathArgs;
+use staticmcp_sse_lib::{MCPBridge, create_data_source, manifest};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// How deep `$ref`s are followed; deeper ones, as in recursive schemas,
+/// become `{}`, which accepts anything.
+const MAX_REF_DEPTH: usize = 16;
+
+/// The methods left out as not reading anything.
+const OTHER_METHODS: [&str; 7] = ["put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Write a StaticMCP site for the GET endpoints of an OpenAPI document.
+#[derive(Debug, Clone, Args)]
+pub struct FromOpenApiArgs {
+    /// OpenAPI 3 document, in YAML or JSON
+    pub spec: PathBuf,
+
+    /// Directory to write the site to
+    #[arg(long, value_name = "DIR")]
+    pub out: PathBuf,
+
+    /// `serverInfo.name` [default: the document's `info.title`]
+    #[arg(long, value_name = "NAME")]
+    pub server_name: Option<String>,
+
+    /// Replace an existing `mcp.json` and tool files
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+/// A tool converted from one operation, with the call its example answers.
+struct Converted {
+    tool: Value,
+    call: HashMap<String, Value>,
+    result: Value,
+}
+
+pub async fn run(args: FromOpenApiArgs) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(&args.spec)
+        .map_err(|e| anyhow::anyhow!("{}: {e}", args.spec.display()))?;
+    // YAML is a superset of JSON, so both parse the same way.
+    let document: Value = serde_yaml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("{}: {e}", args.spec.display()))?;
+    match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => {}
+        _ if document.get("swagger").is_some() => anyhow::bail!(
+            "{}: Swagger 2.0 documents aren't supported; convert it to OpenAPI 3 first",
+            args.spec.display()
+        ),
+        _ => anyhow::bail!(
+            "{}: not an OpenAPI 3 document, which has an `openapi` version such as `3.1.0`",
+            args.spec.display()
+        ),
+    }
+    let manifest_path = args.out.join("mcp.json");
+    if manifest_path.exists() && !args.force {
+        anyhow::bail!(
+            "{} exists; pass --force to replace it",
+            manifest_path.display()
+        );
+    }
+
+    let mut tools = Vec::new();
+    let mut names = HashSet::new();
+    let mut skipped = 0;
+    let paths = document.get("paths").and_then(Value::as_object);
+    for (path, item) in paths.into_iter().flatten() {
+        let item = resolve(&document, item, 0);
+        skipped += OTHER_METHODS
+            .iter()
+            .filter(|method| item.get(**method).is_some())
+            .count();
+        let Some(operation) = item.get("get") else {
+            continue;
+        };
+        let name = unique(tool_name(path, operation), &mut names);
+        match convert(&document, path, &item, operation, name) {
+            Some(converted) => tools.push(converted),
+            None => skipped += 1,
+        }
+    }
+
+    let info = document.get("info");
+    let text = |key: &str| info.and_then(|info| info.get(key)).and_then(Value::as_str);
+    let mut manifest = json!({
+        "serverInfo": {
+            "name": args.server_name.as_deref().or(text("title")).unwrap_or("openapi"),
+            "version": text("version").unwrap_or("1.0.0"),
+        },
+        "capabilities": {
+            "tools": tools.iter().map(|converted| &converted.tool).collect::<Vec<_>>(),
+        },
+    });
+    if let Some(description) = text("description") {
+        manifest["instructions"] = json!(description);
+    }
+
+    // The files go where a bridge serving the site with the same flags looks.
+    let mut bridge = args
+        .paths
+        .apply(MCPBridge::builder(create_data_source(
+            args.out.to_string_lossy().into_owned(),
+        )))
+        .build();
+    bridge.manifest = Some(manifest::load(manifest.clone())?);
+    std::fs::create_dir_all(&args.out)?;
+    let mut written = 0;
+    for converted in &tools {
+        let name = converted.tool["name"].as_str().unwrap_or_default();
+        let relative = match bridge.tool_to_path(name, &converted.call) {
+            Ok(relative) => relative,
+            Err(e) => {
+                eprintln!("warning: tool {name}: its example call has no file: {e}");
+                continue;
+            }
+        };
+        let path = args.out.join(&relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            &path,
+            serde_json::to_string_pretty(&converted.result)? + "\n",
+        )?;
+        written += 1;
+    }
+    std::fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )?;
+    eprintln!(
+        "{}: {} tools and {written} response files written, {skipped} operations left out",
+        args.out.display(),
+        tools.len()
+    );
+    Ok(())
+}
+
+/// One GET operation as a tool, with its example call and result, or
+/// `None` when the document doesn't give them.
+fn convert(
+    document: &Value,
+    path: &str,
+    item: &Value,
+    operation: &Value,
+    name: String,
+) -> Option<Converted> {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut samples = HashMap::new();
+    let mut unsampled = None;
+    for parameter in parameters(document, item, operation) {
+        let Some(parameter_name) = parameter.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        // Headers and cookies aren't arguments a caller chooses.
+        let location = parameter.get("in").and_then(Value::as_str);
+        if !matches!(location, Some("path" | "query")) {
+            continue;
+        }
+        let mut schema = parameter
+            .get("schema")
+            .map(|schema| inline(document, schema, 0))
+            .unwrap_or_else(|| json!({ "type": "string" }));
+        if let Some(description) = parameter.get("description")
+            && let Some(schema) = schema.as_object_mut()
+        {
+            schema
+                .entry("description")
+                .or_insert_with(|| description.clone());
+        }
+        let is_required = location == Some("path")
+            || parameter.get("required").and_then(Value::as_bool) == Some(true);
+        if is_required {
+            required.push(parameter_name.to_string());
+            match sample(document, &parameter, &schema) {
+                Some(value) => {
+                    samples.insert(parameter_name.to_string(), value);
+                }
+                None => {
+                    unsampled.get_or_insert_with(|| parameter_name.to_string());
+                }
+            }
+        }
+        properties.insert(parameter_name.to_string(), schema);
+    }
+
+    let description = ["summary", "description"]
+        .iter()
+        .filter_map(|key| operation.get(*key).and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let mut tool = json!({
+        "name": name,
+        "description": if description.is_empty() { format!("GET {path}") } else { description },
+        "inputSchema": {
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        },
+        "annotations": { "readOnlyHint": true },
+    });
+    if let Some(summary) = operation.get("summary") {
+        tool["title"] = summary.clone();
+    }
+
+    let response = success_response(document, operation);
+    let schema = response
+        .and_then(|content| content.get("schema"))
+        .map(|schema| inline(document, schema, 0));
+    let structured = schema
+        .as_ref()
+        .is_some_and(|schema| schema.get("type").and_then(Value::as_str) == Some("object"));
+    if structured && let Some(schema) = &schema {
+        tool["outputSchema"] = schema.clone();
+    }
+    let example = response.and_then(|content| response_example(document, content, schema.as_ref()));
+    let example = match (example, unsampled) {
+        (Some(_), Some(parameter)) => {
+            eprintln!(
+                "warning: GET {path}: required parameter `{parameter}` has no example, so the operation is left out"
+            );
+            return None;
+        }
+        (None, _) => {
+            eprintln!(
+                "warning: GET {path}: no example JSON response, so the operation is left out"
+            );
+            return None;
+        }
+        (Some(example), None) => example,
+    };
+    let mut result = json!({
+        "content": [{
+            "type": "text",
+            "text": serde_json::to_string_pretty(&example).unwrap_or_default(),
+        }],
+    });
+    if structured && example.is_object() {
+        result["structuredContent"] = example;
+    }
+    Some(Converted {
+        tool,
+        call: samples,
+        result,
+    })
+}
+
+/// The operation's parameters, with its path item's ones it doesn't
+/// override.
+fn parameters(document: &Value, item: &Value, operation: &Value) -> Vec<Value> {
+    let list = |owner: &Value| -> Vec<Value> {
+        owner
+            .get("parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|parameter| resolve(document, parameter, 0))
+            .collect()
+    };
+    let mut parameters = list(operation);
+    for shared in list(item) {
+        let key =
+            |parameter: &Value| (parameter.get("name").cloned(), parameter.get("in").cloned());
+        if !parameters
+            .iter()
+            .any(|parameter| key(parameter) == key(&shared))
+        {
+            parameters.push(shared);
+        }
+    }
+    parameters
+}
+
+/// The JSON `content` entry of the operation's `200`, or else first `2xx`,
+/// response.
+fn success_response<'a>(document: &'a Value, operation: &'a Value) -> Option<&'a Value> {
+    let responses = operation.get("responses")?.as_object()?;
+    let response = responses.get("200").or_else(|| {
+        responses
+            .iter()
+            .find(|(status, _)| status.starts_with('2'))
+            .map(|(_, response)| response)
+    })?;
+    let response = follow(document, response, 0)?;
+    let content = response.get("content")?.as_object()?;
+    content.get("application/json").or_else(|| {
+        content
+            .iter()
+            .find(|(mime, _)| mime.ends_with("+json"))
+            .map(|(_, content)| content)
+    })
+}
+
+/// A response's example: its `example`, its first `examples` entry's
+/// `value`, or its schema's `example`.
+fn response_example(document: &Value, content: &Value, schema: Option<&Value>) -> Option<Value> {
+    if let Some(example) = content.get("example") {
+        return Some(inline(document, example, 0));
+    }
+    let first = content
+        .get("examples")
+        .and_then(Value::as_object)
+        .and_then(|examples| examples.values().next());
+    if let Some(value) = first.and_then(|example| follow(document, example, 0)?.get("value")) {
+        return Some(value.clone());
+    }
+    schema?.get("example").cloned()
+}
+
+/// A value for `parameter` in the example call: its `example`, its first
+/// `examples` entry, or its schema's `example`, `default` or first `enum`.
+fn sample(document: &Value, parameter: &Value, schema: &Value) -> Option<Value> {
+    if let Some(example) = parameter.get("example") {
+        return Some(example.clone());
+    }
+    let first = parameter
+        .get("examples")
+        .and_then(Value::as_object)
+        .and_then(|examples| examples.values().next());
+    if let Some(value) = first.and_then(|example| follow(document, example, 0)?.get("value")) {
+        return Some(value.clone());
+    }
+    ["/example", "/default", "/examples/0", "/enum/0"]
+        .iter()
+        .find_map(|pointer| schema.pointer(pointer))
+        .cloned()
+}
+
+/// The tool name for the operation: its `operationId`, or `get` and the
+/// path, with characters MCP doesn't allow in names replaced by `_`.
+fn tool_name(path: &str, operation: &Value) -> String {
+    let raw = match operation.get("operationId").and_then(Value::as_str) {
+        Some(id) => id.to_string(),
+        None => format!("get{}", path.replace(['{', '}'], "")),
+    };
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "_-.".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    name.trim_matches('_').to_string()
+}
+
+/// `name`, or `name_2` and up when it is taken.
+fn unique(name: String, names: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 2;
+    while !names.insert(candidate.clone()) {
+        candidate = format!("{name}_{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+/// `value`, or what its local `$ref` points to.
+fn follow<'a>(document: &'a Value, value: &'a Value, depth: usize) -> Option<&'a Value> {
+    match value.get("$ref").and_then(Value::as_str) {
+        Some(reference) if depth < MAX_REF_DEPTH => {
+            let target = document.pointer(reference.strip_prefix('#')?)?;
+            follow(document, target, depth + 1)
+        }
+        Some(_) => None,
+        None => Some(value),
+    }
+}
+
+/// [`follow`], cloned, with `{}` for a reference that can't be followed.
+fn resolve(document: &Value, value: &Value, depth: usize) -> Value {
+    follow(document, value, depth)
+        .cloned()
+        .unwrap_or_else(|| json!({}))
+}
+
+/// `value` with every local `$ref` within it replaced by its target,
+/// `depth` counting the references already followed to reach it.
+fn inline(document: &Value, value: &Value, depth: usize) -> Value {
+    match value {
+        Value::Object(object) if object.contains_key("$ref") => {
+            if depth >= MAX_REF_DEPTH {
+                return json!({});
+            }
+            let target = resolve(document, value, depth);
+            inline(document, &target, depth + 1)
+        }
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), inline(document, value, depth)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| inline(document, item, depth))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
@@ -0,0 +1,279 @@
+//! `staticmcp-bridge generate`: writes `mcp.json` for a directory laid out
+//! the way the bridge maps requests to files, inverting that mapping.
+//!
+//! - `resources/{path}.json` becomes the resource `file://{path}`
+//! - `tools/{name}.json` becomes a tool taking no arguments
+//! - `tools/{name}/{value}.json` becomes a tool taking one argument, `arg`,
+//!   whose schema lists each value found, percent-decoded, as an `enum`
+//! - `tools/{name}/{a}/{b}.json` becomes a tool taking `arg1` and `arg2`
+//!
+//! Entries already in the manifest are kept as they are, so descriptions
+//! and argument names written by hand survive regeneration.
+
+use clap::Args;
+use serde_json::{Map, Value, json};
+use staticmcp_sse_lib::resolver::decode_segment;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Build or update a StaticMCP directory's `mcp.json` from its files.
+#[derive(Debug, Clone, Args)]
+pub struct GenerateArgs {
+    /// StaticMCP directory
+    pub data_path: PathBuf,
+
+    /// `serverInfo.name` for a new manifest [default: the directory name]
+    #[arg(long, value_name = "NAME")]
+    pub server_name: Option<String>,
+
+    /// `serverInfo.version` for a new manifest
+    #[arg(long, value_name = "VERSION", default_value = "1.0.0")]
+    pub server_version: String,
+
+    /// Drop manifest entries whose files no longer exist
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Print the manifest instead of writing it
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+/// How calls of one tool were laid out on disk.
+#[derive(Default)]
+struct ToolFiles {
+    no_args: bool,
+    one_arg: BTreeSet<String>,
+    two_args: bool,
+}
+
+pub async fn run(args: GenerateArgs) -> anyhow::Result<()> {
+    let root = &args.data_path;
+    let manifest_path = root.join("mcp.json");
+    let mut manifest: Value = match std::fs::read_to_string(&manifest_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", manifest_path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => json!({}),
+        Err(e) => return Err(e.into()),
+    };
+    let Some(object) = manifest.as_object_mut() else {
+        anyhow::bail!("{}: expected an object", manifest_path.display());
+    };
+
+    object.entry("serverInfo").or_insert_with(|| {
+        let name = args.server_name.clone().unwrap_or_else(|| {
+            std::fs::canonicalize(root)
+                .ok()
+                .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "staticmcp".to_string())
+        });
+        json!({ "name": name, "version": args.server_version })
+    });
+    let capabilities = object
+        .entry("capabilities")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("capabilities: expected an object"))?;
+
+    let resources = scan_resources(root)?;
+    let tools = scan_tools(root)?;
+    let (resource_added, resource_pruned) = merge(
+        capabilities,
+        "resources",
+        "uri",
+        resources
+            .into_iter()
+            .map(|(path, content)| resource_entry(&path, &content)),
+        args.prune,
+    )?;
+    let (tool_added, tool_pruned) = merge(
+        capabilities,
+        "tools",
+        "name",
+        tools.iter().map(|(name, files)| tool_entry(name, files)),
+        args.prune,
+    )?;
+
+    let output = serde_json::to_string_pretty(&manifest)? + "\n";
+    if args.stdout {
+        print!("{output}");
+    } else {
+        std::fs::write(&manifest_path, output)?;
+        eprintln!(
+            "{}: {resource_added} resources and {tool_added} tools added, {} pruned",
+            manifest_path.display(),
+            resource_pruned + tool_pruned
+        );
+    }
+    Ok(())
+}
+
+/// Adds discovered entries the manifest doesn't declare yet and, with
+/// `prune`, drops declared ones that weren't discovered. Returns how many
+/// were added and removed.
+fn merge(
+    capabilities: &mut Map<String, Value>,
+    key: &str,
+    id_field: &str,
+    discovered: impl Iterator<Item = Value>,
+    prune: bool,
+) -> anyhow::Result<(usize, usize)> {
+    let entries = capabilities
+        .entry(key)
+        .or_insert_with(|| json!([]))
+        .as_array_mut()
+        .ok_or_else(|| anyhow::anyhow!("capabilities.{key}: expected an array"))?;
+    let id = |entry: &Value| {
+        entry
+            .get(id_field)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let discovered: Vec<Value> = discovered.collect();
+    let found: BTreeSet<String> = discovered.iter().filter_map(id).collect();
+    let before = entries.len();
+    if prune {
+        entries.retain(|entry| id(entry).is_some_and(|id| found.contains(&id)));
+    }
+    let pruned = before - entries.len();
+
+    let declared: BTreeSet<String> = entries.iter().filter_map(id).collect();
+    let mut added = 0;
+    for entry in discovered {
+        if id(&entry).is_some_and(|id| !declared.contains(&id)) {
+            entries.push(entry);
+            added += 1;
+        }
+    }
+    Ok((added, pruned))
+}
+
+/// `resources/{path}.json` files by `{path}`, with their parsed contents.
+fn scan_resources(root: &Path) -> anyhow::Result<BTreeMap<String, Value>> {
+    let mut resources = BTreeMap::new();
+    for (segments, file) in response_files(&root.join("resources"))? {
+        let content = read_json(&file).unwrap_or(Value::Null);
+        resources.insert(segments.join("/"), content);
+    }
+    Ok(resources)
+}
+
+fn scan_tools(root: &Path) -> anyhow::Result<BTreeMap<String, ToolFiles>> {
+    let mut tools: BTreeMap<String, ToolFiles> = BTreeMap::new();
+    for (segments, _) in response_files(&root.join("tools"))? {
+        let files = tools.entry(segments[0].clone()).or_default();
+        match segments.len() {
+            1 => files.no_args = true,
+            2 => {
+                let value = decode_segment(&segments[1]).unwrap_or_else(|| segments[1].clone());
+                files.one_arg.insert(value);
+            }
+            _ => files.two_args = true,
+        }
+    }
+    Ok(tools)
+}
+
+fn resource_entry(path: &str, content: &Value) -> Value {
+    // Files may hold a bare resource or a `contents` array, as read_resource
+    // accepts both.
+    let described = content
+        .get("contents")
+        .and_then(|contents| contents.get(0))
+        .unwrap_or(content);
+    let name = content
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path));
+    let mime_type = described
+        .get("mimeType")
+        .and_then(Value::as_str)
+        .unwrap_or("application/json");
+    json!({
+        "uri": format!("file://{path}"),
+        "name": name,
+        "mimeType": mime_type,
+    })
+}
+
+fn tool_entry(name: &str, files: &ToolFiles) -> Value {
+    let string = || json!({ "type": "string" });
+    let (properties, required) = if files.two_args {
+        (
+            json!({ "arg1": string(), "arg2": string() }),
+            json!(["arg1", "arg2"]),
+        )
+    } else if !files.one_arg.is_empty() {
+        let property = json!({ "type": "string", "enum": files.one_arg });
+        // A bare `tools/{name}.json` answers calls without the argument.
+        let required = if files.no_args {
+            json!([])
+        } else {
+            json!(["arg"])
+        };
+        (json!({ "arg": property }), required)
+    } else {
+        (json!({}), json!([]))
+    };
+    json!({
+        "name": name,
+        "inputSchema": {
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        },
+    })
+}
+
+/// JSON files under `dir`, precompressed ones included, as their path
+/// segments relative to `dir` without the extension, sorted.
+fn response_files(dir: &Path) -> anyhow::Result<Vec<(Vec<String>, PathBuf)>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        collect(dir, &mut Vec::new(), &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect(
+    dir: &Path,
+    prefix: &mut Vec<String>,
+    files: &mut Vec<(Vec<String>, PathBuf)>,
+) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            prefix.push(name);
+            collect(&path, prefix, files)?;
+            prefix.pop();
+            continue;
+        }
+        let stem = [".json", ".json.gz", ".json.br"]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix));
+        if let Some(stem) = stem {
+            let mut segments = prefix.clone();
+            segments.push(stem.to_string());
+            files.push((segments, path));
+        }
+    }
+    Ok(())
+}
+
+/// Parses a response file, decompressing `.gz` and `.br` ones.
+fn read_json(path: &Path) -> Option<Value> {
+    let bytes = std::fs::read(path).ok()?;
+    let name = path.to_string_lossy();
+    let encoding = staticmcp_sse_lib::precompressed::Encoding::FALLBACKS
+        .into_iter()
+        .find(|encoding| name.ends_with(encoding.suffix()));
+    let bytes = match encoding {
+        Some(encoding) => encoding.decode(&bytes).ok()?,
+        None => bytes,
+    };
+    serde_json::from_slice(&bytes).ok()
+}
@@ -0,0 +1,19 @@
+//! The `staticmcp-bridge` subcommands, each an argument struct and the `run`
+//! function carrying it out, shared with the single-purpose binaries.
+
+pub mod bench;
+pub mod call;
+pub mod config;
+pub mod doctor;
+pub mod from_openapi;
+pub mod generate;
+pub mod mirror;
+pub mod proxy;
+pub mod purge;
+pub mod read;
+pub mod serve;
+pub mod stdio;
+pub mod validate;
+
+/// The runtime flags every binary takes, read before its runtime starts.
+pub use staticmcp_sse_lib::server::RuntimeArgs;
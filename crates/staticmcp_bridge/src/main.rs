@@ -0,0 +1,74 @@
+use clap::{Parser, Subcommand};
+use staticmcp_bridge::{
+    RuntimeArgs, bench, call, config, doctor, from_openapi, generate, mirror, proxy, purge, read,
+    serve, stdio, validate,
+};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Connect MCP clients to StaticMCP sites.
+#[derive(Parser)]
+#[command(name = "staticmcp-bridge", version)]
+struct Cli {
+    /// TOML or YAML file of flag defaults; environment variables and flags
+    /// override it
+    #[arg(long, global = true, value_name = "PATH", env = config::CONFIG_ENV)]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    runtime: RuntimeArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve StaticMCP directories or URLs over HTTP and SSE
+    #[command(after_help = serve::EXAMPLES)]
+    Serve(serve::ServeArgs),
+    /// Proxy to any StaticMCP site named by a `url` query parameter
+    Proxy(proxy::ProxyArgs),
+    /// Serve one StaticMCP directory or URL over stdin and stdout
+    Stdio(stdio::StdioArgs),
+    /// Check a StaticMCP site for a malformed manifest and missing files
+    Validate(validate::ValidateArgs),
+    /// Write or update a StaticMCP directory's manifest from its files
+    Generate(generate::GenerateArgs),
+    /// Write a StaticMCP site for the GET endpoints of an OpenAPI document
+    FromOpenapi(from_openapi::FromOpenApiArgs),
+    /// Diagnose why a remote StaticMCP site can't be reached or served
+    Doctor(doctor::DoctorArgs),
+    /// Call a tool and print its result
+    Call(call::CallArgs),
+    /// Read a resource and print it
+    Read(read::ReadArgs),
+    /// Copy a remote StaticMCP site to a directory for offline serving
+    Mirror(mirror::MirrorArgs),
+    /// Load-test a running bridge and report throughput and latency
+    Bench(bench::BenchArgs),
+    /// Evict cached files from a running bridge
+    Purge(purge::PurgeArgs),
+}
+
+fn main() -> anyhow::Result<ExitCode> {
+    let cli = config::parse::<Cli>();
+    cli.runtime.build()?.block_on(run(cli.command))
+}
+
+async fn run(command: Command) -> anyhow::Result<ExitCode> {
+    match command {
+        Command::Serve(args) => serve::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::Proxy(args) => proxy::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::Stdio(args) => stdio::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::Validate(args) => validate::run(args).await,
+        Command::Call(args) => call::run(args).await,
+        Command::Read(args) => read::run(args).await,
+        Command::Mirror(args) => mirror::run(args).await,
+        Command::Bench(args) => bench::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::Purge(args) => purge::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::Doctor(args) => doctor::run(args).await,
+        Command::Generate(args) => generate::run(args).await.map(|()| ExitCode::SUCCESS),
+        Command::FromOpenapi(args) => from_openapi::run(args).await.map(|()| ExitCode::SUCCESS),
+    }
+}
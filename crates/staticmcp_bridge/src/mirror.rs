@@ -0,0 +1,239 @@
+//! `staticmcp-bridge mirror`: downloads a remote StaticMCP site into a
+//! directory the fixed bridge can serve offline.
+
+use clap::Args;
+use futures::{StreamExt, stream};
+use serde_json::Value;
+use staticmcp_sse_lib::precompressed::Encoding;
+use staticmcp_sse_lib::resolver::url_path;
+use staticmcp_sse_lib::server::PathArgs;
+use staticmcp_sse_lib::server::progress::Progress;
+use staticmcp_sse_lib::{MCPBridge, ToolDef, create_data_source, manifest};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+/// Copy a remote StaticMCP site, every file its manifest references, to a directory.
+#[derive(Debug, Clone, Args)]
+pub struct MirrorArgs {
+    /// StaticMCP URL, the directory holding `mcp.json`
+    pub url: String,
+
+    /// Directory to write the copy to; created when missing
+    pub dest: PathBuf,
+
+    /// How many files to download at once
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 8,
+        env = "MIRROR_CONCURRENCY"
+    )]
+    pub concurrency: usize,
+
+    /// Most response files to fetch for one tool
+    #[arg(long, value_name = "N", default_value_t = 1000)]
+    pub max_calls: usize,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+enum Fetched {
+    Saved(u64),
+    Missing,
+}
+
+/// Exits `1` when any referenced file is missing or couldn't be fetched;
+/// the rest of the copy is still written. Failures are printed as they
+/// happen, and progress every [`Progress::INTERVAL`].
+pub async fn run(args: MirrorArgs) -> anyhow::Result<ExitCode> {
+    let base = args.url.trim_end_matches('/').to_string();
+    if !base.starts_with("http://") && !base.starts_with("https://") {
+        anyhow::bail!("`{}` is not an http or https URL", args.url);
+    }
+    let client = reqwest::Client::new();
+
+    let raw = client
+        .get(format!("{base}/mcp.json"))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let manifest: Value =
+        serde_json::from_slice(&raw).map_err(|e| anyhow::anyhow!("{base}/mcp.json: {e}"))?;
+    let parsed =
+        manifest::load(manifest.clone()).map_err(|e| anyhow::anyhow!("{base}/mcp.json: {e}"))?;
+    std::fs::create_dir_all(&args.dest)?;
+    std::fs::write(args.dest.join("mcp.json"), &raw)?;
+
+    let mut bridge = args
+        .paths
+        .apply(MCPBridge::builder(create_data_source(base.clone())))
+        .build();
+    bridge.manifest = Some(parsed);
+    let mut paths = BTreeSet::new();
+    for resource in bridge
+        .resources()
+        .iter()
+        .filter(|resource| !resource.upstream)
+    {
+        paths.insert(bridge.uri_to_path(&resource.uri));
+    }
+    for tool in bridge.tools() {
+        let name = &tool.name;
+        // A computed tool answers from its script or module, copied as is.
+        if let Some(handler) = &tool.handler {
+            paths.insert(handler.clone());
+            continue;
+        }
+        if tool.upstream {
+            continue;
+        }
+        let calls = argument_combinations(tool, args.max_calls);
+        if calls.is_empty() {
+            eprintln!(
+                "warning: tool {name}: a required argument has no enum, examples or default, \
+                 so its responses can't be listed"
+            );
+        }
+        for call in calls {
+            paths.insert(bridge.tool_to_path(name, &call)?);
+        }
+    }
+    for template in bridge.resource_templates() {
+        eprintln!(
+            "warning: resource template {}: its files can't be listed, so none are mirrored",
+            template.uri_template
+        );
+    }
+    for prompt in bridge.prompts() {
+        match &prompt.path_template {
+            Some(_) => eprintln!(
+                "warning: prompt {}: its files depend on its arguments, so none are mirrored",
+                prompt.name
+            ),
+            None => {
+                paths.insert(bridge.prompt_to_path(prompt, &HashMap::new())?);
+            }
+        }
+    }
+    if bridge
+        .get_manifest()
+        .and_then(|manifest| manifest.capabilities.as_ref())
+        .is_some_and(|capabilities| capabilities.completions.is_some())
+    {
+        eprintln!("warning: completion files can't be listed, so none are mirrored");
+    }
+
+    let mut progress = Progress::new(paths.len());
+    let mut fetches = stream::iter(paths)
+        .map(|path| {
+            let (client, base, dest) = (&client, &base, &args.dest);
+            async move {
+                let result = fetch(client, base, dest, &path).await;
+                (path, result)
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1));
+    while let Some((path, result)) = fetches.next().await {
+        let saved = match result {
+            Ok(Fetched::Saved(size)) => Some(size),
+            Ok(Fetched::Missing) => {
+                eprintln!("error: {path}: not found");
+                None
+            }
+            Err(e) => {
+                eprintln!("error: {path}: {e}");
+                None
+            }
+        };
+        if progress.record(saved) {
+            eprintln!("{progress}");
+        }
+    }
+    eprintln!(
+        "{}: {} files ({} bytes) written, {} failed, in {:.1}s",
+        args.dest.display(),
+        progress.loaded,
+        progress.bytes,
+        progress.failed,
+        progress.elapsed().as_secs_f64()
+    );
+    Ok(if progress.failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Downloads `path`, or its first precompressed sibling the site serves,
+/// keeping the stored bytes and file name.
+async fn fetch(
+    client: &reqwest::Client,
+    base: &str,
+    dest: &Path,
+    path: &str,
+) -> anyhow::Result<Fetched> {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        anyhow::bail!("refusing to write outside the destination");
+    }
+
+    let suffixes = std::iter::once("").chain(Encoding::FALLBACKS.map(Encoding::suffix));
+    for suffix in suffixes {
+        let response = client
+            .get(format!("{base}/{}{suffix}", url_path(path)))
+            .header(reqwest::header::ACCEPT_ENCODING, "identity")
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            continue;
+        }
+        let body = response.error_for_status()?.bytes().await?;
+        let target = dest.join(format!("{path}{suffix}"));
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&target, &body).await?;
+        return Ok(Fetched::Saved(body.len() as u64));
+    }
+    Ok(Fetched::Missing)
+}
+
+/// Every call of `tool` its schema lets us list: each combination of its
+/// required arguments' `enum`, `examples` and `default` values, at most
+/// `limit` of them. Empty when a required argument offers no values.
+fn argument_combinations(tool: &ToolDef, limit: usize) -> Vec<HashMap<String, Value>> {
+    let mut calls = vec![HashMap::new()];
+    for property in tool.required() {
+        let schema = tool.property(property);
+        let mut values: Vec<&Value> = Vec::new();
+        let offered = ["enum", "examples"]
+            .iter()
+            .filter_map(|key| schema?.get(key)?.as_array())
+            .flatten()
+            .chain(schema.and_then(|schema| schema.get("default")));
+        for value in offered {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        calls = calls
+            .iter()
+            .flat_map(|call| {
+                values.iter().map(move |value| {
+                    let mut call = call.clone();
+                    call.insert(property.to_string(), (*value).clone());
+                    call
+                })
+            })
+            .take(limit)
+            .collect();
+    }
+    calls
+}
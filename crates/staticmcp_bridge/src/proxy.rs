@@ -0,0 +1,1353 @@
+//! `staticmcp-bridge proxy`: any StaticMCP site named by the `url` query
+//! parameter, or several at once answering as one.
+
+use axum::response::sse::Event;
+use axum::{
+    Json, Router,
+    extract::{NestedPath, RawQuery, State},
+    http::{HeaderMap, StatusCode, Uri, header},
+    response::{IntoResponse, Response, Sse},
+    routing::get,
+};
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use serde_json::{Value, json};
+use staticmcp_sse_lib::audit::AuditSink;
+use staticmcp_sse_lib::client::ClientInfo;
+use staticmcp_sse_lib::policy::AccessPolicy;
+use staticmcp_sse_lib::server::admin::AdminArgs;
+use staticmcp_sse_lib::server::body::JsonRpc;
+use staticmcp_sse_lib::server::envelope::McpEnvelope;
+use staticmcp_sse_lib::server::fixtures::Fixtures;
+use staticmcp_sse_lib::server::principal::{self, Principal};
+use staticmcp_sse_lib::server::session::{SessionArgs, Sessions};
+use staticmcp_sse_lib::server::stats::TargetStats;
+use staticmcp_sse_lib::server::{PathArgs, ServerArgs, health, methods, request_id, status};
+use staticmcp_sse_lib::{MCPBridge, MCPError, MCPRequest, MCPResponse, RemoteDataSource};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Clone)]
+struct AppState {
+    audit: Option<Arc<dyn AuditSink>>,
+    policy: Option<Arc<AccessPolicy>>,
+    paths: PathArgs,
+    presets: HashMap<String, Vec<String>>,
+    allowed: Vec<AllowedTargets>,
+    /// The `/events` sessions, with the sites each was opened for.
+    sessions: Arc<Sessions<Aggregate>>,
+    stats: Arc<TargetStats>,
+    bridges: Arc<BridgeCache>,
+    fixtures: Option<Arc<Fixtures>>,
+    /// Shared by every site's bridge, so reopening a site reuses its
+    /// connections.
+    client: reqwest::Client,
+}
+
+/// Proxy MCP clients to any StaticMCP site given in the `url` query parameter.
+#[derive(Debug, Clone, Args)]
+#[command(after_help = "Examples:
+  staticmcp-bridge proxy
+  staticmcp-bridge proxy --preset docs=https://docs.example.com/mcp,https://api.example.com/mcp")]
+pub struct ProxyArgs {
+    /// Port to listen on
+    #[arg(default_value_t = 3000, env = "PORT")]
+    pub port: u16,
+
+    /// Name a list of sites that `?preset=NAME` aggregates, each optionally
+    /// given as PREFIX=URL
+    #[arg(long, value_name = "NAME=URL,...", value_parser = parse_preset)]
+    pub preset: Vec<Preset>,
+
+    /// Limit the sites a caller may proxy to URLs under these prefixes, as
+    /// `CALLER=PREFIX,...`; CALLER is an API key, HMAC key or token subject,
+    /// `scope:NAME` for tokens with that scope, or `*` for callers no other
+    /// entry names
+    #[arg(long, value_name = "CALLER=PREFIX,...", value_parser = parse_allowed_targets)]
+    pub allow_targets: Vec<AllowedTargets>,
+
+    /// Serve callers that don't authenticate, letting anyone who reaches
+    /// the proxy have it fetch the sites they name
+    #[arg(long, env = "ALLOW_ANONYMOUS", value_parser = BoolishValueParser::new())]
+    pub allow_anonymous: bool,
+
+    /// How long a site's manifest is reused before it is downloaded again,
+    /// in the background while the old one keeps answering; `0s` downloads
+    /// it for every request
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "1m",
+        value_parser = humantime::parse_duration,
+        env = "MANIFEST_TTL"
+    )]
+    pub manifest_ttl: Duration,
+
+    #[command(flatten)]
+    pub sessions: SessionArgs,
+
+    #[command(flatten)]
+    pub admin: AdminArgs,
+
+    #[command(flatten)]
+    pub server: ServerArgs,
+}
+
+/// A `--preset` entry.
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    pub targets: Vec<String>,
+}
+
+fn parse_preset(value: &str) -> Result<Preset, String> {
+    let (name, targets) = value.split_once('=').ok_or(
+        "expected NAME=URL,..., such as docs=https://docs.example.com/mcp,https://api.example.com/mcp",
+    )?;
+    let targets: Vec<String> = targets
+        .split(',')
+        .map(str::trim)
+        .filter(|target| !target.is_empty())
+        .map(str::to_string)
+        .collect();
+    if name.is_empty() || targets.is_empty() {
+        return Err("both the name and at least one URL must be given".into());
+    }
+    Ok(Preset {
+        name: name.to_string(),
+        targets,
+    })
+}
+
+/// An `--allow-targets` entry.
+#[derive(Debug, Clone)]
+pub struct AllowedTargets {
+    pub caller: String,
+    pub prefixes: Vec<reqwest::Url>,
+}
+
+fn parse_allowed_targets(value: &str) -> Result<AllowedTargets, String> {
+    let (caller, prefixes) = value.split_once('=').ok_or(
+        "expected CALLER=PREFIX,..., such as ci=https://docs.example.com/,https://example.com/mcp",
+    )?;
+    let prefixes = prefixes
+        .split(',')
+        .map(str::trim)
+        .filter(|prefix| !prefix.is_empty())
+        .map(|prefix| reqwest::Url::parse(prefix).map_err(|e| format!("`{prefix}`: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if caller.is_empty() || prefixes.is_empty() {
+        return Err("both the caller and at least one URL prefix must be given".into());
+    }
+    Ok(AllowedTargets {
+        caller: caller.to_string(),
+        prefixes,
+    })
+}
+
+impl AllowedTargets {
+    /// Whether the entry names `caller`, by subject or by a scope it holds.
+    /// `*` entries name no one; they are for callers no entry names.
+    fn names(&self, caller: Option<&Principal>) -> bool {
+        let Some(caller) = caller else {
+            return false;
+        };
+        match self.caller.strip_prefix("scope:") {
+            Some(scope) => caller.scopes.iter().any(|held| held == scope),
+            None => self.caller == caller.subject,
+        }
+    }
+
+    /// Whether `url` is under one of the prefixes: the same scheme, host
+    /// and port, and a path at or below the prefix's.
+    fn allows(&self, url: &str) -> bool {
+        let Ok(url) = reqwest::Url::parse(url) else {
+            return false;
+        };
+        self.prefixes.iter().any(|prefix| {
+            let path = prefix.path().trim_end_matches('/');
+            url.scheme() == prefix.scheme()
+                && url.host_str() == prefix.host_str()
+                && url.port_or_known_default() == prefix.port_or_known_default()
+                && (url.path() == path
+                    || url
+                        .path()
+                        .strip_prefix(path)
+                        .is_some_and(|rest| rest.starts_with('/')))
+        })
+    }
+}
+
+/// A site a request is answered from, and the prefix its names get when
+/// another site of the request declares them too.
+#[derive(Debug, Clone)]
+struct Target {
+    prefix: String,
+    url: String,
+}
+
+/// The site the request's path names, then those named by its `url` and
+/// `preset` parameters, in order. Each is `URL` or `PREFIX=URL`; without a
+/// prefix it is the first label of the URL's host, numbered when two sites
+/// would share one.
+fn targets(
+    state: &AppState,
+    site: Option<&str>,
+    query: Option<&str>,
+) -> Result<Vec<Target>, String> {
+    let mut specs: Vec<String> = site.map(str::to_string).into_iter().collect();
+    for (key, value) in form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        match &*key {
+            "url" => specs.push(value.into_owned()),
+            "preset" => specs.extend(
+                state
+                    .presets
+                    .get(&*value)
+                    .ok_or_else(|| format!("unknown preset `{value}`"))?
+                    .iter()
+                    .cloned(),
+            ),
+            _ => {}
+        }
+    }
+    if specs.is_empty() {
+        return Err("name the site with a `url` or `preset` query parameter".into());
+    }
+
+    let mut targets: Vec<Target> = Vec::with_capacity(specs.len());
+    for (position, spec) in specs.into_iter().enumerate() {
+        let (prefix, url) = match spec.split_once('=') {
+            Some((prefix, url)) if url.contains("://") && is_prefix(prefix) => {
+                (prefix.to_string(), url.to_string())
+            }
+            _ => (host_prefix(&spec), spec),
+        };
+        let prefix = if targets.iter().any(|target| target.prefix == prefix) {
+            format!("{prefix}_{}", position + 1)
+        } else {
+            prefix
+        };
+        targets.push(Target { prefix, url });
+    }
+    Ok(targets)
+}
+
+/// Whether `caller` may use the site `url` under the `--allow-targets`
+/// entries `allowed`: any site when there are none, otherwise one the
+/// entries naming the caller cover, or the `*` entries for a caller none
+/// names. A caller that falls to no entry may use no site.
+fn may_use(allowed: &[AllowedTargets], caller: Option<&Principal>, url: &str) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    let mut rules: Vec<&AllowedTargets> =
+        allowed.iter().filter(|rule| rule.names(caller)).collect();
+    if rules.is_empty() {
+        rules = allowed.iter().filter(|rule| rule.caller == "*").collect();
+    }
+    rules.iter().any(|rule| rule.allows(url))
+}
+
+/// The sites the path and query name, once the caller is found to be
+/// allowed each of them.
+fn requested(
+    state: &AppState,
+    site: Option<&str>,
+    query: Option<&str>,
+) -> Result<Vec<Target>, (StatusCode, String)> {
+    let targets =
+        targets(state, site, query).map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    let caller = principal::current();
+    if let Some(target) = targets
+        .iter()
+        .find(|target| !may_use(&state.allowed, caller.as_ref(), &target.url))
+    {
+        warn!(
+            caller = caller.as_ref().map(|caller| caller.subject.as_str()),
+            target_url = %target.url,
+            "refused target"
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("{} is not a site you may proxy to", target.url),
+        ));
+    }
+    Ok(targets)
+}
+
+/// The site `/bridge/HOST/PATH.../sse` names, given `HOST/PATH...`: the URL
+/// `https://HOST/PATH...`, or `http://` when HOST is written `http:HOST`,
+/// for clients that can't add a `url` parameter to the address they're
+/// given. Segments stay percent-encoded as sent; ones that would climb out
+/// of the path or split it differently once decoded are refused.
+fn path_site(path: &str) -> Result<String, String> {
+    let (scheme, path) = match path.strip_prefix("http:") {
+        Some(path) => ("http", path),
+        None => ("https", path),
+    };
+    let (host, rest) = path.split_once('/').unwrap_or((path, ""));
+    // `@` would make the host a username, and an encoded host could decode
+    // to anything.
+    if host.is_empty() || host.contains(['@', '%', '\\']) {
+        return Err(format!("`{host}` is not a host"));
+    }
+    let origin = reqwest::Url::parse(&format!("{scheme}://{host}/"))
+        .map_err(|e| format!("`{host}` is not a host: {e}"))?;
+    if !rest.is_empty() {
+        for segment in rest.split('/') {
+            let decoded = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+            if matches!(&*decoded, "" | "." | "..") || decoded.contains(['/', '\\']) {
+                return Err(format!("`{segment}` is not a valid path segment"));
+            }
+        }
+    }
+    let url = reqwest::Url::parse(&format!("{scheme}://{host}/{rest}"))
+        .map_err(|e| format!("`{host}/{rest}` is not a site URL: {e}"))?;
+    if url.origin() != origin.origin() {
+        return Err(format!("`{host}` is not a host"));
+    }
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+fn is_prefix(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+}
+
+/// `docs` for `https://docs.example.com/mcp`.
+fn host_prefix(url: &str) -> String {
+    let label = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| Some(url.host_str()?.split('.').next()?.to_string()))
+        .unwrap_or_default();
+    if is_prefix(&label) {
+        label
+    } else {
+        "site".to_string()
+    }
+}
+
+/// The name or URI `name` of a site's entry under `prefix`.
+fn prefixed(prefix: &str, name: &str) -> String {
+    format!("{prefix}__{name}")
+}
+
+/// Bridges to several sites answering as one MCP server. Lists are merged,
+/// and a name declared by more than one site is listed under each site's
+/// prefix. Calls and reads go to the site that declared what they name; one
+/// naming nothing listed is tried on each site in order.
+struct Aggregate {
+    sites: Vec<(Target, Arc<MCPBridge>)>,
+    stats: Arc<TargetStats>,
+    fixtures: Option<Arc<Fixtures>>,
+}
+
+/// A listed entry, with the site it came from and its name there.
+struct Entry {
+    site: usize,
+    name: String,
+    value: Value,
+}
+
+impl Aggregate {
+    /// The answer to `request`, from the only site when there is one, or
+    /// the one recorded for these sites by `--record`.
+    async fn answer(&self, request: MCPRequest) -> MCPResponse {
+        let Some(fixtures) = &self.fixtures else {
+            return self.live(request).await;
+        };
+        fixtures
+            .answer(&self.scope(), request, |request| self.live(request))
+            .await
+    }
+
+    async fn live(&self, request: MCPRequest) -> MCPResponse {
+        match self.sites.as_slice() {
+            [_] => self.ask(0, request).await,
+            _ => self.handle_request(request).await,
+        }
+    }
+
+    /// The sites, as `PREFIX=URL` in order, that answers are recorded under.
+    fn scope(&self) -> String {
+        let sites: Vec<String> = self
+            .sites
+            .iter()
+            .map(|(target, _)| format!("{}={}", target.prefix, target.url))
+            .collect();
+        sites.join(",")
+    }
+
+    /// The answer of the site numbered `site` to `request`, recorded in
+    /// its stats.
+    async fn ask(&self, site: usize, request: MCPRequest) -> MCPResponse {
+        let (target, bridge) = &self.sites[site];
+        let started = Instant::now();
+        let response = bridge.handle_request(request).await;
+        let error = response.error.as_ref().map(|error| error.code);
+        self.stats.request(&target.url, started.elapsed(), error);
+        response
+    }
+
+    async fn handle_request(&self, request: MCPRequest) -> MCPResponse {
+        let id = request.id.clone();
+        let params = request.params.clone().unwrap_or(json!({}));
+        match request.method.as_str() {
+            "initialize" => self.initialize(request).await,
+            "tools/list" => match self.entries("tools/list", "tools", "name").await {
+                Ok(tools) => Self::list(id, "tools", tools),
+                Err(error) => error,
+            },
+            "resources/list" => match self.entries("resources/list", "resources", "uri").await {
+                Ok(resources) => Self::list(id, "resources", resources),
+                Err(error) => error,
+            },
+            // Templates are matched by each site against the URIs it is
+            // sent, so they are listed as the sites declare them.
+            "resources/templates/list" => {
+                match self
+                    .entries("resources/templates/list", "resourceTemplates", "")
+                    .await
+                {
+                    Ok(templates) => Self::list(id, "resourceTemplates", templates),
+                    Err(error) => error,
+                }
+            }
+            "tools/call" => {
+                let tools = match self.entries("tools/list", "tools", "name").await {
+                    Ok(tools) => tools,
+                    Err(error) => return error,
+                };
+                let response = self.route(&request, &params, "name", &tools).await;
+                if response.error.as_ref().is_some_and(Self::unknown_tool) {
+                    let names: Vec<&Value> = tools.iter().map(|tool| &tool.value["name"]).collect();
+                    let name = params["name"].as_str().unwrap_or_default();
+                    return Self::error(
+                        id,
+                        -32602,
+                        format!("Unknown tool: {name}"),
+                        json!({ "tools": names }),
+                    );
+                }
+                response
+            }
+            "resources/read" => match self.entries("resources/list", "resources", "uri").await {
+                Ok(resources) => self.route(&request, &params, "uri", &resources).await,
+                Err(error) => error,
+            },
+            _ => self.ask(0, request).await,
+        }
+    }
+
+    /// The first site's answer, announcing every capability any site has.
+    async fn initialize(&self, request: MCPRequest) -> MCPResponse {
+        let mut capabilities = serde_json::Map::new();
+        let mut first = None;
+        for site in 0..self.sites.len() {
+            let response = self.ask(site, request.clone()).await;
+            if let Some(Value::Object(site)) = response
+                .result
+                .as_ref()
+                .and_then(|result| result.get("capabilities"))
+            {
+                for (key, value) in site {
+                    capabilities.entry(key.clone()).or_insert(value.clone());
+                }
+            }
+            first.get_or_insert(response);
+        }
+        let mut response = first.expect("an aggregate has at least one site");
+        if let Some(result) = response.result.as_mut() {
+            result["capabilities"] = Value::Object(capabilities);
+        }
+        response
+    }
+
+    /// Every site's entries under `field` of its answer to `method`, names
+    /// under `key` prefixed where sites conflict; an empty `key` keeps them
+    /// all as declared.
+    async fn entries(
+        &self,
+        method: &str,
+        field: &str,
+        key: &str,
+    ) -> Result<Vec<Entry>, MCPResponse> {
+        let mut entries = Vec::new();
+        for site in 0..self.sites.len() {
+            let request = MCPRequest {
+                jsonrpc: "2.0".to_string(),
+                id: None,
+                method: method.to_string(),
+                params: None,
+            };
+            let response = self.ask(site, request).await;
+            if response.error.is_some() {
+                return Err(response);
+            }
+            let values = response
+                .result
+                .and_then(|mut result| result.get_mut(field).map(Value::take));
+            for value in values
+                .and_then(|values| values.as_array().cloned())
+                .unwrap_or_default()
+            {
+                let name = value[key].as_str().unwrap_or_default().to_string();
+                entries.push(Entry { site, name, value });
+            }
+        }
+        if key.is_empty() {
+            return Ok(entries);
+        }
+
+        let mut sites_declaring: HashMap<&str, Vec<usize>> = HashMap::new();
+        for entry in &entries {
+            let sites = sites_declaring.entry(entry.name.as_str()).or_default();
+            if !sites.contains(&entry.site) {
+                sites.push(entry.site);
+            }
+        }
+        let conflicts: Vec<String> = sites_declaring
+            .into_iter()
+            .filter(|(_, sites)| sites.len() > 1)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for entry in &mut entries {
+            if conflicts.contains(&entry.name) {
+                entry.value[key] = json!(prefixed(&self.sites[entry.site].0.prefix, &entry.name));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn list(id: Option<Value>, field: &str, entries: Vec<Entry>) -> MCPResponse {
+        let values: Vec<Value> = entries.into_iter().map(|entry| entry.value).collect();
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({ field: values })),
+            error: None,
+        }
+    }
+
+    /// `request` sent to the site that listed the entry its `params[key]`
+    /// names, under that site's own name; or, when none did, to each site
+    /// in turn until one knows it.
+    async fn route(
+        &self,
+        request: &MCPRequest,
+        params: &Value,
+        key: &str,
+        entries: &[Entry],
+    ) -> MCPResponse {
+        let name = params[key].as_str().unwrap_or_default();
+        if let Some(entry) = entries.iter().find(|entry| entry.value[key] == name) {
+            let mut params = params.clone();
+            params[key] = json!(entry.name);
+            let request = MCPRequest {
+                params: Some(params),
+                ..request.clone()
+            };
+            return self.ask(entry.site, request).await;
+        }
+
+        let mut response = None;
+        for site in 0..self.sites.len() {
+            let answer = self.ask(site, request.clone()).await;
+            let unknown = answer
+                .error
+                .as_ref()
+                .is_some_and(|error| error.code == -32002 || Self::unknown_tool(error));
+            if !unknown {
+                return answer;
+            }
+            response.get_or_insert(answer);
+        }
+        response.expect("an aggregate has at least one site")
+    }
+
+    fn unknown_tool(error: &MCPError) -> bool {
+        error.code == -32602 && error.message.starts_with("Unknown tool")
+    }
+
+    fn error(id: Option<Value>, code: i32, message: String, data: Value) -> MCPResponse {
+        let mut error = MCPError {
+            code,
+            message,
+            data: Some(data),
+        };
+        if let Some(request_id) = request_id::current() {
+            error.attach_request_id(request_id);
+        }
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Sites whose bridges are kept; opening another forgets the one least
+/// recently used.
+const MAX_CACHED_SITES: usize = 256;
+
+/// Bridges to recently used sites, by URL, so the `initialize` and
+/// `tools/list` a client sends one after the other don't each download the
+/// site's `mcp.json`.
+struct BridgeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedBridge>>,
+}
+
+struct CachedBridge {
+    bridge: Arc<MCPBridge>,
+    loaded: Instant,
+    used: Instant,
+    refreshing: bool,
+}
+
+impl BridgeCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The cached bridge to `url`, and whether it is older than the TTL and
+    /// the caller should reopen it; only the first caller to find it so is
+    /// told to.
+    fn get(&self, url: &str) -> Option<(Arc<MCPBridge>, bool)> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(url)?;
+        entry.used = Instant::now();
+        let stale = !entry.refreshing && entry.loaded.elapsed() >= self.ttl;
+        entry.refreshing |= stale;
+        Some((entry.bridge.clone(), stale))
+    }
+
+    fn insert(&self, url: &str, bridge: Arc<MCPBridge>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED_SITES && !entries.contains_key(url) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(url, _)| url.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        let now = Instant::now();
+        entries.insert(
+            url.to_string(),
+            CachedBridge {
+                bridge,
+                loaded: now,
+                used: now,
+                refreshing: false,
+            },
+        );
+    }
+
+    /// Keeps answering from the bridge to `url` after reopening it failed,
+    /// trying again once another TTL has passed.
+    fn keep(&self, url: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(url) {
+            entry.loaded = Instant::now();
+            entry.refreshing = false;
+        }
+    }
+}
+
+/// A bridge to `url`, from the cache while its manifest is fresh. A stale
+/// one still answers while the site is reopened in the background.
+async fn open(state: &Arc<AppState>, url: &str) -> anyhow::Result<Arc<MCPBridge>> {
+    // Answers come from the fixtures, so the site is never fetched.
+    if state.fixtures.as_ref().is_some_and(|f| f.replaying()) {
+        return Ok(Arc::new(bridge(state, url)));
+    }
+    if let Some((bridge, stale)) = state.bridges.get(url) {
+        if stale {
+            let state = state.clone();
+            let url = url.to_string();
+            tokio::spawn(async move {
+                match connect(&state, &url).await {
+                    Ok(bridge) => state.bridges.insert(&url, Arc::new(bridge)),
+                    Err(e) => {
+                        warn!(target_url = %url, error = %e, "failed to refresh manifest, keeping the cached one");
+                        state.bridges.keep(&url);
+                    }
+                }
+            });
+        }
+        return Ok(bridge);
+    }
+    let bridge = Arc::new(connect(state, url).await?);
+    state.bridges.insert(url, bridge.clone());
+    Ok(bridge)
+}
+
+/// A new bridge to `url`, its manifest loaded.
+async fn connect(state: &AppState, url: &str) -> anyhow::Result<MCPBridge> {
+    let mut bridge = bridge(state, url);
+    let started = Instant::now();
+    let result = bridge.initialize().await;
+    state.stats.connect(url, started.elapsed(), result.is_ok());
+    result?;
+    Ok(bridge)
+}
+
+/// A bridge to `url` with the server's audit, policy and path settings,
+/// not yet initialized.
+fn bridge(state: &AppState, url: &str) -> MCPBridge {
+    let mut builder =
+        state
+            .paths
+            .apply(MCPBridge::builder(Box::new(RemoteDataSource::with_client(
+                url.to_string(),
+                state.client.clone(),
+            ))));
+    if let Some(sink) = &state.audit {
+        builder = builder.audit_sink(sink.clone());
+    }
+    if let Some(policy) = &state.policy {
+        builder = builder.access_policy(policy.clone());
+    }
+    builder.build()
+}
+
+/// Bridges to each of `targets`, or the URL of the first that fails to
+/// open and why.
+async fn open_all(
+    state: &Arc<AppState>,
+    targets: Vec<Target>,
+) -> Result<Aggregate, (String, anyhow::Error)> {
+    let mut sites = Vec::with_capacity(targets.len());
+    for target in targets {
+        match open(state, &target.url).await {
+            Ok(bridge) => sites.push((target, bridge)),
+            Err(e) => {
+                warn!(target_url = %target.url, error = %e, "failed to create remote bridge");
+                return Err((target.url, e));
+            }
+        }
+    }
+    Ok(Aggregate {
+        sites,
+        stats: state.stats.clone(),
+        fixtures: state.fixtures.clone(),
+    })
+}
+
+/// The `session` query parameter, naming the `/events` session a message is
+/// posted to.
+fn session_id(query: Option<&str>) -> Option<String> {
+    form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .find(|(key, _)| key == "session")
+        .map(|(_, value)| value.into_owned())
+}
+
+async fn mcp_sse_endpoint(
+    State(state): State<Arc<AppState>>,
+    RawQuery(query): RawQuery,
+    JsonRpc(request): JsonRpc,
+) -> Response {
+    post_message(state, None, query, request).await
+}
+
+/// `POST /bridge/HOST/PATH.../sse`: [`mcp_sse_endpoint`] for the site the
+/// path names.
+async fn bridge_sse_endpoint(
+    State(state): State<Arc<AppState>>,
+    uri: Uri,
+    RawQuery(query): RawQuery,
+    JsonRpc(request): JsonRpc,
+) -> Response {
+    let envelope = McpEnvelope {
+        id: request.id.clone(),
+        method: Some(request.method.clone()),
+    };
+    let Some(path) = bridge_path(&uri, "/sse") else {
+        return status::no_such_endpoint(&envelope, uri.path());
+    };
+    match path_site(path) {
+        Ok(site) => post_message(state, Some(site), query, request).await,
+        Err(message) => envelope.reject(StatusCode::BAD_REQUEST, -32602, message, None),
+    }
+}
+
+/// What the `/bridge/...` routes answer, `/sse` and `/events` alike.
+const BRIDGE_METHODS: &str = "GET, POST, HEAD, OPTIONS";
+
+/// `HEAD /bridge/HOST/PATH.../events` or `.../sse`, answered like the
+/// plain routes without opening a session or loading the site.
+async fn bridge_head(uri: Uri) -> Response {
+    let allow = [(header::ALLOW, BRIDGE_METHODS)];
+    if bridge_path(&uri, "/events").is_some() {
+        let content_type = [(header::CONTENT_TYPE, "text/event-stream")];
+        (allow, content_type, StatusCode::OK).into_response()
+    } else if bridge_path(&uri, "/sse").is_some() {
+        (allow, StatusCode::OK).into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+/// `HOST/PATH...` from `/bridge/HOST/PATH...{suffix}`.
+fn bridge_path<'a>(uri: &'a Uri, suffix: &str) -> Option<&'a str> {
+    uri.path()
+        .strip_prefix("/bridge/")?
+        .strip_suffix(suffix)
+        .filter(|path| !path.is_empty())
+}
+
+/// Answers a message for the sites `site` and the query name, or posts it
+/// to the session the query names.
+async fn post_message(
+    state: Arc<AppState>,
+    site: Option<String>,
+    query: Option<String>,
+    request: MCPRequest,
+) -> Response {
+    // Messages posted to a session are answered from its sites, on its
+    // stream.
+    if let Some(id) = session_id(query.as_deref()) {
+        if !state.sessions.known(&id).await {
+            let envelope = McpEnvelope {
+                id: request.id,
+                method: Some(request.method),
+            };
+            return envelope.reject(StatusCode::NOT_FOUND, -32600, "Unknown session", None);
+        }
+        info!(session = id, "MCP message");
+        // Notifications get no answer.
+        let notification = request.id.is_none();
+        let response = match state.sessions.get(&id) {
+            Some(session) => {
+                let client = ClientInfo::from_initialize(&request);
+                let response = session.scope(session.data().answer(request)).await;
+                session.record(client, &response);
+                response
+            }
+            // Another replica's session, whose sites the path and query
+            // name again.
+            None => match requested(&state, site.as_deref(), query.as_deref()) {
+                Ok(targets) => match open_all(&state, targets).await {
+                    Ok(aggregate) => aggregate.answer(request).await,
+                    Err((url, e)) => Aggregate::error(
+                        request.id,
+                        -32603,
+                        format!("Failed to connect to remote MCP: {e}"),
+                        json!({ "url": url }),
+                    ),
+                },
+                Err((status, message)) => {
+                    Aggregate::error(request.id, rejection_code(status), message, json!({}))
+                }
+            },
+        };
+        if !notification {
+            let json = serde_json::to_string(&response).unwrap_or_default();
+            state.sessions.send(&id, json).await;
+        }
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let targets = match requested(&state, site.as_deref(), query.as_deref()) {
+        Ok(targets) => targets,
+        Err((status, message)) => {
+            let envelope = McpEnvelope {
+                id: request.id,
+                method: Some(request.method),
+            };
+            return envelope.reject(status, rejection_code(status), message, None);
+        }
+    };
+    let urls: Vec<&str> = targets.iter().map(|target| target.url.as_str()).collect();
+    info!(target_url = %urls.join(","), "MCP message");
+
+    let response = match open_all(&state, targets).await {
+        Ok(aggregate) => aggregate.answer(request).await,
+        Err((url, e)) => {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": request.id,
+                "error": {
+                    "code": -32603,
+                    "message": format!("Failed to connect to remote MCP: {}", e),
+                    "data": { "requestId": request_id::current(), "url": url }
+                }
+            });
+            return (status::upstream(&e), Json(body)).into_response();
+        }
+    };
+    let status = status::of(response.error.as_ref());
+    (
+        status,
+        Json(serde_json::to_value(response).unwrap_or_default()),
+    )
+        .into_response()
+}
+
+/// The JSON-RPC error code for a [`requested`] rejection: `-32003` for a
+/// refused site, `-32602` for sites that couldn't be named.
+fn rejection_code(status: StatusCode) -> i32 {
+    if status == StatusCode::FORBIDDEN {
+        -32003
+    } else {
+        -32602
+    }
+}
+
+/// Opens a session answering from the sites the query names, or resumes the
+/// one `Last-Event-ID` names, and streams it.
+async fn sse_endpoint(
+    State(state): State<Arc<AppState>>,
+    RawQuery(query): RawQuery,
+    nested: Option<NestedPath>,
+    headers: HeaderMap,
+) -> Response {
+    let endpoint = format!("{}/sse", base_path(&nested));
+    open_stream(state, None, query, endpoint, headers).await
+}
+
+/// `GET /bridge/HOST/PATH.../events`: [`sse_endpoint`] for the site the
+/// path names, whose messages are posted to the matching `/sse` route.
+async fn bridge_events_endpoint(
+    State(state): State<Arc<AppState>>,
+    uri: Uri,
+    RawQuery(query): RawQuery,
+    nested: Option<NestedPath>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(path) = bridge_path(&uri, "/events") else {
+        return status::no_such_endpoint(&McpEnvelope::default(), uri.path());
+    };
+    match path_site(path) {
+        Ok(site) => {
+            let endpoint = format!("{}/bridge/{path}/sse", base_path(&nested));
+            open_stream(state, Some(site), query, endpoint, headers).await
+        }
+        Err(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+    }
+}
+
+/// Streams the session for the sites `site` and the query name, telling
+/// the client to post its messages to `endpoint`.
+async fn open_stream(
+    state: Arc<AppState>,
+    site: Option<String>,
+    query: Option<String>,
+    endpoint: String,
+    headers: HeaderMap,
+) -> Response {
+    match stream(&state, site, query, endpoint, headers).await {
+        Ok(stream) => stream.into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+async fn stream(
+    state: &Arc<AppState>,
+    site: Option<String>,
+    query: Option<String>,
+    endpoint: String,
+    headers: HeaderMap,
+) -> Result<Sse<impl futures::Stream<Item = Result<Event, Infallible>> + use<>>, (StatusCode, String)>
+{
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok());
+    // Another replica's session is taken over with the sites the path and
+    // query name again.
+    let sites = || async {
+        let targets = requested(state, site.as_deref(), query.as_deref()).ok()?;
+        open_all(state, targets).await.ok()
+    };
+    let (session, after) = match state.sessions.resume(last_event_id, sites).await {
+        Some((session, seq)) => (session, Some(seq)),
+        None => {
+            let targets = requested(state, site.as_deref(), query.as_deref())?;
+            let aggregate = open_all(state, targets).await.map_err(|(url, e)| {
+                (
+                    status::upstream(&e),
+                    format!("Failed to connect to remote MCP {url}: {e}"),
+                )
+            })?;
+            (state.sessions.open(aggregate).await, None)
+        }
+    };
+    let endpoint = match query.filter(|query| !query.is_empty()) {
+        Some(query) => format!("{endpoint}?{query}"),
+        None => endpoint,
+    };
+    Ok(Sse::new(state.sessions.stream(session, after, &endpoint)))
+}
+
+async fn info_endpoint(
+    State(state): State<Arc<AppState>>,
+    nested: Option<NestedPath>,
+) -> Json<serde_json::Value> {
+    let base = base_path(&nested);
+    let keep_alive = state.sessions.keep_alive();
+    Json(json!({
+        "bridge": "SSE Static MCP Bridge (Generic Remote)",
+        "version": "1.0.0",
+        "type": "generic",
+        "description": "Generic bridge that can proxy to any remote StaticMCP",
+        "endpoints": {
+            "info": format!("GET {base}/"),
+            "mcp_sse": format!("POST {base}/sse?url={{target_mcp_url}}"),
+            "mcp_sse_events": format!("GET {base}/events?url={{target_mcp_url}}"),
+            "mcp_sse_aggregate": format!("POST {base}/sse?url={{first_url}}&url={{second_url}}"),
+            "mcp_sse_preset": format!("POST {base}/sse?preset={{name}}"),
+            "mcp_sse_path": format!("POST {base}/bridge/{{host}}/{{path}}/sse"),
+            "mcp_sse_events_path": format!("GET {base}/bridge/{{host}}/{{path}}/events"),
+            "liveness": format!("GET {base}/healthz"),
+            "readiness": format!("GET {base}/readyz"),
+            "stats": format!("GET {base}/stats"),
+            "metrics": format!("GET {base}/metrics"),
+        },
+        "sse_keep_alive": (!keep_alive.is_zero())
+            .then(|| humantime::format_duration(keep_alive).to_string()),
+        "manifest_ttl": (!state.bridges.ttl.is_zero())
+            .then(|| humantime::format_duration(state.bridges.ttl).to_string()),
+        "usage": {
+            "mcp_clients": format!("Point MCP client to: http://localhost:PORT{base}/sse?url=TARGET_URL"),
+            "standard_endpoints": [
+                format!("GET {base}/ (for info)"),
+                format!("POST {base}/sse?url=https://staticmcp.com/mcp (for SSE messages)")
+            ],
+        }
+    }))
+}
+
+/// Requests, errors and latency per site.
+async fn stats_endpoint(State(state): State<Arc<AppState>>) -> Json<Value> {
+    Json(state.stats.json())
+}
+
+/// The per-site stats for Prometheus, labelled by `target`.
+async fn metrics_endpoint(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.stats.prometheus(),
+    )
+}
+
+/// Prefix the routes are mounted under by `--base-path`, or `""`.
+fn base_path(nested: &Option<NestedPath>) -> &str {
+    nested.as_ref().map_or("", NestedPath::as_str)
+}
+
+/// Serves the proxy until the listener fails.
+pub async fn run(args: ProxyArgs) -> anyhow::Result<()> {
+    let _logging = args.server.logging.init();
+
+    let port = args.port;
+    if !args.server.authenticates() && !args.allow_anonymous {
+        anyhow::bail!(
+            "the proxy fetches whatever sites its callers name: require them to authenticate \
+             with --api-key, --hmac-key or --auth-issuer, or pass --allow-anonymous to serve anyone"
+        );
+    }
+
+    let state = Arc::new(AppState {
+        audit: args.server.audit_sink()?,
+        policy: args.server.access_policy()?,
+        paths: args.server.paths.clone(),
+        presets: args
+            .preset
+            .iter()
+            .map(|preset| (preset.name.clone(), preset.targets.clone()))
+            .collect(),
+        allowed: args.allow_targets.clone(),
+        sessions: args.sessions.sessions(),
+        stats: Arc::default(),
+        bridges: Arc::new(BridgeCache::new(args.manifest_ttl)),
+        fixtures: args.server.fixtures.fixtures()?,
+        client: args.server.http_client.client()?,
+    });
+
+    let mcp_routes = args.server.apply_mcp(
+        Router::new()
+            .route("/sse", methods::messages(mcp_sse_endpoint))
+            .route("/events", methods::events(sse_endpoint))
+            .route(
+                "/bridge/*path",
+                methods::answering(
+                    get(bridge_events_endpoint)
+                        .post(bridge_sse_endpoint)
+                        .head(bridge_head),
+                    BRIDGE_METHODS,
+                ),
+            )
+            .with_state(state.clone()),
+    );
+    let stats_routes = args.admin.guard(
+        Router::new()
+            .route("/stats", get(stats_endpoint))
+            .route("/metrics", get(metrics_endpoint))
+            .with_state(state.clone()),
+    );
+    let app = Router::new()
+        .route("/", get(info_endpoint))
+        // Targets are chosen per request, so being up is all readiness can promise.
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::healthz))
+        .with_state(state)
+        .merge(stats_routes)
+        .merge(mcp_routes)
+        .fallback(status::not_found);
+    let app = args
+        .server
+        .apply(methods::with_cors(app, args.server.cors()));
+
+    info!(
+        sse = %args.server.url(port, "/sse?url=TARGET_URL"),
+        "generic bridge ready"
+    );
+
+    args.server.serve(app, port).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(subject: &str, scopes: &[&str]) -> Principal {
+        Principal {
+            subject: subject.to_string(),
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+        }
+    }
+
+    fn allowed(entries: &[&str]) -> Vec<AllowedTargets> {
+        entries
+            .iter()
+            .map(|entry| parse_allowed_targets(entry).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn names_subjects_and_scopes() {
+        let ci = principal("ci", &["docs:read"]);
+        assert!(allowed(&["ci=https://example.com/"])[0].names(Some(&ci)));
+        assert!(allowed(&["scope:docs:read=https://example.com/"])[0].names(Some(&ci)));
+        assert!(!allowed(&["ops=https://example.com/"])[0].names(Some(&ci)));
+        assert!(!allowed(&["scope:admin=https://example.com/"])[0].names(Some(&ci)));
+        let anyone = &allowed(&["*=https://example.com/"])[0];
+        assert!(!anyone.names(Some(&ci)));
+        assert!(!anyone.names(None));
+    }
+
+    #[test]
+    fn allows_paths_at_or_below_the_prefix() {
+        let rule = &allowed(&["ci=https://example.com/mcp"])[0];
+        assert!(rule.allows("https://example.com/mcp"));
+        assert!(rule.allows("https://example.com/mcp/"));
+        assert!(rule.allows("https://example.com/mcp/v2"));
+        assert!(!rule.allows("https://example.com/mcpx"));
+        assert!(!rule.allows("https://example.com/mcp2/v2"));
+        assert!(!rule.allows("https://example.com/"));
+        let root = &allowed(&["ci=https://example.com/"])[0];
+        assert!(root.allows("https://example.com/anything"));
+    }
+
+    #[test]
+    fn allows_only_the_same_origin() {
+        let rule = &allowed(&["ci=https://example.com/mcp"])[0];
+        assert!(rule.allows("https://example.com:443/mcp"));
+        assert!(!rule.allows("http://example.com/mcp"));
+        assert!(!rule.allows("https://example.com:8443/mcp"));
+        assert!(!rule.allows("https://example.com.evil.test/mcp"));
+        assert!(!rule.allows("https://evil.test/example.com/mcp"));
+        assert!(!rule.allows("not a url"));
+    }
+
+    #[test]
+    fn callers_follow_their_own_entries_over_star() {
+        let allowed = allowed(&["ci=https://docs.example.com/", "*=https://example.com/mcp"]);
+        let ci = principal("ci", &[]);
+        assert!(may_use(&allowed, Some(&ci), "https://docs.example.com/v1"));
+        assert!(!may_use(&allowed, Some(&ci), "https://example.com/mcp"));
+        let ops = principal("ops", &[]);
+        assert!(may_use(&allowed, Some(&ops), "https://example.com/mcp"));
+        assert!(!may_use(&allowed, Some(&ops), "https://docs.example.com/"));
+        assert!(may_use(&allowed, None, "https://example.com/mcp/v2"));
+    }
+
+    #[test]
+    fn callers_no_entry_covers_may_use_no_site() {
+        let allowed = allowed(&["ci=https://docs.example.com/"]);
+        let ops = principal("ops", &[]);
+        assert!(!may_use(&allowed, Some(&ops), "https://docs.example.com/"));
+        assert!(!may_use(&allowed, None, "https://docs.example.com/"));
+        assert!(may_use(&[], Some(&ops), "https://anything.test/"));
+    }
+
+    #[test]
+    fn path_sites_default_to_https() {
+        assert_eq!(
+            path_site("docs.example.com/mcp").unwrap(),
+            "https://docs.example.com/mcp"
+        );
+        assert_eq!(
+            path_site("docs.example.com").unwrap(),
+            "https://docs.example.com"
+        );
+        assert_eq!(
+            path_site("docs.example.com/").unwrap(),
+            "https://docs.example.com"
+        );
+    }
+
+    #[test]
+    fn path_sites_take_an_http_prefix() {
+        assert_eq!(
+            path_site("http:localhost:8080/mcp").unwrap(),
+            "http://localhost:8080/mcp"
+        );
+        assert_eq!(path_site("http:localhost").unwrap(), "http://localhost");
+    }
+
+    #[test]
+    fn path_sites_keep_ports_and_ipv6_hosts() {
+        assert_eq!(
+            path_site("docs.example.com:8443/mcp").unwrap(),
+            "https://docs.example.com:8443/mcp"
+        );
+        assert_eq!(
+            path_site("docs.example.com:443/mcp").unwrap(),
+            "https://docs.example.com/mcp"
+        );
+        assert_eq!(
+            path_site("[::1]:8080/mcp").unwrap(),
+            "https://[::1]:8080/mcp"
+        );
+        assert_eq!(path_site("http:[::1]/mcp").unwrap(), "http://[::1]/mcp");
+        assert!(path_site("docs.example.com:port/mcp").is_err());
+    }
+
+    #[test]
+    fn path_sites_refuse_credentials_and_encoded_hosts() {
+        assert!(path_site("user@evil.test/mcp").is_err());
+        assert!(path_site("docs.example.com:443@evil.test/mcp").is_err());
+        assert!(path_site("http:user:pass@localhost/mcp").is_err());
+        assert!(path_site("docs%2eexample.com/mcp").is_err());
+        assert!(path_site("docs.example.com\\evil.test/mcp").is_err());
+        assert!(path_site("").is_err());
+        assert!(path_site("http:").is_err());
+        assert!(path_site("/mcp").is_err());
+    }
+
+    #[test]
+    fn path_sites_refuse_segments_that_move_the_path() {
+        for path in [
+            "docs.example.com/%2e%2e/admin",
+            "docs.example.com/mcp/%2E%2E",
+            "docs.example.com/.%2e/admin",
+            "docs.example.com/../admin",
+            "docs.example.com/./mcp",
+            "docs.example.com/%2e",
+            "docs.example.com/a%2Fb",
+            "docs.example.com/a%2fb",
+            "docs.example.com/a%5Cb",
+            "docs.example.com//mcp",
+            "docs.example.com/mcp/",
+            "docs.example.com/mcp//v2",
+        ] {
+            assert!(path_site(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn path_sites_keep_other_segments_encoded() {
+        assert_eq!(
+            path_site("docs.example.com/a%20b/%2e%2e.json").unwrap(),
+            "https://docs.example.com/a%20b/%2e%2e.json"
+        );
+    }
+
+    #[test]
+    fn path_sites_keep_the_origin_the_host_names() {
+        for path in [
+            "docs.example.com/@evil.test",
+            "docs.example.com/mcp@evil.test:80",
+            "docs.example.com/%40evil.test",
+        ] {
+            let site = reqwest::Url::parse(&path_site(path).unwrap()).unwrap();
+            assert_eq!(site.host_str(), Some("docs.example.com"), "{path}");
+            assert_eq!(site.port_or_known_default(), Some(443), "{path}");
+        }
+    }
+
+    #[test]
+    fn bridge_paths_strip_the_route() {
+        let path = |path: &str, suffix| {
+            let uri: Uri = path.parse().unwrap();
+            bridge_path(&uri, suffix).map(str::to_string)
+        };
+        assert_eq!(
+            path("/bridge/docs.example.com/mcp/sse", "/sse").as_deref(),
+            Some("docs.example.com/mcp")
+        );
+        assert_eq!(
+            path("/bridge/docs.example.com/events", "/events").as_deref(),
+            Some("docs.example.com")
+        );
+        assert_eq!(path("/bridge/docs.example.com/mcp/sse", "/events"), None);
+        assert_eq!(path("/bridge/sse", "/sse"), None);
+        assert_eq!(path("/bridge//sse", "/sse"), None);
+        assert_eq!(path("/other/docs.example.com/sse", "/sse"), None);
+    }
+
+    /// What [`bridge_path`] and [`base_path`] see for `path`, with the
+    /// routes mounted under `base` as `--base-path` mounts them.
+    async fn routed(base: Option<&str>, path: &str) -> String {
+        use axum::body::{Body, to_bytes};
+        use tower::ServiceExt;
+
+        let routes = Router::new().route(
+            "/bridge/*path",
+            get(|uri: Uri, nested: Option<NestedPath>| async move {
+                let path = bridge_path(&uri, "/events").unwrap_or("none");
+                format!("{}|{path}", base_path(&nested))
+            }),
+        );
+        let app = match base {
+            Some(base) => Router::new().nest(base, routes),
+            None => routes,
+        };
+        let request = axum::http::Request::get(path).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bridge_paths_under_a_base_path() {
+        assert_eq!(
+            routed(None, "/bridge/docs.example.com/mcp/events").await,
+            "|docs.example.com/mcp"
+        );
+        assert_eq!(
+            routed(Some("/proxy"), "/proxy/bridge/docs.example.com/mcp/events").await,
+            "/proxy|docs.example.com/mcp"
+        );
+    }
+
+    #[test]
+    fn rejections_map_to_json_rpc_codes() {
+        assert_eq!(rejection_code(StatusCode::FORBIDDEN), -32003);
+        assert_eq!(rejection_code(StatusCode::BAD_REQUEST), -32602);
+    }
+}
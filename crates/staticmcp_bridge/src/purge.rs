@@ -0,0 +1,45 @@
+//! `staticmcp-bridge purge`: evicts cached files from a running bridge, e.g.
+//! right after its static site is redeployed.
+
+use clap::Args;
+use serde_json::Value;
+
+/// Purge a running bridge's cache through its admin endpoint.
+#[derive(Debug, Clone, Args)]
+pub struct PurgeArgs {
+    /// The bridge's base URL, e.g. `http://localhost:3000`
+    pub url: String,
+
+    /// Only purge files whose path starts with this, e.g. `tools/search`
+    #[arg(long, value_name = "PREFIX")]
+    pub prefix: Option<String>,
+
+    /// The token the bridge was started with as `--admin-token`
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "ADMIN_TOKEN",
+        hide_env_values = true
+    )]
+    pub admin_token: String,
+}
+
+pub async fn run(args: PurgeArgs) -> anyhow::Result<()> {
+    let url = format!("{}/admin/cache", args.url.trim_end_matches('/'));
+    let mut request = reqwest::Client::new()
+        .delete(&url)
+        .bearer_auth(&args.admin_token);
+    if let Some(prefix) = &args.prefix {
+        request = request.query(&[("prefix", prefix)]);
+    }
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!("DELETE {url} answered {}", response.status());
+    }
+    let body: Value = response.json().await?;
+    println!(
+        "purged {} cached files",
+        body["purged"].as_u64().unwrap_or(0)
+    );
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! `staticmcp-bridge read`: reads one resource of a StaticMCP site and prints
+//! it, without an MCP client.
+
+use crate::call::{load, send};
+use clap::Args;
+use serde_json::json;
+use staticmcp_sse_lib::server::PathArgs;
+use std::process::ExitCode;
+
+/// Read a resource of a StaticMCP directory or URL and print it.
+#[derive(Debug, Clone, Args)]
+pub struct ReadArgs {
+    /// StaticMCP directory or URL
+    pub source: String,
+
+    /// Resource URI, e.g. `file://readme`
+    pub uri: String,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+pub async fn run(args: ReadArgs) -> anyhow::Result<ExitCode> {
+    let bridge = load(&args.source, &args.paths).await?;
+    let uri = bridge.resolve_uri(&args.uri).unwrap_or(&args.uri);
+    eprintln!("path: {}", bridge.uri_to_path(uri));
+    send(&bridge, "resources/read", json!({ "uri": args.uri })).await
+}
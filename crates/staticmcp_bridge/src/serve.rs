@@ -0,0 +1,334 @@
+//! `staticmcp-bridge serve`: one or more fixed StaticMCP sites over HTTP.
+
+use axum::Router;
+use clap::Args;
+use staticmcp_sse_lib::audit::AuditSink;
+use staticmcp_sse_lib::policy::AccessPolicy;
+use staticmcp_sse_lib::server::admin::{AdminArgs, CacheRoute};
+use staticmcp_sse_lib::server::cache::{self, CacheArgs, CachedDataSource};
+use staticmcp_sse_lib::server::fixtures::Fixtures;
+use staticmcp_sse_lib::server::router;
+#[cfg(feature = "search")]
+use staticmcp_sse_lib::server::search::SearchArgs;
+use staticmcp_sse_lib::server::session::SessionArgs;
+use staticmcp_sse_lib::server::{self, PathArgs, ServerArgs, VirtualHosts, methods, status};
+use staticmcp_sse_lib::{MCPBridge, RouterOptions, create_data_source_with_client};
+use std::sync::Arc;
+use tracing::{error, info};
+
+type AppState = Arc<MCPBridge>;
+
+pub const EXAMPLES: &str = "Examples:
+  staticmcp-bridge serve ./my-static-mcp 3000
+  staticmcp-bridge serve /path/to/mcp/data
+  staticmcp-bridge serve https://staticmcp.com/mcp 3000
+  staticmcp-bridge serve --mount /docs=./docs-mcp --mount /api=https://example.com/mcp
+  staticmcp-bridge serve --vhost docs.example.com=./docs-mcp --vhost blog.example.com=./blog-mcp";
+
+/// Serve the StaticMCP data at the specified path via SSE.
+#[derive(Debug, Clone, Args)]
+pub struct ServeArgs {
+    /// StaticMCP directory or URL served at the root; required unless
+    /// `--mount` or `--vhost` is given
+    pub data_path: Option<String>,
+
+    /// Port to listen on
+    #[arg(default_value_t = 3000, env = "PORT")]
+    pub port: u16,
+
+    /// Also serve the StaticMCP directory or URL SOURCE under PREFIX, as its
+    /// own bridge; repeat for more
+    #[arg(long, value_name = "PREFIX=SOURCE", value_parser = parse_mount)]
+    pub mount: Vec<Mount>,
+
+    /// Serve the StaticMCP directory or URL SOURCE to requests whose `Host` is
+    /// HOST, as its own bridge; repeat for more. Other hosts get the DATA_PATH
+    /// and `--mount` bridges
+    #[arg(long, value_name = "HOST=SOURCE", value_parser = parse_vhost)]
+    pub vhost: Vec<VirtualHost>,
+
+    /// Send reads of resource files larger than this many bytes, such as
+    /// PDFs or videos, in chunks over SSE to clients that accept
+    /// `text/event-stream`, instead of reading them whole
+    #[arg(long, value_name = "BYTES", env = "STREAM_THRESHOLD")]
+    pub stream_threshold: Option<u64>,
+
+    #[command(flatten)]
+    pub sessions: SessionArgs,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    #[cfg(feature = "search")]
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    #[command(flatten)]
+    pub admin: AdminArgs,
+
+    #[command(flatten)]
+    pub server: ServerArgs,
+}
+
+/// A `--mount` entry.
+#[derive(Debug, Clone)]
+pub struct Mount {
+    pub prefix: String,
+    pub source: String,
+}
+
+fn parse_mount(value: &str) -> Result<Mount, String> {
+    let (prefix, source) = value
+        .split_once('=')
+        .ok_or("expected PREFIX=SOURCE, such as /docs=./docs-mcp")?;
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        return Err(
+            "the prefix must not be empty; pass the source as DATA_PATH to serve it at the root"
+                .into(),
+        );
+    }
+    if source.is_empty() {
+        return Err("the source must not be empty".into());
+    }
+    Ok(Mount {
+        prefix: format!("/{prefix}"),
+        source: source.to_string(),
+    })
+}
+
+/// A `--vhost` entry.
+#[derive(Debug, Clone)]
+pub struct VirtualHost {
+    pub host: String,
+    pub source: String,
+}
+
+fn parse_vhost(value: &str) -> Result<VirtualHost, String> {
+    let (host, source) = value
+        .split_once('=')
+        .ok_or("expected HOST=SOURCE, such as docs.example.com=./docs-mcp")?;
+    if host.is_empty() || source.is_empty() {
+        return Err("both the host and the source must be given".into());
+    }
+    Ok(VirtualHost {
+        host: host.to_string(),
+        source: source.to_string(),
+    })
+}
+
+/// Settings shared by every bridge the process serves.
+struct BridgeOptions {
+    audit: Option<Arc<dyn AuditSink>>,
+    policy: Option<Arc<AccessPolicy>>,
+    paths: PathArgs,
+    stream_threshold: Option<u64>,
+    cache: CacheArgs,
+    #[cfg(feature = "search")]
+    search: SearchArgs,
+    fixtures: Option<Arc<Fixtures>>,
+    /// Shared by every remote source, so they share its connections.
+    client: reqwest::Client,
+}
+
+/// Opens `source` and loads its manifest, exiting with troubleshooting hints
+/// when that fails, then preloads its cache when asked to. The caches are
+/// returned alongside for the admin endpoints, labelled with `route`. With
+/// `--replay` the source isn't read at all.
+async fn open_bridge(
+    route: &str,
+    source: &str,
+    options: &BridgeOptions,
+) -> anyhow::Result<(AppState, Option<CacheRoute>)> {
+    let mut data_source =
+        create_data_source_with_client(source.to_string(), options.client.clone());
+    let cache = options.cache.cache();
+    if let Some(cache) = &cache {
+        data_source = Box::new(CachedDataSource::new(data_source, cache.clone()));
+    }
+    let mut builder = options
+        .paths
+        .apply(MCPBridge::builder(data_source))
+        .stream_threshold(options.stream_threshold);
+    let responses = options.cache.response_cache();
+    if let Some(responses) = &responses {
+        builder = builder.response_cache(responses.clone());
+    }
+    let caches = (cache.is_some() || responses.is_some()).then(|| CacheRoute {
+        route: route.to_string(),
+        source: source.to_string(),
+        cache: cache.clone(),
+        responses,
+    });
+    #[cfg(feature = "search")]
+    {
+        builder = options.search.apply(builder)?;
+    }
+    if let Some(sink) = &options.audit {
+        builder = builder.audit_sink(sink.clone());
+    }
+    if let Some(policy) = &options.policy {
+        builder = builder.access_policy(policy.clone());
+    }
+    let mut bridge = builder.build();
+    if options.fixtures.as_ref().is_some_and(|f| f.replaying()) {
+        return Ok((Arc::new(bridge), caches));
+    }
+
+    match bridge.initialize().await {
+        Ok(()) => {
+            #[cfg(feature = "search")]
+            options.search.save(&bridge)?;
+            if options.cache.preload {
+                cache::preload(&bridge, options.cache.preload_concurrency).await;
+            }
+            Ok((Arc::new(bridge), caches))
+        }
+        Err(e) => {
+            error!(source, error = %e, "failed to initialize bridge");
+            info!(
+                source,
+                "check that mcp.json exists at the source, that the source is reachable \
+                 and that the file is valid JSON"
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Info, readiness and MCP routes for one bridge, relative to its mount
+/// point, with its own `/events` sessions and its answers recorded or
+/// replayed under `scope`.
+fn bridge_routes(
+    server: &ServerArgs,
+    bridge: AppState,
+    sessions: &SessionArgs,
+    fixtures: &Option<Arc<Fixtures>>,
+    scope: &str,
+) -> Router {
+    let options = RouterOptions {
+        sessions: sessions.sessions(),
+        recording: fixtures
+            .clone()
+            .map(|fixtures| (fixtures, Arc::from(scope))),
+        ..RouterOptions::default()
+    };
+    router::routes(bridge, options, |routes| server.apply_mcp(routes))
+}
+
+/// Serves the bridges `args` describe until the listener fails.
+pub async fn run(args: ServeArgs) -> anyhow::Result<()> {
+    // Checked here rather than by clap, which doesn't count sources set by
+    // `--config` as present.
+    if args.data_path.is_none() && args.mount.is_empty() && args.vhost.is_empty() {
+        anyhow::bail!("nothing to serve: give a DATA_PATH, --mount or --vhost");
+    }
+    if args.cache.preload && args.cache.cache_ttl.is_none() {
+        anyhow::bail!("--preload needs --cache-ttl to preload into");
+    }
+    #[cfg(feature = "search")]
+    if args.search.search_index.is_some()
+        && usize::from(args.data_path.is_some()) + args.mount.len() + args.vhost.len() > 1
+    {
+        anyhow::bail!(
+            "--search-index holds one site's index; it can't be used with several sources"
+        );
+    }
+    let _logging = args.server.logging.init();
+
+    let port = args.port;
+    let options = BridgeOptions {
+        audit: args.server.audit_sink()?,
+        policy: args.server.access_policy()?,
+        paths: args.server.paths.clone(),
+        stream_threshold: args.stream_threshold,
+        cache: args.cache.clone(),
+        #[cfg(feature = "search")]
+        search: args.search.clone(),
+        fixtures: args.server.fixtures.fixtures()?,
+        client: args.server.http_client.client()?,
+    };
+
+    info!(
+        source = ?args.data_path,
+        mounts = args.mount.len(),
+        vhosts = args.vhost.len(),
+        "fixed path SSE bridge starting"
+    );
+
+    let mut app = Router::new();
+    let mut caches = Vec::new();
+    if let Some(source) = &args.data_path {
+        let (bridge, cache) = open_bridge("/", source, &options).await?;
+        caches.extend(cache);
+        app = app.merge(bridge_routes(
+            &args.server,
+            bridge,
+            &args.sessions,
+            &options.fixtures,
+            "/",
+        ));
+    }
+    for mount in &args.mount {
+        let (bridge, cache) = open_bridge(&mount.prefix, &mount.source, &options).await?;
+        caches.extend(cache);
+        app = server::nest(
+            app,
+            &mount.prefix,
+            bridge_routes(
+                &args.server,
+                bridge,
+                &args.sessions,
+                &options.fixtures,
+                &mount.prefix,
+            ),
+        );
+    }
+    app = app.fallback(status::not_found);
+    if !args.vhost.is_empty() {
+        let mut hosts = VirtualHosts::new().default_router(app);
+        for vhost in &args.vhost {
+            let (bridge, cache) = open_bridge(&vhost.host, &vhost.source, &options).await?;
+            caches.extend(cache);
+            info!(host = %vhost.host, source = %vhost.source, "virtual host ready");
+            hosts = hosts.host(
+                &vhost.host,
+                bridge_routes(
+                    &args.server,
+                    bridge,
+                    &args.sessions,
+                    &options.fixtures,
+                    &vhost.host,
+                )
+                .fallback(status::not_found),
+            );
+        }
+        app = hosts.into_router();
+    }
+    if let Some(admin) = args.admin.router(caches) {
+        app = admin.merge(app);
+    }
+    let app = args
+        .server
+        .apply(methods::with_cors(app, args.server.cors()));
+
+    if args.data_path.is_some() {
+        info!(
+            info = %args.server.url(port, "/"),
+            messages = %args.server.url(port, "/sse"),
+            events = %args.server.url(port, "/events"),
+            "server ready"
+        );
+    }
+    for mount in &args.mount {
+        info!(
+            source = %mount.source,
+            info = %args.server.url(port, &mount.prefix),
+            messages = %args.server.url(port, &format!("{}/sse", mount.prefix)),
+            events = %args.server.url(port, &format!("{}/events", mount.prefix)),
+            "mount ready"
+        );
+    }
+
+    args.server.serve(app, port).await
+}
@@ -0,0 +1,123 @@
+//! `staticmcp-bridge stdio`: one StaticMCP site over the MCP stdio transport,
+//! for clients that launch their servers as subprocesses.
+
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use serde_json::Value;
+use serde_json::json;
+use staticmcp_sse_lib::audit::JsonlAuditLog;
+use staticmcp_sse_lib::client::ClientInfo;
+#[cfg(feature = "search")]
+use staticmcp_sse_lib::server::SearchArgs;
+use staticmcp_sse_lib::server::{self, LoggingArgs, PathArgs, protocol};
+use staticmcp_sse_lib::{MCPBridge, create_data_source, request};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::{debug, info};
+
+/// Serve a StaticMCP directory or URL to an MCP client on stdin and stdout.
+#[derive(Debug, Clone, Args)]
+pub struct StdioArgs {
+    /// StaticMCP directory or URL
+    pub data_path: String,
+
+    /// Append a JSONL audit record for every tool call and resource read
+    #[arg(long, value_name = "PATH", env = "AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+
+    /// Accept messages with a missing or numeric `jsonrpc`, params sent as a
+    /// JSON string or wrapped in an array, logging a warning for each
+    #[arg(long, env = "LENIENT", value_parser = BoolishValueParser::new())]
+    pub lenient: bool,
+
+    #[cfg(feature = "search")]
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    #[command(flatten)]
+    pub logging: LoggingArgs,
+}
+
+/// Answers newline-delimited JSON-RPC messages from stdin on stdout until
+/// stdin closes. Logs go to stderr, keeping stdout for the protocol.
+pub async fn run(args: StdioArgs) -> anyhow::Result<()> {
+    let _logging = args.logging.init();
+
+    let mut builder = args.paths.apply(MCPBridge::builder(create_data_source(
+        args.data_path.clone(),
+    )));
+    if let Some(path) = &args.audit_log {
+        builder = builder.audit_sink(Arc::new(JsonlAuditLog::open(path)?));
+    }
+    #[cfg(feature = "search")]
+    {
+        builder = args.search.apply(builder)?;
+    }
+    let mut bridge = builder.build();
+    bridge
+        .initialize()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to initialize bridge for {}: {e}", args.data_path))?;
+    #[cfg(feature = "search")]
+    args.search.save(&bridge)?;
+    info!(source = %args.data_path, "stdio bridge ready");
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    // What `initialize` agreed on, for the messages after it.
+    let mut version = None;
+    let mut client: Option<Arc<ClientInfo>> = None;
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed =
+            serde_json::from_str::<Value>(&line).map(|value| request::parse(value, args.lenient));
+        let response = match parsed {
+            // Notifications expect no answer.
+            Ok(Ok(request)) if request.id.is_none() => {
+                debug!(method = %request.method, "notification received");
+                continue;
+            }
+            Ok(Ok(request)) => {
+                let initializing = ClientInfo::from_initialize(&request);
+                let answer = async {
+                    match version {
+                        Some(version) => {
+                            protocol::scope(version, bridge.handle_request(request)).await
+                        }
+                        None => bridge.handle_request(request).await,
+                    }
+                };
+                let response = match &client {
+                    Some(client) => server::client::scope(client.clone(), answer).await,
+                    None => answer.await,
+                };
+                if let Some(agreed) = protocol::agreed(&response) {
+                    version = Some(agreed);
+                    client = initializing.map(Arc::new).or(client);
+                }
+                serde_json::to_value(response)?
+            }
+            Ok(Err(invalid)) => json!({
+                "jsonrpc": "2.0",
+                "id": invalid.id,
+                "error": { "code": -32600, "message": invalid.message, "data": invalid.data() }
+            }),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": format!("Parse error: {e}") }
+            }),
+        };
+        let mut output = serde_json::to_vec(&response)?;
+        output.push(b'\n');
+        stdout.write_all(&output).await?;
+        stdout.flush().await?;
+    }
+    Ok(())
+}
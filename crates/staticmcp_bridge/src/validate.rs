@@ -0,0 +1,268 @@
+//! `staticmcp-bridge validate`: checks a StaticMCP site before it is served.
+
+use clap::Args;
+use serde_json::Value;
+use staticmcp_sse_lib::manifest::{self, Severity};
+use staticmcp_sse_lib::rewrite::Rewrite;
+use staticmcp_sse_lib::server::PathArgs;
+use staticmcp_sse_lib::{MCPBridge, RequestContext, ToolDef, checksum, create_data_source};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// The site has problems that would make requests fail.
+const EXIT_PROBLEMS: u8 = 1;
+/// The manifest itself couldn't be loaded.
+const EXIT_UNREADABLE: u8 = 2;
+
+/// Check a StaticMCP directory or URL for a malformed manifest and missing files.
+#[derive(Debug, Clone, Args)]
+pub struct ValidateArgs {
+    /// StaticMCP directory or URL
+    pub data_path: String,
+
+    /// Fail on warnings too, such as tools without an `inputSchema`
+    #[arg(long)]
+    pub strict: bool,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+}
+
+#[derive(Default)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn error(&mut self, message: impl std::fmt::Display) {
+        self.errors += 1;
+        println!("error: {message}");
+    }
+
+    fn warning(&mut self, message: impl std::fmt::Display) {
+        self.warnings += 1;
+        println!("warning: {message}");
+    }
+}
+
+/// Prints each problem found and exits `0` for a sound site, `1` when
+/// something is broken (or, with `--strict`, merely suspect), and `2` when
+/// the manifest can't be loaded at all.
+pub async fn run(args: ValidateArgs) -> anyhow::Result<ExitCode> {
+    let mut bridge = args
+        .paths
+        .apply(MCPBridge::builder(create_data_source(
+            args.data_path.clone(),
+        )))
+        .build();
+    let raw = match bridge
+        .data_source
+        .load_json("mcp.json", &RequestContext::default())
+        .await
+    {
+        Ok(raw) => raw,
+        Err(e) => {
+            println!("error: mcp.json: {e}");
+            return Ok(ExitCode::from(EXIT_UNREADABLE));
+        }
+    };
+    let mut report = Report::default();
+    for diagnostic in manifest::check(&raw) {
+        match diagnostic.severity {
+            Severity::Error => report.error(&diagnostic),
+            Severity::Warning => report.warning(&diagnostic),
+        }
+    }
+    // Entries the check rejected can't be loaded either, so past those
+    // errors there is nothing further to check.
+    let manifest = match manifest::load(raw) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            if report.errors == 0 {
+                println!("error: mcp.json: {e}");
+            }
+            return Ok(ExitCode::from(EXIT_UNREADABLE));
+        }
+    };
+
+    // Set rather than loaded by `initialize`, which would stop at the
+    // first problem.
+    bridge.manifest = Some(manifest);
+    let resources = bridge.resources();
+    let mut uris = HashSet::new();
+    for resource in resources {
+        // Forwarded resources have no file.
+        if !uris.insert(&resource.uri) || resource.upstream {
+            continue;
+        }
+        let path = bridge.uri_to_path(&resource.uri);
+        if let Err(e) = bridge
+            .data_source
+            .load_json(&path, &RequestContext::default())
+            .await
+        {
+            report.error(format!("resource {}: {path}: {e}", resource.uri));
+        }
+    }
+
+    let tools = bridge.tools();
+    let local = local_root(&args.data_path);
+    let rewrites_paths = bridge
+        .rewrites
+        .iter()
+        .chain(
+            bridge
+                .manifest
+                .iter()
+                .flat_map(|manifest| &manifest.rewrites),
+        )
+        .any(|rule| rule.kind == Rewrite::PathToPath);
+    let mut names = HashSet::new();
+    for tool in tools {
+        let name = &tool.name;
+        // Computed tools answer from their handler rather than response
+        // files, and forwarded ones have none either.
+        if !names.insert(name) || tool.handler.is_some() || tool.upstream {
+            continue;
+        }
+        // A `pathTemplate` or path rewrite can put responses anywhere, so
+        // those tools are probed like remote ones.
+        match &local {
+            Some(root) if tool.path_template.is_none() && !rewrites_paths => {
+                if !has_response_file(root, name) {
+                    report.error(format!(
+                        "tool {name}: no response files at tools/{name}.json or under tools/{name}/"
+                    ));
+                }
+            }
+            _ => probe_tool(&bridge, tool, &mut report).await,
+        }
+    }
+
+    for prompt in bridge.prompts() {
+        let name = &prompt.name;
+        // Templated prompts' files depend on their arguments, like tools'.
+        if prompt.path_template.is_some() {
+            continue;
+        }
+        let path = format!("prompts/{name}.json");
+        if let Err(e) = bridge
+            .data_source
+            .load_json(&path, &RequestContext::default())
+            .await
+        {
+            report.error(format!("prompt {name}: {path}: {e}"));
+        }
+    }
+
+    let checksums = bridge
+        .get_manifest()
+        .map(|manifest| manifest.checksums.clone())
+        .unwrap_or_default();
+    if !checksums.is_empty() && !checksum::VERIFIED {
+        report.warning("checksums can't be checked without the `sha256` feature");
+    }
+    for (path, expected) in &checksums {
+        match bridge.data_source.load_bytes(path).await {
+            Ok(bytes) => {
+                if let Err(e) = checksum::verify(path, &bytes, expected) {
+                    report.error(format!("checksum: {e}"));
+                }
+            }
+            Err(e) => report.error(format!("checksum: {path}: {e}")),
+        }
+    }
+
+    println!(
+        "{}: {} resources, {} tools, {} errors, {} warnings",
+        args.data_path,
+        resources.len(),
+        tools.len(),
+        report.errors,
+        report.warnings
+    );
+    let failed = report.errors > 0 || (args.strict && report.warnings > 0);
+    Ok(if failed {
+        ExitCode::from(EXIT_PROBLEMS)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// The directory behind a local source; URLs have none.
+fn local_root(source: &str) -> Option<PathBuf> {
+    let remote = source.starts_with("http://") || source.starts_with("https://");
+    (!remote).then(|| PathBuf::from(source))
+}
+
+/// Whether a JSON file, possibly precompressed, answers some call of `name`.
+fn has_response_file(root: &Path, name: &str) -> bool {
+    let tools = root.join("tools");
+    let flat = ["", ".gz", ".br"]
+        .iter()
+        .any(|suffix| tools.join(format!("{name}.json{suffix}")).is_file());
+    flat || contains_json(&tools.join(name))
+}
+
+fn contains_json(dir: &Path) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            return contains_json(&path);
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        name.ends_with(".json") || name.ends_with(".json.gz") || name.ends_with(".json.br")
+    })
+}
+
+/// Where a tool's responses can't be listed, it is probed with its required
+/// arguments set from their schemas; tools whose schema offers none are only
+/// warned about.
+async fn probe_tool(bridge: &MCPBridge, tool: &ToolDef, report: &mut Report) {
+    let name = &tool.name;
+    let args = match sample_arguments(tool) {
+        Ok(args) => args,
+        Err(property) => {
+            report.warning(format!(
+                "tool {name}: can't probe, `{property}` has no enum, examples or default"
+            ));
+            return;
+        }
+    };
+    let path = match bridge.tool_to_path(name, &args) {
+        Ok(path) => path,
+        Err(e) => {
+            report.error(format!("tool {name}: {e}"));
+            return;
+        }
+    };
+    if let Err(e) = bridge
+        .data_source
+        .load_json(&path, &RequestContext::default())
+        .await
+    {
+        report.error(format!("tool {name}: {path}: {e}"));
+    }
+}
+
+/// Arguments for a sample call of `tool`: each required property set to the
+/// first `enum`, `examples` or `default` value of its schema. Fails with the
+/// first property that has none.
+pub(crate) fn sample_arguments(tool: &ToolDef) -> Result<HashMap<String, Value>, String> {
+    let mut args = HashMap::new();
+    for property in tool.required() {
+        let schema = tool.property(property);
+        let sample = ["/enum/0", "/examples/0", "/default"]
+            .iter()
+            .find_map(|pointer| schema?.pointer(pointer))
+            .ok_or_else(|| property.to_string())?;
+        args.insert(property.to_string(), sample.clone());
+    }
+    Ok(args)
+}
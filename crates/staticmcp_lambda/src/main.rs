@@ -0,0 +1,116 @@
+use axum::response::sse::Event;
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    middleware,
+    response::{IntoResponse, Response, Sse},
+    routing::get,
+};
+use futures::stream;
+use serde_json::json;
+use staticmcp_sse_lib::server::body::{JsonRpc, Lenient};
+use staticmcp_sse_lib::server::{methods, protocol, status};
+use staticmcp_sse_lib::{MCPBridge, create_bridge};
+use std::sync::Arc;
+use tower_http::cors::CorsLayer;
+use tracing::{debug, info};
+
+#[derive(Clone)]
+struct AppState {
+    bridge: Arc<MCPBridge>,
+    streaming: bool,
+}
+
+async fn mcp_message_endpoint(
+    State(state): State<AppState>,
+    JsonRpc(request): JsonRpc,
+) -> Response {
+    debug!("MCP message received");
+    let response = state.bridge.handle_request(request).await;
+    let status = status::of(response.error.as_ref());
+    (
+        status,
+        Json(serde_json::to_value(response).unwrap_or_default()),
+    )
+        .into_response()
+}
+
+async fn sse_endpoint(State(state): State<AppState>) -> Response {
+    debug!("SSE connection requested");
+    if state.streaming {
+        let stream = MCPBridge::create_sse_stream(state.bridge, MCPBridge::PING_INTERVAL);
+        Sse::new(stream).into_response()
+    } else {
+        // Buffered invocations only return once the body is complete, so the
+        // stream has to end after the handshake instead of idling on pings.
+        let stream = stream::iter([Ok::<_, axum::Error>(
+            Event::default()
+                .event("message")
+                .data(r#"{"jsonrpc":"2.0","method":"ready","params":{}}"#),
+        )]);
+        Sse::new(stream).into_response()
+    }
+}
+
+async fn info_endpoint(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let manifest_info = if let Some(manifest) = state.bridge.get_manifest() {
+        json!({
+            "serverInfo": manifest.server_info,
+            "capabilities": manifest.capabilities
+        })
+    } else {
+        json!({ "error": "Manifest not loaded" })
+    };
+
+    Json(json!({
+        "bridge": "SSE Static MCP Bridge (AWS Lambda)",
+        "version": "1.0.0",
+        "type": "lambda",
+        "streaming": state.streaming,
+        "sse_keep_alive": state
+            .streaming
+            .then(|| format!("{}s", MCPBridge::PING_INTERVAL.as_secs())),
+        "manifest": manifest_info,
+        "endpoints": {
+            "info": "GET /",
+            "mcp_sse": "POST /sse",
+            "mcp_sse_events": "GET /events",
+        }
+    }))
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), lambda_http::Error> {
+    lambda_http::tracing::init_default_subscriber();
+
+    let source_path = std::env::var("STATICMCP_SOURCE")
+        .map_err(|_| "STATICMCP_SOURCE must be set to the StaticMCP directory or URL")?;
+    let streaming = env_flag("STATICMCP_STREAMING");
+    let lenient = env_flag("STATICMCP_LENIENT");
+
+    info!(source = %source_path, streaming, "lambda SSE bridge starting");
+
+    let bridge = Arc::new(create_bridge(source_path).await?);
+
+    let mut app = Router::new()
+        .route("/", get(info_endpoint))
+        .route("/sse", methods::messages(mcp_message_endpoint))
+        .route("/events", methods::events(sse_endpoint))
+        .fallback(status::not_found)
+        .layer(middleware::from_fn(protocol::header));
+    if lenient {
+        app = app.layer(Extension(Lenient));
+    }
+    let app =
+        methods::with_cors(app, CorsLayer::permissive()).with_state(AppState { bridge, streaming });
+
+    if streaming {
+        lambda_http::run_with_streaming_response(app).await
+    } else {
+        lambda_http::run(app).await
+    }
+}
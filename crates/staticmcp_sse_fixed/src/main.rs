@@ -1,116 +1,34 @@
-use axum::response::sse::{Event, KeepAlive};
-use axum::{
-    Json, Router,
-    extract::State,
-    response::Sse,
-    routing::{get, post},
-};
-use futures::stream::Stream;
-use serde_json::json;
-use staticmcp_sse_lib::{MCPBridge, MCPRequest, create_bridge};
-use std::sync::Arc;
-use tower_http::cors::CorsLayer;
-
-type AppState = Arc<MCPBridge>;
-
-async fn mcp_message_endpoint(
-    State(bridge): State<AppState>,
-    Json(request): Json<MCPRequest>,
-) -> Json<serde_json::Value> {
-    eprintln!("📨 MCP Message received");
-    let response = bridge.handle_request(request).await;
-    Json(serde_json::to_value(response).unwrap_or_default())
-}
-
-async fn sse_endpoint(
-    State(bridge): State<AppState>,
-) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
-    eprintln!("🌊 SSE connection requested");
-    let stream = MCPBridge::create_sse_stream(bridge);
-    Sse::new(stream).keep_alive(KeepAlive::default())
-}
-
-async fn info_endpoint(State(bridge): State<AppState>) -> Json<serde_json::Value> {
-    let manifest_info = if let Some(manifest) = bridge.get_manifest() {
-        json!({
-            "serverInfo": manifest.server_info,
-            "capabilities": manifest.capabilities
-        })
-    } else {
-        json!({ "error": "Manifest not loaded" })
-    };
-
-    Json(json!({
-        "bridge": "SSE Static MCP Bridge (Fixed Path)",
-        "version": "1.0.0",
-        "type": "fixed",
-        "manifest": manifest_info,
-        "endpoints": {
-            "info": "GET /",
-            "mcp_sse": "POST /sse",
-            "mcp_sse_events": "GET /events",
-        },
-        "usage": {
-            "mcp_clients": "Point MCP client to: http://localhost:PORT/",
-            "standard_endpoints": [
-                "GET / (for info)",
-                "POST /sse (for SSE messages)"
-            ]
-        }
-    }))
+//! `staticmcp-bridge serve` under its original name.
+
+use clap::Parser;
+use staticmcp_bridge::serve::{self, ServeArgs};
+use staticmcp_bridge::{RuntimeArgs, config};
+use std::path::PathBuf;
+
+/// Serve the StaticMCP data at the specified path via SSE.
+#[derive(Parser)]
+#[command(version, after_help = EXAMPLES)]
+struct Cli {
+    /// TOML or YAML file of flag defaults; environment variables and flags
+    /// override it
+    #[arg(long, value_name = "PATH", env = config::CONFIG_ENV)]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    runtime: RuntimeArgs,
+
+    #[command(flatten)]
+    args: ServeArgs,
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
-
-    if args.len() < 2 {
-        eprintln!("Usage: {} <DATA_PATH> [PORT]", args[0]);
-        eprintln!();
-        eprintln!("Examples:");
-        eprintln!("  {} ./my-static-mcp 3000", args[0]);
-        eprintln!("  {} /path/to/mcp/data", args[0]);
-        eprintln!("  {} https://staticmcp.com/mcp 3000", args[0]);
-        eprintln!();
-        eprintln!("The server will serve the StaticMCP data at the specified path via SSE.");
-        std::process::exit(1);
-    }
-
-    let source_path = args[1].clone();
-    let port = args.get(2).and_then(|p| p.parse().ok()).unwrap_or(3000);
-
-    eprintln!("🚀 Fixed Path SSE Bridge starting...");
-    eprintln!("📍 Source: {source_path}");
-
-    let bridge = match create_bridge(source_path).await {
-        Ok(bridge) => Arc::new(bridge),
-        Err(e) => {
-            eprintln!("❌ Failed to initialize bridge: {e}");
-            eprintln!();
-            eprintln!("🔍 Troubleshooting:");
-            eprintln!("  1. Check that mcp.json exists at the specified location");
-            eprintln!("  2. Verify the URL/path is accessible");
-            eprintln!("  3. Ensure the JSON is valid");
-            std::process::exit(1);
-        }
-    };
-
-    let app = Router::new()
-        .route("/", get(info_endpoint))
-        .route("/sse", post(mcp_message_endpoint))
-        .route("/events", get(sse_endpoint))
-        .layer(CorsLayer::permissive())
-        .with_state(bridge);
-
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}")).await?;
+const EXAMPLES: &str = "Examples:
+  staticmcp_sse_fixed ./my-static-mcp 3000
+  staticmcp_sse_fixed https://staticmcp.com/mcp 3000
+  staticmcp_sse_fixed --mount /docs=./docs-mcp --mount /api=https://example.com/mcp
 
-    eprintln!("✅ Server ready at http://localhost:{port}");
-    eprintln!("📖 Endpoints available:");
-    eprintln!("   GET  http://localhost:{port}/     (info)");
-    eprintln!("   POST http://localhost:{port}/sse  (MCP SSE messages)");
-    eprintln!();
-    eprintln!("🔌 For MCP clients: http://localhost:{port}/");
+Equivalent to `staticmcp-bridge serve`.";
 
-    axum::serve(listener, app).await?;
-    Ok(())
+fn main() -> anyhow::Result<()> {
+    let cli = config::parse::<Cli>();
+    cli.runtime.build()?.block_on(serve::run(cli.args))
 }
@@ -0,0 +1,104 @@
+//! Audit trail of tool calls and resource reads.
+
+use crate::client::ClientInfo;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+}
+
+/// One audited MCP call. Fields that don't apply to the method are omitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Authenticated principal, when the transport established one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// The MCP client's name and version, as its session's `initialize`
+    /// gave them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_info: Option<ClientInfo>,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The live MCP server that answered, for forwarded requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(method: &str) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            request_id: None,
+            client: None,
+            client_info: None,
+            method: method.to_string(),
+            tool: None,
+            arguments: None,
+            uri: None,
+            path: None,
+            upstream: None,
+            outcome: AuditOutcome::Ok,
+            error: None,
+        }
+    }
+}
+
+/// Destination for [`AuditEntry`] records. Implementations must not fail the
+/// request; report write problems out of band.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, entry: &AuditEntry);
+}
+
+/// Appends one JSON object per line to a file.
+pub struct JsonlAuditLog {
+    file: Mutex<File>,
+}
+
+impl JsonlAuditLog {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for JsonlAuditLog {
+    fn record(&self, entry: &AuditEntry) {
+        let mut line = match serde_json::to_vec(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to serialize audit entry");
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        // A single write per entry keeps lines whole in `O_APPEND` mode.
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.write_all(&line) {
+            tracing::warn!(error = %e, "failed to write audit entry");
+        }
+    }
+}
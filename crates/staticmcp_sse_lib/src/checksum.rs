@@ -0,0 +1,29 @@
+//! Checks of the files the bridge reads against the `checksums` a version 2
+//! manifest lists, so a file changed since the manifest was written, as
+//! during a partial deploy, is refused rather than served.
+
+/// Fails unless `bytes`, read from `path`, have the `sha256:` checksum
+/// `expected`.
+#[cfg(feature = "sha256")]
+pub fn verify(path: &str, bytes: &[u8], expected: &str) -> anyhow::Result<()> {
+    use sha2::{Digest, Sha256};
+    let actual: String = Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let expected_hex = expected.strip_prefix("sha256:").unwrap_or(expected);
+    if !actual.eq_ignore_ascii_case(expected_hex) {
+        anyhow::bail!("{path} doesn't match its checksum {expected}: found sha256:{actual}");
+    }
+    Ok(())
+}
+
+/// Without the `sha256` feature files aren't checked, as the bridge warns
+/// when it loads a manifest listing checksums.
+#[cfg(not(feature = "sha256"))]
+pub fn verify(_path: &str, _bytes: &[u8], _expected: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Whether files are checked against their checksums.
+pub const VERIFIED: bool = cfg!(feature = "sha256");
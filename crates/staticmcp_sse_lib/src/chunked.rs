@@ -0,0 +1,145 @@
+//! Large resource files answered in pieces: read from the data source a
+//! chunk at a time and sent as one `contents` entry per chunk, so neither
+//! the file nor the response is ever held whole.
+
+use crate::mime;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde_json::{Value, json};
+
+/// Bytes of the file in each `contents` entry. A multiple of 3, so blob
+/// entries carry no base64 padding and their concatenation decodes to the
+/// file.
+pub const CHUNK_SIZE: usize = 48 * 1024;
+
+/// A file read in pieces, see [`crate::MCPDataSource::stream_bytes`].
+pub struct ByteStream {
+    /// Length of the file, when the source knows it before reading.
+    pub size: Option<u64>,
+    pub chunks: BoxStream<'static, anyhow::Result<Bytes>>,
+}
+
+impl ByteStream {
+    /// The whole of `bytes`, in one piece.
+    pub fn whole(bytes: Bytes) -> Self {
+        Self {
+            size: Some(bytes.len() as u64),
+            chunks: stream::once(async { Ok(bytes) }).boxed(),
+        }
+    }
+
+    /// Reads the rest of the file into memory, a file in one piece as it
+    /// is.
+    pub async fn collect(mut self) -> anyhow::Result<Bytes> {
+        let Some(first) = self.chunks.next().await.transpose()? else {
+            return Ok(Bytes::new());
+        };
+        let Some(second) = self.chunks.next().await.transpose()? else {
+            return Ok(first);
+        };
+        let mut bytes = Vec::with_capacity(self.size.unwrap_or_default() as usize);
+        bytes.extend_from_slice(&first);
+        bytes.extend_from_slice(&second);
+        while let Some(chunk) = self.chunks.next().await {
+            bytes.extend_from_slice(&chunk?);
+        }
+        Ok(bytes.into())
+    }
+}
+
+/// The contents of the resource `uri`, still to be read from its file of
+/// type `mime`.
+pub struct ChunkedContents {
+    pub uri: String,
+    pub mime: String,
+    pub size: Option<u64>,
+    /// Fields every entry carries besides its piece of the file, such as
+    /// the `title` and `annotations` the manifest declares.
+    pub description: serde_json::Map<String, Value>,
+    bytes: ByteStream,
+}
+
+impl ChunkedContents {
+    pub(crate) fn new(uri: &str, mime: &str, bytes: ByteStream) -> Self {
+        Self {
+            uri: uri.to_string(),
+            mime: mime.to_string(),
+            size: bytes.size,
+            description: serde_json::Map::new(),
+            bytes,
+        }
+    }
+
+    /// The `contents` entries, one per [`CHUNK_SIZE`] bytes of the file,
+    /// and for text cut back to the last whole character. A stream ending in
+    /// an error means the file couldn't be read to its end.
+    pub fn entries(self) -> BoxStream<'static, anyhow::Result<Value>> {
+        let Self {
+            uri,
+            mime,
+            description,
+            bytes: ByteStream { chunks, .. },
+            ..
+        } = self;
+        let text = mime::is_text(&mime);
+        stream::unfold(
+            (chunks, Vec::new(), false),
+            move |(mut chunks, mut pending, mut done)| {
+                let (uri, mime, description) = (uri.clone(), mime.clone(), description.clone());
+                async move {
+                    while !done && pending.len() < CHUNK_SIZE {
+                        match chunks.next().await {
+                            Some(Ok(chunk)) => pending.extend_from_slice(&chunk),
+                            Some(Err(e)) => return Some((Err(e), (chunks, Vec::new(), true))),
+                            None => done = true,
+                        }
+                    }
+                    if pending.is_empty() {
+                        return None;
+                    }
+                    let mut end = pending.len().min(CHUNK_SIZE);
+                    if text && end < pending.len() {
+                        end = utf8_boundary(&pending, end);
+                    }
+                    let rest = pending.split_off(end);
+                    let entry = if text {
+                        String::from_utf8(pending)
+                            .map(|text| json!({ "uri": uri, "mimeType": mime, "text": text }))
+                            .map_err(|_| anyhow::anyhow!("{uri} is not UTF-8 text"))
+                    } else {
+                        crate::blob_entry(&uri, &mime, &pending)
+                    }
+                    .map(|mut entry| {
+                        if let Some(entry) = entry.as_object_mut() {
+                            entry.extend(description);
+                        }
+                        entry
+                    });
+                    Some((entry, (chunks, rest, done)))
+                }
+            },
+        )
+        .boxed()
+    }
+
+    /// The contents read whole, for transports that can't send them in
+    /// pieces.
+    pub async fn collect(self) -> anyhow::Result<Vec<Value>> {
+        let mut entries = self.entries();
+        let mut contents = Vec::new();
+        while let Some(entry) = entries.next().await {
+            contents.push(entry?);
+        }
+        Ok(contents)
+    }
+}
+
+/// The largest index at most `end` that starts a UTF-8 character of
+/// `bytes`; `end` itself when no character starts within the last four
+/// bytes, leaving the invalid text to fail decoding.
+fn utf8_boundary(bytes: &[u8], end: usize) -> usize {
+    (end.saturating_sub(3)..=end)
+        .rev()
+        .find(|&index| bytes.get(index).is_none_or(|&b| (b as i8) >= -0x40))
+        .unwrap_or(end)
+}
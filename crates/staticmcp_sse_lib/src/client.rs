@@ -0,0 +1,49 @@
+//! What an MCP client says about itself in `initialize`: its `clientInfo`
+//! and its capabilities, kept for the rest of its session so answers can
+//! be attributed to, or tailored for, the client asking.
+
+use crate::MCPRequest;
+use serde::Serialize;
+use serde_json::Value;
+
+/// A client as its `initialize` request described it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The `capabilities` it declared, such as `roots` or `sampling`, as
+    /// sent. Left out of audit records.
+    #[serde(skip)]
+    pub capabilities: Value,
+}
+
+impl ClientInfo {
+    /// The client `request` describes, when it is an `initialize` naming
+    /// one.
+    pub fn from_initialize(request: &MCPRequest) -> Option<Self> {
+        if request.method != "initialize" {
+            return None;
+        }
+        let params = request.params.as_ref()?;
+        let info = params.get("clientInfo")?;
+        Some(Self {
+            name: info.get("name")?.as_str()?.to_string(),
+            version: info
+                .get("version")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            capabilities: params.get("capabilities").cloned().unwrap_or(Value::Null),
+        })
+    }
+}
+
+impl std::fmt::Display for ClientInfo {
+    /// `name/version`, or just the name without a version.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}/{version}", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! `completion/complete` answered from files listing an argument's values:
+//! `completions/prompts/{prompt}/{argument}.json` for a prompt's arguments
+//! and `completions/resources/{uri template}/{variable}.json` for a resource
+//! template's, names percent-encoded as one path segment each. A file holds
+//! an array of strings, or an object with one under `values`.
+
+use crate::resolver::encode_segment;
+use serde_json::{Value, json};
+
+/// The most values one answer offers, as MCP allows.
+pub const MAX_VALUES: usize = 100;
+
+/// What a completion is asked for.
+#[derive(Debug, Clone, Copy)]
+pub enum Reference<'a> {
+    /// An argument of the prompt with this name.
+    Prompt(&'a str),
+    /// A variable of the resource template with this URI template.
+    Resource(&'a str),
+}
+
+impl<'a> Reference<'a> {
+    /// The reference `params.ref` makes, when it is one.
+    pub fn of(params: &'a Value) -> Option<Self> {
+        let reference = params.get("ref")?;
+        match reference.get("type")?.as_str()? {
+            "ref/prompt" => Some(Self::Prompt(reference.get("name")?.as_str()?)),
+            "ref/resource" => Some(Self::Resource(reference.get("uri")?.as_str()?)),
+            _ => None,
+        }
+    }
+
+    /// The file listing the values of `argument`.
+    pub fn path(&self, argument: &str) -> anyhow::Result<String> {
+        let (kind, name) = match self {
+            Self::Prompt(name) => ("prompts", name),
+            Self::Resource(uri_template) => ("resources", uri_template),
+        };
+        Ok(format!(
+            "completions/{kind}/{}/{}.json",
+            encode_segment(name)?,
+            encode_segment(argument)?
+        ))
+    }
+}
+
+/// The `completion/complete` result offering the values `file` lists that
+/// start with `prefix`, compared case-insensitively.
+pub fn complete(file: &Value, prefix: &str) -> Value {
+    let prefix = prefix.to_lowercase();
+    let matching = file
+        .get("values")
+        .unwrap_or(file)
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter(|value| value.to_lowercase().starts_with(&prefix))
+        .collect::<Vec<_>>();
+    json!({
+        "completion": {
+            "values": &matching[..matching.len().min(MAX_VALUES)],
+            "total": matching.len(),
+            "hasMore": matching.len() > MAX_VALUES,
+        }
+    })
+}
@@ -0,0 +1,58 @@
+//! What a data source is told about the request a file is read for, so a
+//! source of an embedder's own can route by tenant, add the caller's
+//! credentials to its own requests, or give up once the request's deadline
+//! passes.
+
+use crate::client::ClientInfo;
+use crate::policy::Principal;
+use crate::{MCPBridge, protocol};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The request a file is read for. Reads outside a request, such as the
+/// manifest's at startup, get [`RequestContext::default`], naming no one.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    /// The `/events` session the request was posted to.
+    pub session_id: Option<String>,
+    /// The MCP client, as its session's `initialize` described it.
+    pub client: Option<Arc<ClientInfo>>,
+    /// The caller, as the server authenticated it.
+    pub principal: Option<Principal>,
+    /// The protocol version the answer is built for.
+    pub protocol_version: &'static str,
+    /// When the server gives up on the request, under `--request-timeout`.
+    pub deadline: Option<Instant>,
+}
+
+impl Default for RequestContext {
+    fn default() -> Self {
+        Self {
+            session_id: None,
+            client: None,
+            principal: None,
+            protocol_version: protocol::LATEST,
+            deadline: None,
+        }
+    }
+}
+
+impl RequestContext {
+    /// The context of the request being handled, as the server's layers
+    /// recorded it.
+    pub fn current() -> Self {
+        Self {
+            session_id: MCPBridge::session_id(),
+            client: MCPBridge::client_info(),
+            principal: MCPBridge::principal(),
+            protocol_version: MCPBridge::protocol_version(),
+            deadline: MCPBridge::deadline(),
+        }
+    }
+
+    /// Time left before [`Self::deadline`], zero once it has passed.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
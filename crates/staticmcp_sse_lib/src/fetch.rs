@@ -0,0 +1,97 @@
+use crate::{MCPDataSource, MCPManifest, RequestContext};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::Value;
+use serde_json::value::RawValue;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// Data source backed by the global `fetch` function, for browsers and
+/// Workers runtimes where neither the filesystem nor reqwest's native client
+/// are available.
+pub struct FetchDataSource {
+    pub base_url: String,
+}
+
+impl FetchDataSource {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The body of the file `relative_path`.
+    async fn body(&self, relative_path: &str) -> anyhow::Result<Bytes> {
+        let url = format!(
+            "{}/{}",
+            self.base_url,
+            crate::resolver::url_path(relative_path)
+        );
+
+        let response = fetch(&url).await?;
+        if !response.ok() {
+            anyhow::bail!("HTTP {}: {}", response.status(), response.status_text());
+        }
+
+        let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+            .await
+            .map_err(js_error)?;
+        Ok(js_sys::Uint8Array::new(&buffer).to_vec().into())
+    }
+}
+
+fn js_error(value: JsValue) -> anyhow::Error {
+    anyhow::anyhow!(value.as_string().unwrap_or_else(|| format!("{value:?}")))
+}
+
+// `fetch` is looked up on the global object rather than through `Window` so the
+// same code works in Workers, where there is no window.
+async fn fetch(url: &str) -> anyhow::Result<web_sys::Response> {
+    let global = js_sys::global();
+    let fetch_fn: js_sys::Function = js_sys::Reflect::get(&global, &JsValue::from_str("fetch"))
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("fetch is not available in this environment"))?;
+
+    let promise: js_sys::Promise = fetch_fn
+        .call1(&global, &JsValue::from_str(url))
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(js_error)?;
+
+    JsFuture::from(promise)
+        .await
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(js_error)
+}
+
+#[async_trait(?Send)]
+impl MCPDataSource for FetchDataSource {
+    async fn load_json(
+        &self,
+        relative_path: &str,
+        _context: &RequestContext,
+    ) -> anyhow::Result<Value> {
+        crate::parse_json(&self.body(relative_path).await?)
+    }
+
+    async fn load_raw_json(
+        &self,
+        relative_path: &str,
+        _context: &RequestContext,
+    ) -> anyhow::Result<Box<RawValue>> {
+        crate::parse_raw_json(self.body(relative_path).await?)
+    }
+
+    async fn load_manifest(&self) -> anyhow::Result<MCPManifest> {
+        crate::manifest::parse(
+            self.load_json("mcp.json", &RequestContext::default())
+                .await?,
+        )
+    }
+
+    async fn load_bytes(&self, relative_path: &str) -> anyhow::Result<Bytes> {
+        self.body(relative_path).await
+    }
+}
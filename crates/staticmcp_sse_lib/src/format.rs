@@ -0,0 +1,62 @@
+//! Versions of the StaticMCP manifest format, declared by a manifest's
+//! `staticmcpVersion`; a manifest without one is version 1.
+//!
+//! - `1`: resources, resource templates and tools, with `pathTemplate`s.
+//! - `2`: also `prompts` answered from files, `completions` of prompt and
+//!   resource template arguments, `checksums` of the site's files, and
+//!   [`rewrite`](crate::rewrite) rules.
+//!
+//! What a manifest's version lacks is ignored, so a site can't come to rely
+//! on what bridges reading only that version don't serve. Manifests of a
+//! version newer than the bridge's are refused rather than served in part.
+
+use serde_json::Value;
+
+pub const FIELD: &str = "staticmcpVersion";
+
+/// The newest version the bridge reads.
+pub const LATEST: u64 = 2;
+
+/// The keys versions after the first added: where each is found, as a JSON
+/// pointer to its object, its name, and the version adding it.
+pub const INTRODUCED: &[(&str, &str, u64)] = &[
+    ("", "checksums", 2),
+    ("", "rewrites", 2),
+    ("/capabilities", "prompts", 2),
+    ("/capabilities", "completions", 2),
+];
+
+/// The version `manifest` declares, or why the bridge can't read it.
+pub fn version(manifest: &Value) -> Result<u64, String> {
+    let declared = match manifest.get(FIELD) {
+        None | Some(Value::Null) => return Ok(1),
+        Some(declared) => declared,
+    };
+    match declared.as_u64() {
+        Some(version @ 1..=LATEST) => Ok(version),
+        Some(version) if version > LATEST => Err(format!(
+            "`{FIELD}` {version} is newer than this bridge reads, which is up to {LATEST}; \
+             upgrade the bridge to serve this site"
+        )),
+        _ => Err(format!(
+            "expected `{FIELD}` to be a version from 1 to {LATEST}, found {declared}"
+        )),
+    }
+}
+
+/// Removes from `manifest` the keys `version` lacks, returning the JSON
+/// pointer of each one removed.
+pub fn strip(manifest: &mut Value, version: u64) -> Vec<String> {
+    let mut removed = Vec::new();
+    for &(parent, key, introduced) in INTRODUCED {
+        if version >= introduced {
+            continue;
+        }
+        if let Some(Value::Object(object)) = manifest.pointer_mut(parent)
+            && object.remove(key).is_some()
+        {
+            removed.push(format!("{parent}/{key}"));
+        }
+    }
+    removed
+}
@@ -0,0 +1,116 @@
+//! Tools whose results are computed rather than stored as files: a manifest
+//! entry's `handler` names a Rhai script or a WASM module the bridge reads
+//! from the data source and runs for each call, sandboxed and bounded by
+//! [`HandlerLimits`].
+//!
+//! A script sees the call's arguments as the map `args` and returns its
+//! result. A module exports its `memory`, `alloc(len) -> ptr`, into which
+//! the bridge writes the arguments as JSON, and `call(ptr, len) -> i64`,
+//! returning where its result's JSON is as `ptr << 32 | len`; it may import
+//! nothing. Either way a string result is answered as text, anything else
+//! as a file holding it would be. Neither can reach the file system or
+//! network, so at most they spend their operations, fuel, memory and time.
+
+use crate::MCPDataSource;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[cfg(feature = "rhai")]
+mod script;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+/// Bounds on each run of a handler, past which the call fails.
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerLimits {
+    /// Time a run may take.
+    pub timeout: Duration,
+    /// Rhai operations, such as expressions evaluated and loop iterations,
+    /// a script may perform.
+    pub max_operations: u64,
+    /// Fuel a WASM module may burn, about one per instruction run.
+    pub fuel: u64,
+    /// Bytes of linear memory a WASM module may grow to.
+    pub max_memory: usize,
+}
+
+impl Default for HandlerLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(1),
+            max_operations: 1_000_000,
+            fuel: 100_000_000,
+            max_memory: 16 << 20,
+        }
+    }
+}
+
+/// The result of calling the tool `handler` computes with `arguments`,
+/// reading the handler from `source`.
+pub async fn call(
+    source: &dyn MCPDataSource,
+    handler: &str,
+    arguments: &HashMap<String, Value>,
+    limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    let output = if handler.ends_with(".rhai") {
+        run_script(source, handler, arguments, limits).await?
+    } else if handler.ends_with(".wasm") {
+        run_module(source, handler, arguments, limits).await?
+    } else {
+        anyhow::bail!("{handler} is neither a `.rhai` script nor a `.wasm` module");
+    };
+    Ok(match output {
+        Value::String(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+        output => output,
+    })
+}
+
+#[cfg(feature = "rhai")]
+async fn run_script(
+    source: &dyn MCPDataSource,
+    handler: &str,
+    arguments: &HashMap<String, Value>,
+    limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    let code = source.load_bytes(handler).await?;
+    let code =
+        String::from_utf8(code.to_vec()).map_err(|_| anyhow::anyhow!("{handler} is not UTF-8"))?;
+    script::run(code, arguments, limits).await
+}
+
+#[cfg(not(feature = "rhai"))]
+async fn run_script(
+    _source: &dyn MCPDataSource,
+    handler: &str,
+    _arguments: &HashMap<String, Value>,
+    _limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    anyhow::bail!(
+        "{handler} is a Rhai script, and this bridge was built without the `rhai` feature"
+    )
+}
+
+#[cfg(feature = "wasm")]
+async fn run_module(
+    source: &dyn MCPDataSource,
+    handler: &str,
+    arguments: &HashMap<String, Value>,
+    limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    let code = source.load_bytes(handler).await?;
+    wasm::run(handler, code, arguments, limits).await
+}
+
+#[cfg(not(feature = "wasm"))]
+async fn run_module(
+    _source: &dyn MCPDataSource,
+    handler: &str,
+    _arguments: &HashMap<String, Value>,
+    _limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    anyhow::bail!(
+        "{handler} is a WASM module, and this bridge was built without the `wasm` feature"
+    )
+}
@@ -0,0 +1,125 @@
+//! Rhai handlers, each run on a blocking thread by an engine of its own
+//! that can't import modules, `eval` code or print to the terminal.
+
+use super::HandlerLimits;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::debug;
+
+/// Nesting a script may reach, in calls and in expressions.
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPR_DEPTH: usize = 64;
+
+/// The largest string, array and map a script may build.
+const MAX_STRING_BYTES: usize = 1 << 20;
+const MAX_ITEMS: usize = 100_000;
+
+/// The value `code` evaluates to with `arguments` as `args`.
+pub async fn run(
+    code: String,
+    arguments: &HashMap<String, Value>,
+    limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    let arguments = rhai::serde::to_dynamic(arguments)?;
+    tokio::task::spawn_blocking(move || evaluate(&code, arguments, limits)).await?
+}
+
+fn evaluate(code: &str, arguments: Dynamic, limits: HandlerLimits) -> anyhow::Result<Value> {
+    let engine = engine(limits);
+    let mut scope = Scope::new();
+    scope.push("args", arguments);
+    match engine.eval_with_scope::<Dynamic>(&mut scope, code) {
+        Ok(output) => Ok(rhai::serde::from_dynamic(&output)?),
+        Err(error) => match *error {
+            EvalAltResult::ErrorTerminated(..) => {
+                anyhow::bail!("the script ran longer than {:?}", limits.timeout)
+            }
+            EvalAltResult::ErrorTooManyOperations(..) => anyhow::bail!(
+                "the script ran more than {} operations",
+                limits.max_operations
+            ),
+            error => Err(anyhow::anyhow!("the script failed: {error}")),
+        },
+    }
+}
+
+fn engine(limits: HandlerLimits) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(limits.max_operations)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH)
+        .set_max_string_size(MAX_STRING_BYTES)
+        .set_max_array_size(MAX_ITEMS)
+        .set_max_map_size(MAX_ITEMS)
+        .on_print(|text| debug!(text, "handler printed"))
+        .on_debug(|text, _, position| debug!(text, %position, "handler debug"));
+    let deadline = Instant::now() + limits.timeout;
+    engine.on_progress(move |_| (Instant::now() >= deadline).then(Dynamic::default));
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn eval(code: &str, arguments: Value, limits: HandlerLimits) -> anyhow::Result<Value> {
+        let arguments = rhai::serde::to_dynamic(arguments).unwrap();
+        evaluate(code, arguments, limits)
+    }
+
+    #[test]
+    fn computes_from_arguments() {
+        let code = r#"let slug = args.title.to_lower(); slug.replace(" ", "-"); slug"#;
+        let slug = eval(
+            code,
+            json!({ "title": "Hello World" }),
+            HandlerLimits::default(),
+        );
+        assert_eq!(slug.unwrap(), json!("hello-world"));
+    }
+
+    #[test]
+    fn returns_maps_as_json() {
+        let code = "#{ total: args.a + args.b, items: [args.a, args.b] }";
+        let sum = eval(code, json!({ "a": 2, "b": 3 }), HandlerLimits::default());
+        assert_eq!(sum.unwrap(), json!({ "total": 5, "items": [2, 3] }));
+    }
+
+    #[test]
+    fn stops_after_max_operations() {
+        let limits = HandlerLimits {
+            max_operations: 1_000,
+            ..HandlerLimits::default()
+        };
+        let error = eval("loop {}", json!({}), limits).unwrap_err();
+        assert!(error.to_string().contains("1000 operations"), "{error}");
+    }
+
+    #[test]
+    fn stops_after_timeout() {
+        let limits = HandlerLimits {
+            timeout: Duration::from_millis(50),
+            max_operations: 0,
+            ..HandlerLimits::default()
+        };
+        let started = Instant::now();
+        let error = eval("loop {}", json!({}), limits).unwrap_err();
+        assert!(error.to_string().contains("ran longer"), "{error}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn cannot_import_or_eval() {
+        let limits = HandlerLimits::default();
+        assert!(eval(r#"import "secrets" as s; 1"#, json!({}), limits).is_err());
+        assert!(eval(r#"eval("1 + 1")"#, json!({}), limits).is_err());
+    }
+}
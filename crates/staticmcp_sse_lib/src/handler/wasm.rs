@@ -0,0 +1,199 @@
+//! WASM handlers, run by wasmtime on a blocking thread, each call in a
+//! store of its own with nothing imported, so a module only reaches the
+//! memory it was given.
+//!
+//! Fuel bounds the instructions a call runs and a store limiter the memory
+//! it grows to; a background thread advancing the engine's epoch every
+//! [`TICK`] interrupts calls that outlive their timeout.
+
+use super::HandlerLimits;
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+/// How often the engine's epoch advances, and so how closely timeouts are
+/// kept.
+const TICK: Duration = Duration::from_millis(10);
+
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true).epoch_interruption(true);
+    let engine = Engine::new(&config).expect("fuel and epochs are supported everywhere");
+    let ticker = engine.clone();
+    std::thread::Builder::new()
+        .name("wasm-epoch".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(TICK);
+                ticker.increment_epoch();
+            }
+        })
+        .expect("failed to start the WASM epoch thread");
+    engine
+});
+
+/// Modules compiled so far, by handler path, with the bytes each was
+/// compiled from, so a module is compiled again only when it changes.
+static MODULES: LazyLock<Mutex<HashMap<String, (Bytes, Module)>>> = LazyLock::new(Mutex::default);
+
+/// The result the module `code`, read from `handler`, computes from
+/// `arguments`.
+pub async fn run(
+    handler: &str,
+    code: Bytes,
+    arguments: &HashMap<String, Value>,
+    limits: HandlerLimits,
+) -> anyhow::Result<Value> {
+    let input = serde_json::to_vec(arguments)?;
+    let handler = handler.to_string();
+    tokio::task::spawn_blocking(move || {
+        let module = module(&handler, code)?;
+        execute(&module, &input, limits)
+    })
+    .await?
+}
+
+fn module(handler: &str, code: Bytes) -> anyhow::Result<Module> {
+    let modules = || MODULES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((compiled, module)) = modules().get(handler)
+        && *compiled == code
+    {
+        return Ok(module.clone());
+    }
+    let module = Module::from_binary(&ENGINE, &code)
+        .map_err(|e| anyhow::anyhow!("{handler} is not a valid WASM module: {e}"))?;
+    modules().insert(handler.to_string(), (code, module.clone()));
+    Ok(module)
+}
+
+fn execute(module: &Module, input: &[u8], limits: HandlerLimits) -> anyhow::Result<Value> {
+    let store_limits = StoreLimitsBuilder::new()
+        .memory_size(limits.max_memory)
+        .instances(1)
+        .memories(1)
+        .tables(1)
+        .build();
+    let mut store = Store::new(&ENGINE, store_limits);
+    store.limiter(|limits: &mut StoreLimits| limits);
+    store.set_fuel(limits.fuel)?;
+    let ticks = limits.timeout.as_nanos().div_ceil(TICK.as_nanos()).max(1);
+    store.set_epoch_deadline(u64::try_from(ticks).unwrap_or(u64::MAX));
+
+    let instance = Instance::new(&mut store, module, &[])
+        .map_err(|e| failure(e.into(), limits, "couldn't be instantiated"))?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| anyhow::anyhow!("the module exports no `memory`"))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(|e| anyhow::anyhow!("the module exports no `alloc(i32) -> i32`: {e}"))?;
+    let call = instance
+        .get_typed_func::<(i32, i32), i64>(&mut store, "call")
+        .map_err(|e| anyhow::anyhow!("the module exports no `call(i32, i32) -> i64`: {e}"))?;
+
+    let len = i32::try_from(input.len())?;
+    let ptr = alloc
+        .call(&mut store, len)
+        .map_err(|e| failure(e.into(), limits, "failed"))?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input)
+        .map_err(|_| anyhow::anyhow!("`alloc` returned memory out of bounds"))?;
+    let packed = call
+        .call(&mut store, (ptr, len))
+        .map_err(|e| failure(e.into(), limits, "failed"))? as u64;
+
+    let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    let output = memory
+        .data(&store)
+        .get(ptr..ptr + len)
+        .ok_or_else(|| anyhow::anyhow!("`call` returned a result out of bounds"))?;
+    serde_json::from_slice(output).map_err(|e| anyhow::anyhow!("the result isn't JSON: {e}"))
+}
+
+/// `error` from running a module, naming the limit it ran into.
+fn failure(error: anyhow::Error, limits: HandlerLimits, what: &str) -> anyhow::Error {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => anyhow::anyhow!("the module ran out of its {} fuel", limits.fuel),
+        Some(Trap::Interrupt) => {
+            anyhow::anyhow!("the module ran longer than {:?}", limits.timeout)
+        }
+        _ => anyhow::anyhow!("the module {what}: {error:#}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A module echoing its input back, after running `work` in `call`.
+    fn module(work: &str) -> Module {
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) i32.const 16)
+                (func (export "call") (param $ptr i32) (param $len i32) (result i64)
+                    {work}
+                    (i64.or
+                        (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                        (i64.extend_i32_u (local.get $len)))))"#
+        );
+        Module::new(&ENGINE, wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    fn input() -> Vec<u8> {
+        serde_json::to_vec(&json!({ "name": "world" })).unwrap()
+    }
+
+    #[test]
+    fn passes_arguments_and_returns_json() {
+        let output = execute(&module(""), &input(), HandlerLimits::default()).unwrap();
+        assert_eq!(output, json!({ "name": "world" }));
+    }
+
+    #[test]
+    fn stops_when_out_of_fuel() {
+        let limits = HandlerLimits {
+            fuel: 10_000,
+            ..HandlerLimits::default()
+        };
+        let error = execute(&module("(loop br 0)"), &input(), limits).unwrap_err();
+        assert!(error.to_string().contains("10000 fuel"), "{error}");
+    }
+
+    #[test]
+    fn stops_after_timeout() {
+        let limits = HandlerLimits {
+            fuel: u64::MAX,
+            timeout: Duration::from_millis(50),
+            ..HandlerLimits::default()
+        };
+        let error = execute(&module("(loop br 0)"), &input(), limits).unwrap_err();
+        assert!(error.to_string().contains("ran longer"), "{error}");
+    }
+
+    #[test]
+    fn cannot_grow_past_max_memory() {
+        let limits = HandlerLimits {
+            max_memory: 2 << 16,
+            ..HandlerLimits::default()
+        };
+        // Growing by two pages would reach three; failing, it returns -1,
+        // which the module turns into a trap.
+        let work = "(if (i32.eq (memory.grow (i32.const 2)) (i32.const -1)) (then unreachable))";
+        let error = execute(&module(work), &input(), limits).unwrap_err();
+        assert!(error.to_string().contains("failed"), "{error}");
+        assert!(execute(&module(work), &input(), HandlerLimits::default()).is_ok());
+    }
+
+    #[test]
+    fn rejects_modules_that_import() {
+        let wat = r#"(module (import "env" "open" (func)) (memory (export "memory") 1))"#;
+        let module = Module::new(&ENGINE, wat::parse_str(wat).unwrap()).unwrap();
+        let error = execute(&module, &input(), HandlerLimits::default()).unwrap_err();
+        assert!(error.to_string().contains("instantiated"), "{error}");
+    }
+}
@@ -0,0 +1,36 @@
+//! Hooks run around every request the bridge answers, for checks, rewrites,
+//! metrics or caching of an embedder's own without changing
+//! [`MCPBridge::reply`](crate::MCPBridge::reply).
+//!
+//! Hooks run in the order they were added, before and after alike. A
+//! `before_request` answering or refusing the request skips the bridge and
+//! the remaining hooks' `before_request`, but every hook still sees the
+//! response. Reads sent in chunks are not seen by `after_response`, their
+//! contents not having been read yet.
+
+use crate::{MCPError, MCPRequest, MCPResponse};
+use async_trait::async_trait;
+
+// JS futures are not `Send`, so on wasm32 the trait (and every impl) drops the bound.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait BridgeHook: Send + Sync {
+    /// Called before the bridge looks at `request`, which it may rewrite.
+    /// Returning a response answers the request with it instead, as a
+    /// cache would; returning an error refuses it, as an authorization
+    /// check would.
+    async fn before_request(
+        &self,
+        _request: &mut MCPRequest,
+    ) -> Result<Option<MCPResponse>, MCPError> {
+        Ok(None)
+    }
+
+    /// Called with every successful `response` to `request`, which it may
+    /// rewrite.
+    async fn after_response(&self, _request: &MCPRequest, _response: &mut MCPResponse) {}
+
+    /// Called with the error answering `request`, whether the bridge's or a
+    /// `before_request` refusal, which it may rewrite.
+    async fn on_error(&self, _request: &MCPRequest, _error: &mut MCPError) {}
+}
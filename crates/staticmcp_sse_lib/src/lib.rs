@@ -1,12 +1,72 @@
 use async_trait::async_trait;
+#[cfg(feature = "sse")]
 use axum::response::sse::Event;
+use bytes::Bytes;
+#[cfg(feature = "sse")]
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_json::{Value, json};
+use std::borrow::Cow;
 use std::collections::HashMap;
+#[cfg(feature = "local")]
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
+#[cfg(feature = "local")]
 use tokio::fs;
+use tracing::{Instrument, debug, info, warn};
+
+pub mod audit;
+pub mod checksum;
+pub mod chunked;
+pub mod client;
+pub mod completion;
+pub mod context;
+pub mod format;
+pub mod handler;
+pub mod hook;
+pub mod locale;
+pub mod manifest;
+pub mod meta;
+pub mod metrics;
+pub mod mime;
+pub mod normalize;
+mod path_template;
+pub mod policy;
+pub mod protocol;
+pub mod request;
+pub mod resolver;
+pub mod rewrite;
+#[cfg(feature = "search")]
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub use server::router::{RouterOptions, router};
+
+pub use context::RequestContext;
+pub mod singleflight;
+pub mod template;
+#[cfg(feature = "remote")]
+pub mod upstream;
+
+use audit::{AuditEntry, AuditOutcome, AuditSink};
+use chunked::{ByteStream, ChunkedContents};
+use client::ClientInfo;
+use hook::BridgeHook;
+use metrics::BridgeMetrics;
+use policy::{AccessPolicy, Principal};
+use resolver::{DefaultPathResolver, PathResolver, encode_segment, path_value};
+use rewrite::{Rewrite, RewriteRule};
+
+#[cfg(feature = "precompressed")]
+pub mod precompressed;
+
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+mod fetch;
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+pub use fetch::FetchDataSource;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MCPRequest {
@@ -34,11 +94,125 @@ pub struct MCPError {
     pub data: Option<Value>,
 }
 
+impl MCPError {
+    /// Adds `requestId` to `data`, keeping any object fields already there.
+    pub fn attach_request_id(&mut self, request_id: String) {
+        match &mut self.data {
+            Some(Value::Object(data)) => {
+                data.insert("requestId".to_string(), Value::String(request_id));
+            }
+            Some(_) => {}
+            None => self.data = Some(json!({ "requestId": request_id })),
+        }
+    }
+}
+
+/// The answer to a request: a whole response, a resource read whose
+/// contents are still to be read from a large file and sent in chunks, or a
+/// result file answered as it was read.
+pub enum MCPReply {
+    Response(MCPResponse),
+    Chunked {
+        id: Option<Value>,
+        contents: ChunkedContents,
+    },
+    Verbatim {
+        id: Option<Value>,
+        result: Box<RawValue>,
+    },
+}
+
+impl MCPReply {
+    /// The reply as one response, chunked contents read whole.
+    pub async fn into_response(self) -> MCPResponse {
+        let (id, contents) = match self {
+            Self::Response(response) => return response,
+            Self::Chunked { id, contents } => (id, contents),
+            Self::Verbatim { id, result } => {
+                return MCPResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: serde_json::from_str(result.get()).ok(),
+                    error: None,
+                };
+            }
+        };
+        let uri = contents.uri.clone();
+        match contents.collect().await {
+            Ok(contents) => MCPResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: Some(json!({ "contents": contents })),
+                error: None,
+            },
+            Err(e) => {
+                warn!(uri, error = %e, "failed to read resource");
+                let mut response = MCPResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: None,
+                    error: Some(MCPError {
+                        code: -32603,
+                        message: format!("Failed to read resource {uri}"),
+                        data: None,
+                    }),
+                };
+                MCPBridge::tag_error(&mut response);
+                response
+            }
+        }
+    }
+}
+
+impl MCPReply {
+    /// The reply serialized as one JSON-RPC message, a verbatim result
+    /// copied in as it was read.
+    pub async fn into_json(self) -> Bytes {
+        #[derive(Serialize)]
+        struct Verbatim<'a> {
+            jsonrpc: &'a str,
+            id: Option<Value>,
+            result: &'a RawValue,
+        }
+        let json = match self {
+            Self::Verbatim { id, result } => serde_json::to_vec(&Verbatim {
+                jsonrpc: "2.0",
+                id,
+                result: &result,
+            }),
+            reply => serde_json::to_vec(&reply.into_response().await),
+        };
+        json.map(Bytes::from).unwrap_or_default()
+    }
+}
+
+impl From<MCPResponse> for MCPReply {
+    fn from(response: MCPResponse) -> Self {
+        Self::Response(response)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MCPManifest {
+    /// The [`format`] version the manifest is written in, version 1 when
+    /// it doesn't say.
+    #[serde(
+        rename = "staticmcpVersion",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub staticmcp_version: Option<u64>,
     #[serde(rename = "serverInfo")]
     pub server_info: Option<ServerInfo>,
     pub capabilities: Option<Capabilities>,
+    /// The `sha256:` checksum of each site file, such as
+    /// `tools/weather.json`, that files read are checked against.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub checksums: HashMap<String, String>,
+    /// [`rewrite`] rules for the site's URIs and paths, after the bridge's
+    /// own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rewrites: Vec<RewriteRule>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,90 +223,936 @@ pub struct ServerInfo {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Capabilities {
-    pub resources: Option<Vec<Value>>,
-    pub tools: Option<Vec<Value>>,
+    pub resources: Option<Vec<ResourceDef>>,
+    #[serde(
+        rename = "resourceTemplates",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub resource_templates: Option<Vec<ResourceTemplateDef>>,
+    pub tools: Option<Vec<ToolDef>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<Vec<PromptDef>>,
+    /// Present when `completion/complete` is answered from the site's
+    /// [`completion`] files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completions: Option<Value>,
 }
 
-#[async_trait]
+/// A resource declared in the manifest. Fields the bridge doesn't use, such
+/// as `annotations`, are kept in `extra` and listed to clients unchanged.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceDef {
+    pub uri: String,
+    /// Former URIs still read as this resource.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// The file the resource is read from, relative to the site root, in
+    /// place of the path derived from its URI.
+    #[serde(
+        rename = "pathTemplate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub path_template: Option<String>,
+    /// MIME types the resource is also published as, most preferred first,
+    /// each in a file beside its own with the type's extension, such as
+    /// `resources/guide.md` for `text/markdown`; `application/json` is the
+    /// resource's usual file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    /// Always read from the bridge's upstream MCP server, not a file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upstream: bool,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// A family of resources declared in the manifest by an RFC 6570 URI
+/// template, such as `file://docs/{page}`, for sites with too many files to
+/// list one by one.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceTemplateDef {
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// The file each matching resource is read from, such as
+    /// `pages/{page}.json`, filled in from the URI template's variables.
+    #[serde(
+        rename = "pathTemplate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub path_template: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl ResourceTemplateDef {
+    /// Whether expanding the template can produce `uri`. A `{var}` stands for
+    /// one path segment or part of one, `{+var}` for any text including `/`;
+    /// URIs with a `..` segment never match, so they can't climb out of the
+    /// site.
+    pub fn matches(&self, uri: &str) -> bool {
+        self.captures(uri).is_some()
+    }
+
+    fn captures(&self, uri: &str) -> Option<HashMap<String, String>> {
+        if uri.split('/').any(|segment| segment == "..") {
+            return None;
+        }
+        path_template::captures(&self.uri_template, uri)
+    }
+
+    /// The `pathTemplate` file for `uri`, when the template has one and
+    /// matches it.
+    fn path(&self, uri: &str) -> Option<String> {
+        let path_template = self.path_template.as_deref()?;
+        let captures = self.captures(uri)?;
+        path_template::expand(path_template, |name| captures.get(name).cloned()).ok()
+    }
+}
+
+/// A prompt declared in the manifest, answered from `prompts/{name}.json`
+/// with its `{{args.NAME}}` placeholders filled in from the arguments.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptDef {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<PromptArgument>,
+    /// The file answering each get, such as `prompts/review/{language}.json`,
+    /// filled in from the arguments, in place of `prompts/{name}.json`.
+    #[serde(
+        rename = "pathTemplate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub path_template: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// A tool declared in the manifest. Fields the bridge doesn't use, such as
+/// `outputSchema`, are kept in `extra` and listed to clients unchanged.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolDef {
+    pub name: String,
+    /// Former names still called as this tool.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The JSON Schema of the tool's arguments, kept as written.
+    #[serde(
+        rename = "inputSchema",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub input_schema: Option<Value>,
+    /// The file answering each call, such as `tools/search/{query}/{page}.json`,
+    /// filled in from the arguments, in place of the path derived from their
+    /// values.
+    #[serde(
+        rename = "pathTemplate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub path_template: Option<String>,
+    /// How calls with more than two arguments name their file, in place of
+    /// the bridge's `--argument-hash`.
+    #[serde(
+        rename = "argumentHash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub argument_hash: Option<resolver::ArgumentHash>,
+    /// How calls with two arguments order them in the path, in place of the
+    /// bridge's `--argument-order`.
+    #[serde(
+        rename = "argumentOrder",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub argument_order: Option<resolver::ArgumentOrder>,
+    /// The [`handler`] computing each call's result instead of a file: a
+    /// Rhai script such as `scripts/slugify.rhai` or a WASM module such as
+    /// `plugins/render.wasm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    /// Always called on the bridge's upstream MCP server, not looked up as
+    /// a file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upstream: bool,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl ToolDef {
+    /// The names the input schema lists as `required`.
+    pub fn required(&self) -> impl Iterator<Item = &str> {
+        self.input_schema
+            .as_ref()
+            .and_then(|schema| schema.get("required"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+    }
+
+    /// Each argument whose schema declares a `default`, with that default.
+    pub fn defaults(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.input_schema
+            .as_ref()
+            .and_then(|schema| schema.get("properties"))
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, schema)| Some((name.as_str(), schema.get("default")?)))
+    }
+
+    /// The input schema's schema for the argument `name`.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.input_schema.as_ref()?.get("properties")?.get(name)
+    }
+}
+
+// JS futures are not `Send`, so on wasm32 the trait (and every impl) drops the bound.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait MCPDataSource: Send + Sync {
-    async fn load_json(&self, relative_path: &str) -> anyhow::Result<Value>;
+    /// A JSON file, read for the request `context` describes.
+    async fn load_json(
+        &self,
+        relative_path: &str,
+        context: &RequestContext,
+    ) -> anyhow::Result<Value>;
     async fn load_manifest(&self) -> anyhow::Result<MCPManifest>;
+
+    /// A JSON file as it is, checked but not parsed, so it can be answered
+    /// without being built up and serialized again; by default
+    /// [`Self::load_json`] serialized back.
+    async fn load_raw_json(
+        &self,
+        relative_path: &str,
+        context: &RequestContext,
+    ) -> anyhow::Result<Box<RawValue>> {
+        Ok(serde_json::value::to_raw_value(
+            &self.load_json(relative_path, context).await?,
+        )?)
+    }
+
+    /// The raw bytes of a file that isn't JSON, such as an image; sources
+    /// that can't read them fail.
+    async fn load_bytes(&self, _relative_path: &str) -> anyhow::Result<Bytes> {
+        anyhow::bail!("the data source doesn't support binary files")
+    }
+
+    /// A file that isn't JSON read in pieces, with its size when known
+    /// before reading, for files too large to hold whole; by default
+    /// [`Self::load_bytes`] in one piece.
+    async fn stream_bytes(&self, relative_path: &str) -> anyhow::Result<ByteStream> {
+        Ok(ByteStream::whole(self.load_bytes(relative_path).await?))
+    }
+
+    /// The site-relative paths of the files under `prefix`, such as
+    /// `tools/`, sorted; sources that can't list their files fail.
+    async fn list(&self, _prefix: &str) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!("the data source doesn't support listing")
+    }
 }
 
+/// Local JSON files larger than this are parsed straight from the file,
+/// memory-mapped with the `mmap` feature and read as they are parsed
+/// otherwise, without first copying the whole file into memory.
+#[cfg(feature = "local")]
+const LARGE_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+#[cfg(feature = "local")]
 pub struct LocalDataSource {
     pub base_path: PathBuf,
 }
 
+#[cfg(feature = "local")]
 impl LocalDataSource {
     pub fn new(base_path: PathBuf) -> Self {
         Self { base_path }
     }
+
+    /// Whether `full_path` is a file over [`LARGE_FILE_BYTES`].
+    async fn is_large(full_path: &std::path::Path) -> bool {
+        fs::metadata(full_path)
+            .await
+            .is_ok_and(|metadata| metadata.len() > LARGE_FILE_BYTES)
+    }
+
+    /// Parses the large file at `full_path` with `parse` on a blocking
+    /// thread.
+    async fn parse_large_file<T>(
+        full_path: PathBuf,
+        parse: fn(&std::path::Path) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+    {
+        debug!(path = %full_path.display(), "parsing large file");
+        tokio::task::spawn_blocking(move || parse(&full_path)).await?
+    }
+
+    /// Parses the file at `full_path` into a [`Value`] with simd-json. It
+    /// parses in place, so the file is read whole into a buffer of its own,
+    /// memory-mapped or not.
+    #[cfg(feature = "simd")]
+    fn parse_value_file(full_path: &std::path::Path) -> anyhow::Result<Value> {
+        let mut bytes = std::fs::read(full_path)?;
+        Ok(simd_json::serde::from_slice(&mut bytes)?)
+    }
+
+    /// Parses the file at `full_path` into a [`Value`].
+    #[cfg(not(feature = "simd"))]
+    fn parse_value_file(full_path: &std::path::Path) -> anyhow::Result<Value> {
+        Self::parse_file(full_path)
+    }
+
+    /// Parses the file at `full_path` from a memory map of it.
+    #[cfg(feature = "mmap")]
+    fn parse_file<T: serde::de::DeserializeOwned>(
+        full_path: &std::path::Path,
+    ) -> anyhow::Result<T> {
+        let file = std::fs::File::open(full_path)?;
+        // SAFETY: the map is only read while parsing. A site's files aren't
+        // expected to be rewritten in place while they are served; one that
+        // is truncated meanwhile may fail the read, like any torn read would.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(serde_json::from_slice(&map)?)
+    }
+
+    /// Parses the file at `full_path` as it is read.
+    #[cfg(not(feature = "mmap"))]
+    fn parse_file<T: serde::de::DeserializeOwned>(
+        full_path: &std::path::Path,
+    ) -> anyhow::Result<T> {
+        let file = std::io::BufReader::new(std::fs::File::open(full_path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// The bytes of the JSON file at `full_path`, or of its first
+    /// precompressed sibling when it is missing.
+    async fn read_json_file(&self, full_path: &std::path::Path) -> anyhow::Result<Bytes> {
+        debug!(path = %full_path.display(), "reading file");
+        match fs::read(full_path).await {
+            Ok(content) => Ok(content.into()),
+            #[cfg(feature = "precompressed")]
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Ok(self.read_precompressed(full_path).await?.ok_or(err)?.into())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Reads the first precompressed sibling of `full_path` that exists.
+    #[cfg(feature = "precompressed")]
+    async fn read_precompressed(
+        &self,
+        full_path: &std::path::Path,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        for encoding in precompressed::Encoding::FALLBACKS {
+            let mut candidate = full_path.as_os_str().to_owned();
+            candidate.push(encoding.suffix());
+            match fs::read(&candidate).await {
+                Ok(compressed) => {
+                    debug!(path = ?candidate, ?encoding, "reading precompressed file");
+                    return Ok(Some(encoding.decode(&compressed)?));
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(None)
+    }
 }
 
+#[cfg(feature = "local")]
 #[async_trait]
 impl MCPDataSource for LocalDataSource {
-    async fn load_json(&self, relative_path: &str) -> anyhow::Result<Value> {
+    async fn load_json(
+        &self,
+        relative_path: &str,
+        _context: &RequestContext,
+    ) -> anyhow::Result<Value> {
         let full_path = self.base_path.join(relative_path);
-        eprintln!("📁 Reading: {}", full_path.display());
-        let content = fs::read_to_string(full_path).await?;
-        Ok(serde_json::from_str(&content)?)
+        if Self::is_large(&full_path).await {
+            return Self::parse_large_file(full_path, Self::parse_value_file).await;
+        }
+        parse_json(&self.read_json_file(&full_path).await?)
+    }
+
+    async fn load_raw_json(
+        &self,
+        relative_path: &str,
+        _context: &RequestContext,
+    ) -> anyhow::Result<Box<RawValue>> {
+        let full_path = self.base_path.join(relative_path);
+        if Self::is_large(&full_path).await {
+            return Self::parse_large_file(full_path, Self::parse_file).await;
+        }
+        parse_raw_json(self.read_json_file(&full_path).await?)
     }
 
     async fn load_manifest(&self) -> anyhow::Result<MCPManifest> {
-        let manifest_data = self.load_json("mcp.json").await?;
-        Ok(serde_json::from_value(manifest_data)?)
+        manifest::parse(
+            self.load_json("mcp.json", &RequestContext::default())
+                .await?,
+        )
+    }
+
+    async fn load_bytes(&self, relative_path: &str) -> anyhow::Result<Bytes> {
+        let full_path = self.base_path.join(relative_path);
+        debug!(path = %full_path.display(), "reading file");
+        Ok(fs::read(&full_path).await?.into())
+    }
+
+    async fn stream_bytes(&self, relative_path: &str) -> anyhow::Result<ByteStream> {
+        use tokio::io::AsyncReadExt;
+        let full_path = self.base_path.join(relative_path);
+        debug!(path = %full_path.display(), "opening file");
+        let file = fs::File::open(&full_path).await?;
+        let size = file.metadata().await?.len();
+        let chunks = futures::stream::try_unfold(file, |mut file| async move {
+            let mut chunk = vec![0; chunked::CHUNK_SIZE];
+            let read = file.read(&mut chunk).await?;
+            chunk.truncate(read);
+            Ok((read > 0).then_some((Bytes::from(chunk), file)))
+        });
+        Ok(ByteStream {
+            size: Some(size),
+            chunks: Box::pin(chunks),
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let mut paths = Vec::new();
+        let mut pending = vec![self.base_path.join(prefix)];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.base_path) {
+                    paths.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
     }
 }
 
+#[cfg(feature = "remote")]
 pub struct RemoteDataSource {
     pub base_url: String,
     pub client: reqwest::Client,
+    /// Where each fetch is reported with how long it took.
+    pub metrics: Option<Arc<dyn BridgeMetrics>>,
+    /// JSON loads in flight, shared by concurrent loads of the same file.
+    flights: singleflight::Flights<Value>,
+    raw_flights: singleflight::Flights<Box<RawValue>>,
 }
 
+#[cfg(feature = "remote")]
 impl RemoteDataSource {
     pub fn new(base_url: String) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// The source fetching with `client`, sharing its connection pool with
+    /// every other source given a clone of it.
+    pub fn with_client(base_url: String, client: reqwest::Client) -> Self {
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
-            client: reqwest::Client::new(),
+            client,
+            metrics: None,
+            flights: singleflight::Flights::new(),
+            raw_flights: singleflight::Flights::new(),
         }
     }
-}
 
-#[async_trait]
-impl MCPDataSource for RemoteDataSource {
-    async fn load_json(&self, relative_path: &str) -> anyhow::Result<Value> {
-        let url = format!("{}/{}", self.base_url, relative_path);
-        eprintln!("🌐 Fetching: {url}");
+    /// The source reporting each fetch to `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<dyn BridgeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        #[cfg(feature = "otel")]
+        let request = request.headers(server::telemetry::trace_headers());
+        #[cfg(feature = "server")]
+        let request = match server::request_id::current() {
+            Some(id) => request.header(server::request_id::REQUEST_ID_HEADER.as_str(), id),
+            None => request,
+        };
+        #[cfg(feature = "server")]
+        let request = match server::client::current()
+            .and_then(|client| reqwest::header::HeaderValue::from_str(&client.to_string()).ok())
+        {
+            Some(client) => request.header(server::client::MCP_CLIENT_HEADER.as_str(), client),
+            None => request,
+        };
+        request
+    }
 
-        let response = self.client.get(&url).send().await?;
+    /// `load`, given up on at the deadline of the request `context`
+    /// describes. Shared fetches are bounded by each caller's own deadline
+    /// rather than the first one's: a caller giving up drops the fetch, and
+    /// those still waiting start it over.
+    async fn within<T>(
+        context: &RequestContext,
+        load: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        match context.remaining() {
+            Some(remaining) => tokio::time::timeout(remaining, load).await?,
+            None => load.await,
+        }
+    }
+
+    async fn send(
+        &self,
+        url: &str,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        let started = Instant::now();
+        let response = request.send().await;
+        if let Some(metrics) = &self.metrics {
+            let status = response
+                .as_ref()
+                .ok()
+                .map(|response| response.status().as_u16());
+            metrics.upstream_fetch(url, status, started.elapsed());
+        }
+        let response = response?;
+        debug!(
+            %url,
+            status = response.status().as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "upstream fetch"
+        );
+        Ok(response)
+    }
+
+    /// `response`, if its status is a success.
+    fn check(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
         if !response.status().is_success() {
-            anyhow::bail!(
+            let message = format!(
                 "HTTP {}: {}",
                 response.status(),
                 response.status().canonical_reason().unwrap_or("Unknown")
             );
+            // Typed like a missing local file, so callers can tell the two
+            // apart from the source failing.
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Err(std::io::Error::new(std::io::ErrorKind::NotFound, message).into());
+            }
+            anyhow::bail!(message);
+        }
+        Ok(response)
+    }
+
+    /// Fetches the first precompressed sibling of `url` the site serves.
+    ///
+    /// These are requested with `Accept-Encoding: identity` so the body is
+    /// always the stored file, even from hosts that would otherwise mark it
+    /// as `Content-Encoding: gzip`.
+    #[cfg(feature = "precompressed")]
+    async fn fetch_precompressed(&self, url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        for encoding in precompressed::Encoding::FALLBACKS {
+            let candidate = format!("{url}{}", encoding.suffix());
+            let request = self
+                .get(&candidate)
+                .header(reqwest::header::ACCEPT_ENCODING, "identity");
+            let response = self.send(&candidate, request).await?;
+            if response.status().is_success() {
+                let compressed = response.bytes().await?;
+                return Ok(Some(encoding.decode(&compressed)?));
+            }
         }
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "remote")]
+#[async_trait]
+impl MCPDataSource for RemoteDataSource {
+    async fn load_json(
+        &self,
+        relative_path: &str,
+        context: &RequestContext,
+    ) -> anyhow::Result<Value> {
+        let url = format!("{}/{}", self.base_url, resolver::url_path(relative_path));
+        let load = self.flights.run(&url, || async {
+            let response = self.send(&url, self.get(&url)).await?;
+
+            #[cfg(feature = "precompressed")]
+            if response.status() == reqwest::StatusCode::NOT_FOUND
+                && let Some(content) = self.fetch_precompressed(&url).await?
+            {
+                return parse_json(&content);
+            }
 
-        let text = response.text().await?;
-        Ok(serde_json::from_str(&text)?)
+            parse_json(&Self::check(response)?.bytes().await?)
+        });
+        Self::within(context, load).await
+    }
+
+    async fn load_raw_json(
+        &self,
+        relative_path: &str,
+        context: &RequestContext,
+    ) -> anyhow::Result<Box<RawValue>> {
+        let url = format!("{}/{}", self.base_url, resolver::url_path(relative_path));
+        let load = self.raw_flights.run(&url, || async {
+            let response = self.send(&url, self.get(&url)).await?;
+
+            #[cfg(feature = "precompressed")]
+            if response.status() == reqwest::StatusCode::NOT_FOUND
+                && let Some(content) = self.fetch_precompressed(&url).await?
+            {
+                return parse_raw_json(content.into());
+            }
+
+            parse_raw_json(Self::check(response)?.bytes().await?)
+        });
+        Self::within(context, load).await
     }
 
     async fn load_manifest(&self) -> anyhow::Result<MCPManifest> {
-        let manifest_data = self.load_json("mcp.json").await?;
-        Ok(serde_json::from_value(manifest_data)?)
+        manifest::parse(
+            self.load_json("mcp.json", &RequestContext::default())
+                .await?,
+        )
+    }
+
+    async fn load_bytes(&self, relative_path: &str) -> anyhow::Result<Bytes> {
+        let url = format!("{}/{}", self.base_url, resolver::url_path(relative_path));
+        let response = self.send(&url, self.get(&url)).await?;
+        Ok(Self::check(response)?.bytes().await?)
+    }
+
+    async fn stream_bytes(&self, relative_path: &str) -> anyhow::Result<ByteStream> {
+        let url = format!("{}/{}", self.base_url, resolver::url_path(relative_path));
+        let response = Self::check(self.send(&url, self.get(&url)).await?)?;
+        let size = response.content_length();
+        let chunks = futures::stream::try_unfold(response, |mut response| async move {
+            let chunk = response.chunk().await?;
+            Ok(chunk.map(|chunk| (chunk, response)))
+        });
+        Ok(ByteStream {
+            size,
+            chunks: Box::pin(chunks),
+        })
     }
 }
 
 pub struct MCPBridge {
     pub data_source: Box<dyn MCPDataSource>,
     pub manifest: Option<MCPManifest>,
+    pub audit: Option<Arc<dyn AuditSink>>,
+    pub policy: Option<Arc<AccessPolicy>>,
+    pub path_resolver: Arc<dyn PathResolver>,
+    /// [`BridgeHook`]s run around every request, in order.
+    pub hooks: Vec<Arc<dyn BridgeHook>>,
+    /// Where requests are reported as they start and finish.
+    pub metrics: Option<Arc<dyn BridgeMetrics>>,
+    /// [`rewrite`] rules tried before the manifest's.
+    pub rewrites: Vec<RewriteRule>,
+    /// Whether tool names and string arguments are compared and mapped in
+    /// their [`normalize::normalize`]d form.
+    pub normalize: bool,
+    /// Whether a call with arguments that has no file of its own is answered
+    /// from `tools/{name}/index.json`.
+    pub tool_index_fallback: bool,
+    /// The file, such as `_default`, answering a call with arguments that
+    /// has no file of its own from `tools/{name}/{file}.json`, with its
+    /// [`template`] placeholders filled in from the arguments.
+    pub tool_default_file: Option<String>,
+    /// Whether every tool and resource file has its [`template`]
+    /// placeholders filled in, not only the default file.
+    pub render_templates: bool,
+    /// Bounds on each run of a tool's [`handler`].
+    pub handler_limits: handler::HandlerLimits,
+    /// Whether the [`meta`] tools are listed and answered.
+    pub meta_tools: bool,
+    /// Annotations, such as `readOnlyHint`, that tools the bridge answers
+    /// are listed with where they don't set their own.
+    pub tool_annotations: serde_json::Map<String, Value>,
+    /// The locales whose [`locale`] translations of resources the site
+    /// has; empty when it serves one language.
+    pub locales: Vec<String>,
+    /// Size in bytes above which a raw resource file is left to be sent in
+    /// [`chunked`] pieces by [`Self::reply`]; `None` reads every file whole.
+    pub stream_threshold: Option<u64>,
+    /// The live MCP server answering undeclared tools and resources, those
+    /// marked `upstream` and those whose file is missing.
+    #[cfg(feature = "remote")]
+    pub upstream: Option<Arc<upstream::Upstream>>,
+    /// The index answering the synthetic `search` tool, unless the manifest
+    /// declares a tool of that name.
+    #[cfg(feature = "search")]
+    pub search: Option<Arc<search::SearchIndex>>,
+    /// Whether [`Self::initialize`] builds [`Self::search`] when none is set.
+    #[cfg(feature = "search")]
+    pub build_search_index: bool,
+    /// Results sent for `resources/read` and `tools/call`, answering the
+    /// same requests again.
+    #[cfg(feature = "server")]
+    pub response_cache: Option<Arc<server::response_cache::ResponseCache>>,
+}
+
+/// Collects optional bridge settings before the bridge is created.
+pub struct MCPBridgeBuilder {
+    data_source: Box<dyn MCPDataSource>,
+    audit: Option<Arc<dyn AuditSink>>,
+    policy: Option<Arc<AccessPolicy>>,
+    path_resolver: Arc<dyn PathResolver>,
+    hooks: Vec<Arc<dyn BridgeHook>>,
+    metrics: Option<Arc<dyn BridgeMetrics>>,
+    rewrites: Vec<RewriteRule>,
+    normalize: bool,
+    tool_index_fallback: bool,
+    tool_default_file: Option<String>,
+    render_templates: bool,
+    handler_limits: handler::HandlerLimits,
+    meta_tools: bool,
+    tool_annotations: serde_json::Map<String, Value>,
+    locales: Vec<String>,
+    stream_threshold: Option<u64>,
+    #[cfg(feature = "remote")]
+    upstream: Option<Arc<upstream::Upstream>>,
+    #[cfg(feature = "search")]
+    search: Option<Arc<search::SearchIndex>>,
+    #[cfg(feature = "search")]
+    build_search_index: bool,
+    #[cfg(feature = "server")]
+    response_cache: Option<Arc<server::response_cache::ResponseCache>>,
+}
+
+impl MCPBridgeBuilder {
+    /// Records every tool call and resource read to `sink`.
+    pub fn audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Restricts tools and resources to the principals `policy` allows.
+    pub fn access_policy(mut self, policy: Arc<AccessPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Maps requests to files with `resolver` instead of the StaticMCP layout.
+    pub fn path_resolver(mut self, resolver: Arc<dyn PathResolver>) -> Self {
+        self.path_resolver = resolver;
+        self
+    }
+
+    /// Runs `hook` around every request, after the hooks added before it.
+    pub fn hook(mut self, hook: Arc<dyn BridgeHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Reports every request to `metrics` as it starts and finishes.
+    pub fn metrics(mut self, metrics: Arc<dyn BridgeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Rewrites URIs and paths by `rules` before the manifest's own.
+    pub fn rewrites(mut self, rules: Vec<RewriteRule>) -> Self {
+        self.rewrites = rules;
+        self
+    }
+
+    /// Looks tools up, and maps their string arguments to files, after
+    /// trimming, lowercasing and NFC-normalizing them.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Answers calls with arguments that have no file of their own from the
+    /// tool's `index.json`, a catch-all the site provides.
+    pub fn tool_index_fallback(mut self, fallback: bool) -> Self {
+        self.tool_index_fallback = fallback;
+        self
+    }
+
+    /// Answers calls with arguments that have no file of their own from the
+    /// tool's `{file}.json`, filling its placeholders from the arguments.
+    pub fn tool_default_file(mut self, file: Option<String>) -> Self {
+        self.tool_default_file = file;
+        self
+    }
+
+    /// Fills in the placeholders of every tool and resource file, such as
+    /// `{{args.query}}` or `{{uri}}`, from the request it answers.
+    pub fn render_templates(mut self, render: bool) -> Self {
+        self.render_templates = render;
+        self
+    }
+
+    /// Bounds each run of a tool's [`handler`] by `limits`.
+    pub fn handler_limits(mut self, limits: handler::HandlerLimits) -> Self {
+        self.handler_limits = limits;
+        self
+    }
+
+    /// Lists and answers the [`meta`] tools, which show the files the data
+    /// source has for each tool, when it can list them.
+    pub fn meta_tools(mut self, meta_tools: bool) -> Self {
+        self.meta_tools = meta_tools;
+        self
+    }
+
+    /// Lists the tools the bridge answers, not those of its upstream, with
+    /// `annotations` where they don't set their own, such as
+    /// `readOnlyHint: true` for a site that only serves files.
+    pub fn tool_annotations(mut self, annotations: serde_json::Map<String, Value>) -> Self {
+        self.tool_annotations = annotations;
+        self
+    }
+
+    /// Reads resources from their [`locale`] translation among `locales`
+    /// when the request asks for one, falling back to the untranslated file.
+    pub fn locales(mut self, locales: Vec<String>) -> Self {
+        self.locales = locales;
+        self
+    }
+
+    /// Leaves reads of raw resource files larger than `threshold` bytes to
+    /// be sent in chunks; see [`MCPBridge::reply`].
+    pub fn stream_threshold(mut self, threshold: Option<u64>) -> Self {
+        self.stream_threshold = threshold;
+        self
+    }
+
+    /// Forwards requests the static files can't answer to `upstream`.
+    #[cfg(feature = "remote")]
+    pub fn upstream(mut self, upstream: Arc<upstream::Upstream>) -> Self {
+        self.upstream = Some(upstream);
+        self
+    }
+
+    /// Indexes the text of every resource when the manifest loads, and
+    /// answers a synthetic `search` tool from it.
+    #[cfg(feature = "search")]
+    pub fn search(mut self, search: bool) -> Self {
+        self.build_search_index = search;
+        self
+    }
+
+    /// Answers a synthetic `search` tool from an index built earlier.
+    #[cfg(feature = "search")]
+    pub fn search_index(mut self, index: Arc<search::SearchIndex>) -> Self {
+        self.search = Some(index);
+        self
+    }
+
+    /// Answers repeated `resources/read` and `tools/call` requests with the
+    /// results `cache` kept of the first.
+    #[cfg(feature = "server")]
+    pub fn response_cache(mut self, cache: Arc<server::response_cache::ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Creates the bridge; call [`MCPBridge::initialize`] to load the manifest.
+    pub fn build(self) -> MCPBridge {
+        MCPBridge {
+            data_source: self.data_source,
+            manifest: None,
+            audit: self.audit,
+            policy: self.policy,
+            path_resolver: self.path_resolver,
+            hooks: self.hooks,
+            metrics: self.metrics,
+            rewrites: self.rewrites,
+            normalize: self.normalize,
+            tool_index_fallback: self.tool_index_fallback,
+            tool_default_file: self.tool_default_file,
+            render_templates: self.render_templates,
+            handler_limits: self.handler_limits,
+            meta_tools: self.meta_tools,
+            tool_annotations: self.tool_annotations,
+            locales: self.locales,
+            stream_threshold: self.stream_threshold,
+            #[cfg(feature = "remote")]
+            upstream: self.upstream,
+            #[cfg(feature = "search")]
+            search: self.search,
+            #[cfg(feature = "search")]
+            build_search_index: self.build_search_index,
+            #[cfg(feature = "server")]
+            response_cache: self.response_cache,
+        }
+    }
 }
 
 impl MCPBridge {
     pub fn new(data_source: Box<dyn MCPDataSource>) -> Self {
-        Self {
+        Self::builder(data_source).build()
+    }
+
+    pub fn builder(data_source: Box<dyn MCPDataSource>) -> MCPBridgeBuilder {
+        MCPBridgeBuilder {
             data_source,
-            manifest: None,
+            audit: None,
+            policy: None,
+            path_resolver: Arc::new(DefaultPathResolver::default()),
+            hooks: Vec::new(),
+            metrics: None,
+            rewrites: Vec::new(),
+            normalize: false,
+            tool_index_fallback: false,
+            tool_default_file: None,
+            render_templates: false,
+            handler_limits: handler::HandlerLimits::default(),
+            meta_tools: false,
+            tool_annotations: serde_json::Map::new(),
+            locales: Vec::new(),
+            stream_threshold: None,
+            #[cfg(feature = "remote")]
+            upstream: None,
+            #[cfg(feature = "search")]
+            search: None,
+            #[cfg(feature = "search")]
+            build_search_index: false,
+            #[cfg(feature = "server")]
+            response_cache: None,
         }
     }
 
@@ -151,106 +1171,1021 @@ impl MCPBridge {
                 .map(|s| s.version.as_str())
                 .unwrap_or("0.0.0");
 
-            eprintln!("✅ Loaded manifest: {server_name} v{server_version}");
+            info!(
+                server = server_name,
+                version = server_version,
+                format = manifest.staticmcp_version.unwrap_or(1),
+                "loaded manifest"
+            );
+            if !manifest.checksums.is_empty() && !checksum::VERIFIED {
+                warn!(
+                    "the manifest lists checksums, but without the `sha256` feature files aren't checked against them"
+                );
+            }
+        }
+
+        #[cfg(feature = "search")]
+        if self.build_search_index && self.search.is_none() {
+            self.search = Some(Arc::new(search::SearchIndex::build(self).await?));
+        }
+
+        Ok(())
+    }
+
+    fn record_audit(
+        &self,
+        method: &str,
+        fill: impl FnOnce(&mut AuditEntry),
+        error: Option<&anyhow::Error>,
+    ) {
+        let Some(sink) = &self.audit else {
+            return;
+        };
+
+        let mut entry = AuditEntry::new(method);
+        #[cfg(feature = "server")]
+        {
+            entry.request_id = server::request_id::current();
+        }
+        entry.client = Self::principal().map(|principal| principal.subject);
+        entry.client_info = Self::client_info().map(|client| (*client).clone());
+        fill(&mut entry);
+        if let Some(e) = error {
+            entry.outcome = AuditOutcome::Error;
+            entry.error = Some(e.to_string());
+        }
+        sink.record(&entry);
+    }
+
+    /// The checksum the manifest lists for the file `path`.
+    fn checksum(&self, path: &str) -> Option<&str> {
+        self.manifest
+            .as_ref()?
+            .checksums
+            .get(path)
+            .map(String::as_str)
+    }
+
+    /// The file `path` read whole and checked, when the manifest lists a
+    /// checksum for it.
+    async fn load_checked(&self, path: &str) -> anyhow::Result<Option<Bytes>> {
+        let Some(expected) = self.checksum(path) else {
+            return Ok(None);
+        };
+        let bytes = self
+            .data_source
+            .load_bytes(path)
+            .instrument(tracing::info_span!("data_source_load", path))
+            .await?;
+        checksum::verify(path, &bytes, expected)?;
+        Ok(Some(bytes))
+    }
+
+    async fn load_json(&self, path: &str) -> anyhow::Result<Value> {
+        if let Some(bytes) = self.load_checked(path).await? {
+            return parse_json(&bytes);
+        }
+        self.data_source
+            .load_json(path, &RequestContext::current())
+            .instrument(tracing::info_span!("data_source_load", path))
+            .await
+    }
+
+    async fn load_raw_json(&self, path: &str) -> anyhow::Result<Box<RawValue>> {
+        if let Some(bytes) = self.load_checked(path).await? {
+            return parse_raw_json(bytes);
+        }
+        self.data_source
+            .load_raw_json(path, &RequestContext::current())
+            .instrument(tracing::info_span!("data_source_load", path))
+            .await
+    }
+
+    /// Loads the JSON file `path`, kept as it was read when `verbatim`
+    /// accepts its top-level keys, so it is answered without being parsed
+    /// and serialized again. Files whose placeholders are rendered are
+    /// always parsed.
+    async fn load_json_as(
+        &self,
+        path: &str,
+        verbatim: impl Fn(&[&str]) -> bool,
+    ) -> anyhow::Result<Loaded> {
+        if self.render_templates {
+            return self.load_json(path).await.map(Loaded::Whole);
+        }
+        let raw = self.load_raw_json(path).await?;
+        // Only the top level is read; keys written with escapes don't borrow
+        // and are left to the full parse.
+        let keys = serde_json::from_str::<HashMap<&str, &RawValue>>(raw.get())
+            .ok()
+            .map(|object| object.into_keys().collect::<Vec<_>>());
+        if keys.is_some_and(|keys| verbatim(&keys)) {
+            return Ok(Loaded::Verbatim(raw));
+        }
+        Ok(Loaded::Whole(parse_json(raw.get().as_bytes())?))
+    }
+
+    async fn load_bytes(&self, path: &str) -> anyhow::Result<Bytes> {
+        if let Some(bytes) = self.load_checked(path).await? {
+            return Ok(bytes);
+        }
+        self.data_source
+            .load_bytes(path)
+            .instrument(tracing::info_span!("data_source_load", path))
+            .await
+    }
+
+    /// Files with a checksum are read whole to check it, then streamed from
+    /// memory.
+    async fn stream_bytes(&self, path: &str) -> anyhow::Result<ByteStream> {
+        if let Some(bytes) = self.load_checked(path).await? {
+            return Ok(ByteStream::whole(bytes));
+        }
+        self.data_source
+            .stream_bytes(path)
+            .instrument(tracing::info_span!("data_source_load", path))
+            .await
+    }
+
+    /// Loads the file holding the resource `uri`, returning its path: its
+    /// translation for the first of `locales` the site has one for, or else
+    /// the untranslated file, in the best of its `variants` that `accept`
+    /// allows and the site has.
+    pub(crate) async fn load_resource(
+        &self,
+        uri: &str,
+        locales: &[&str],
+        accept: Option<&str>,
+    ) -> (String, anyhow::Result<Loaded>) {
+        let path = self.uri_to_path(uri);
+        let variants = self
+            .resources()
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .map(|resource| mime::negotiate(&resource.variants, accept))
+            .unwrap_or_default();
+        for locale in locales {
+            let localized = locale::localized_path(&path, locale);
+            match self.load_variant(uri, localized, &variants).await {
+                (localized, Err(e)) if is_not_found(&e) => {
+                    debug!(
+                        uri,
+                        path = localized,
+                        "no translation, trying the next locale"
+                    );
+                }
+                loaded => return loaded,
+            }
+        }
+        self.load_variant(uri, path, &variants).await
+    }
+
+    /// Loads the resource `uri` from the first of `variants` published
+    /// beside `path`, or from `path` itself when none is.
+    async fn load_variant(
+        &self,
+        uri: &str,
+        path: String,
+        variants: &[&str],
+    ) -> (String, anyhow::Result<Loaded>) {
+        for &variant in variants {
+            let extension = match mime::extension_for(variant) {
+                Some("json") => break,
+                Some(extension) => extension,
+                None => continue,
+            };
+            let stem = match mime::extension(&path) {
+                Some(current) => &path[..path.len() - current.len() - 1],
+                None => &path,
+            };
+            let variant_path = format!("{stem}.{extension}");
+            match self.load_raw(uri, &variant_path, variant).await {
+                Err(e) if is_not_found(&e) => {
+                    debug!(uri, path = variant_path, "no such variant, trying the next");
+                }
+                loaded => return (variant_path, loaded),
+            }
+        }
+        self.load_resource_file(uri, path).await
+    }
+
+    /// Loads the resource `uri` from `path`. A raw file `path` names is read
+    /// as it is; one found by dropping `.json` only answers when the JSON
+    /// file is missing, so sites wrapping such files in JSON keep working.
+    async fn load_resource_file(
+        &self,
+        uri: &str,
+        path: String,
+    ) -> (String, anyhow::Result<Loaded>) {
+        let raw = self.raw_resource_file(uri, &path);
+        if let Some((raw_path, mime)) = &raw
+            && *raw_path == path
+        {
+            return (path.clone(), self.load_raw(uri, &path, mime).await);
+        }
+        // A file holding just its `contents` is already the result, unless
+        // the manifest describes the resource further.
+        let described = !self.description(uri).is_empty();
+        let mut result = self
+            .load_json_as(&path, |keys| !described && keys == ["contents"])
+            .await;
+        if self.render_templates
+            && let Ok(Loaded::Whole(resource)) = &mut result
+        {
+            template::render(resource, &template::resource(uri));
+        }
+        match (result, raw) {
+            (Err(e), Some((raw_path, mime))) if is_not_found(&e) => {
+                let result = self.load_raw(uri, &raw_path, &mime).await;
+                (raw_path, result)
+            }
+            (result, _) => (path, result),
+        }
+    }
+
+    /// The contents of the resource `uri` held in the raw file `path` of
+    /// type `mime`: as `text`, placeholders rendered when the bridge renders
+    /// templates, or base64-encoded in a `blob`. Files larger than
+    /// [`Self::stream_threshold`] are left to be read in chunks, unless their
+    /// placeholders are to be rendered.
+    async fn load_raw(&self, uri: &str, path: &str, mime: &str) -> anyhow::Result<Loaded> {
+        let text = mime::is_text(mime);
+        let bytes = match self.stream_threshold {
+            Some(threshold) if !(text && self.render_templates) => {
+                let bytes = self.stream_bytes(path).await?;
+                if bytes.size.is_some_and(|size| size > threshold) {
+                    debug!(uri, path, size = bytes.size, "answering in chunks");
+                    return Ok(Loaded::Chunked(ChunkedContents::new(uri, mime, bytes)));
+                }
+                bytes.collect().await?
+            }
+            _ => self.load_bytes(path).await?,
+        };
+        if !text {
+            return blob_contents(uri, mime, &bytes).map(Loaded::Whole);
+        }
+        let mut text = String::from_utf8(Vec::from(bytes))
+            .map_err(|_| anyhow::anyhow!("{path} is not UTF-8 text"))?;
+        if self.render_templates
+            && let Some(rendered) = template::render_text(&text, &template::resource(uri))
+        {
+            text = rendered;
+        }
+        Ok(Loaded::Whole(
+            json!({ "contents": [{ "uri": uri, "mimeType": mime, "text": text }] }),
+        ))
+    }
+
+    /// The file the resource `uri` is read from as it is rather than as
+    /// JSON, and its MIME type, when its path `path` names one: a file with
+    /// a known extension, such as `.png` or `.md`, or with any extension
+    /// when the manifest declares the resource's `mimeType`. A `.json` the
+    /// path resolver appended to such a name is dropped, so
+    /// `file://logo.png` may be read from `resources/logo.png`.
+    pub(crate) fn raw_resource_file(&self, uri: &str, path: &str) -> Option<(String, String)> {
+        let declared = self
+            .resources()
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .and_then(|resource| resource.mime_type.clone());
+        let raw = |path: &str| {
+            let extension = mime::extension(path)?;
+            if extension.eq_ignore_ascii_case("json") {
+                return None;
+            }
+            match mime::from_extension(extension) {
+                Some(detected) => Some(declared.clone().unwrap_or_else(|| detected.to_string())),
+                None => declared.clone(),
+            }
+        };
+        if let Some(mime) = raw(path) {
+            return Some((path.to_string(), mime));
+        }
+        let stripped = path.strip_suffix(".json")?;
+        raw(stripped).map(|mime| (stripped.to_string(), mime))
+    }
+
+    /// Loads the response to a call of `name` with `args` from `path`. When
+    /// the call has arguments but no file, the tool's default file and then
+    /// its `index.json` answer instead, if enabled and present. Returns the
+    /// path it was answered from.
+    async fn load_tool_response(
+        &self,
+        name: &str,
+        args: &HashMap<String, Value>,
+        path: String,
+    ) -> (String, anyhow::Result<Loaded>) {
+        let mut result = self.load_json_as(&path, is_tool_result).await;
+        let e = match &mut result {
+            Ok(loaded) => {
+                if self.render_templates
+                    && let Loaded::Whole(value) = loaded
+                {
+                    template::render(value, &template::arguments(args));
+                }
+                return (path, result);
+            }
+            Err(e) => e,
+        };
+        if args.is_empty() || !is_not_found(e) {
+            return (path, result);
+        }
+        let default = self
+            .tool_default_file
+            .iter()
+            .map(|file| (format!("tools/{name}/{file}.json"), true));
+        let index = self
+            .tool_index_fallback
+            .then(|| (format!("tools/{name}/index.json"), false));
+        for (fallback, templated) in default.chain(index) {
+            debug!(path, fallback, "no response file, trying a fallback");
+            let loaded = if templated || self.render_templates {
+                self.load_json(&fallback).await.map(|mut value| {
+                    template::render(&mut value, &template::arguments(args));
+                    Loaded::Whole(value)
+                })
+            } else {
+                self.load_json_as(&fallback, is_tool_result).await
+            };
+            match loaded {
+                Err(e) if is_not_found(&e) => continue,
+                loaded => return (fallback, loaded),
+            }
+        }
+        (path, result)
+    }
+
+    /// The upstream's answer to `method` with `params`, under the caller's
+    /// `id`, audited with `fill`; `None` when the bridge has no upstream.
+    #[cfg(feature = "remote")]
+    async fn forward(
+        &self,
+        id: Option<Value>,
+        method: &str,
+        params: &Value,
+        fill: impl FnOnce(&mut AuditEntry),
+    ) -> Option<MCPResponse> {
+        let upstream = self.upstream.as_ref()?;
+        info!(method, url = %upstream.url, "forwarding to upstream");
+        let result = upstream.request(method, params.clone()).await;
+        let error = match &result {
+            Ok(response) => response
+                .error
+                .as_ref()
+                .map(|error| anyhow::anyhow!("{}", error.message)),
+            Err(e) => Some(anyhow::anyhow!("{e}")),
+        };
+        self.record_audit(
+            method,
+            |entry| {
+                fill(entry);
+                entry.upstream = Some(upstream.url.clone());
+            },
+            error.as_ref(),
+        );
+        let response = result.unwrap_or_else(|e| {
+            warn!(method, error = %e, "upstream request failed");
+            MCPResponse {
+                jsonrpc: "2.0".to_string(),
+                id: None,
+                result: None,
+                error: Some(MCPError {
+                    code: -32603,
+                    message: "Upstream request failed".to_string(),
+                    data: None,
+                }),
+            }
+        });
+        Some(MCPResponse { id, ..response })
+    }
+
+    #[cfg(not(feature = "remote"))]
+    async fn forward(
+        &self,
+        _id: Option<Value>,
+        _method: &str,
+        _params: &Value,
+        _fill: impl FnOnce(&mut AuditEntry),
+    ) -> Option<MCPResponse> {
+        None
+    }
+
+    /// The entries under `key` of the upstream's answer to a list `method`,
+    /// none without an upstream or when it fails.
+    #[cfg(feature = "remote")]
+    async fn upstream_entries(&self, method: &str, key: &str) -> Vec<Value> {
+        let Some(upstream) = &self.upstream else {
+            return Vec::new();
+        };
+        let result = match upstream.request(method, json!({})).await {
+            Ok(response) => response.result,
+            Err(e) => {
+                warn!(method, error = %e, "upstream request failed");
+                None
+            }
+        };
+        match result {
+            Some(Value::Object(mut result)) => match result.remove(key) {
+                Some(Value::Array(entries)) => entries,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    #[cfg(not(feature = "remote"))]
+    async fn upstream_entries(&self, _method: &str, _key: &str) -> Vec<Value> {
+        Vec::new()
+    }
+
+    /// The index answering the `search` tool, unless the manifest declares
+    /// its own.
+    #[cfg(feature = "search")]
+    fn search_index(&self) -> Option<&search::SearchIndex> {
+        self.search
+            .as_deref()
+            .filter(|_| self.find_tool(search::TOOL_NAME).is_none())
+    }
+
+    /// Answers a call of the synthetic `search` tool, leaving out resources
+    /// the caller may not read.
+    #[cfg(feature = "search")]
+    fn call_search(
+        &self,
+        id: Option<Value>,
+        index: &search::SearchIndex,
+        args: &HashMap<String, Value>,
+    ) -> MCPResponse {
+        let name = search::TOOL_NAME;
+        if !self.permits_tool(name) {
+            warn!(tool = name, "tool call denied by access policy");
+            let error = anyhow::anyhow!("access denied");
+            self.record_audit(
+                "tools/call",
+                |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args));
+                },
+                Some(&error),
+            );
+            return Self::access_denied(id, format!("Access denied: tool {name}"));
+        }
+        let result = index.call(args, |uri| self.permits_resource(uri));
+        let error = result.as_ref().err().map(|e| anyhow::anyhow!("{e}"));
+        self.record_audit(
+            "tools/call",
+            |entry| {
+                entry.tool = Some(name.to_string());
+                entry.arguments = Some(json!(args));
+            },
+            error.as_ref(),
+        );
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result.unwrap_or_else(|e| Self::tool_error(name, &e))),
+            error: None,
+        }
+    }
+
+    /// Answers a call of the meta tool `name`.
+    async fn call_meta(
+        &self,
+        id: Option<Value>,
+        name: &str,
+        args: &HashMap<String, Value>,
+    ) -> MCPResponse {
+        if !self.permits_tool(name) {
+            warn!(tool = name, "tool call denied by access policy");
+            let error = anyhow::anyhow!("access denied");
+            self.record_audit(
+                "tools/call",
+                |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args));
+                },
+                Some(&error),
+            );
+            return Self::access_denied(id, format!("Access denied: tool {name}"));
+        }
+        let result = meta::call(self, name, args).await;
+        let error = result.as_ref().err().map(|e| anyhow::anyhow!("{e}"));
+        self.record_audit(
+            "tools/call",
+            |entry| {
+                entry.tool = Some(name.to_string());
+                entry.arguments = Some(json!(args));
+            },
+            error.as_ref(),
+        );
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result.unwrap_or_else(|e| Self::tool_error(name, &e))),
+            error: None,
+        }
+    }
+
+    /// An `isError` tool result saying why calling `name` failed.
+    fn tool_error(name: &str, message: &str) -> Value {
+        json!({
+            "content": [{
+                "type": "text",
+                "text": format!("Error calling {name}: {message}")
+            }],
+            "isError": true
+        })
+    }
+
+    pub fn get_manifest(&self) -> Option<&MCPManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// The tools the manifest declares, none before [`Self::initialize`].
+    pub fn tools(&self) -> &[ToolDef] {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .and_then(|capabilities| capabilities.tools.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The resources the manifest declares, none before [`Self::initialize`].
+    pub fn resources(&self) -> &[ResourceDef] {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .and_then(|capabilities| capabilities.resources.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The resource templates the manifest declares, none before
+    /// [`Self::initialize`].
+    pub fn resource_templates(&self) -> &[ResourceTemplateDef] {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .and_then(|capabilities| capabilities.resource_templates.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The prompts the manifest declares, none before [`Self::initialize`].
+    pub fn prompts(&self) -> &[PromptDef] {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .and_then(|capabilities| capabilities.prompts.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The static file answering a get of `prompt` with `args`: its
+    /// `pathTemplate` filled in, or `prompts/{name}.json`.
+    pub fn prompt_to_path(
+        &self,
+        prompt: &PromptDef,
+        args: &HashMap<String, Value>,
+    ) -> anyhow::Result<String> {
+        let path = match &prompt.path_template {
+            Some(path_template) => Self::expand_tool_path(path_template, args)?,
+            None => format!("prompts/{}.json", prompt.name),
+        };
+        Ok(self.rewrite_path(path))
+    }
+
+    /// What the first of the bridge's and then the manifest's [`rewrite`]
+    /// rules of `kind` matching `input` rewrites it into.
+    pub fn rewrite(&self, kind: Rewrite, input: &str) -> Option<String> {
+        let manifest = self.manifest.iter().flat_map(|manifest| &manifest.rewrites);
+        let rewritten = rewrite::first(self.rewrites.iter().chain(manifest), kind, input)?;
+        debug!(?kind, input, rewritten, "rewritten");
+        Some(rewritten)
+    }
+
+    /// `path` as the path rewrite rules leave it.
+    fn rewrite_path(&self, path: String) -> String {
+        self.rewrite(Rewrite::PathToPath, &path).unwrap_or(path)
+    }
+
+    /// The declared tool named or aliased `name`, compared normalized when
+    /// the bridge normalizes.
+    pub fn find_tool(&self, name: &str) -> Option<&ToolDef> {
+        if self.normalize {
+            let name = normalize::normalize(name);
+            return self.tools().iter().find(|tool| {
+                normalize::normalize(&tool.name) == name
+                    || tool
+                        .aliases
+                        .iter()
+                        .any(|alias| normalize::normalize(alias) == name)
+            });
+        }
+        self.tools()
+            .iter()
+            .find(|tool| tool.name == name || tool.aliases.iter().any(|alias| alias == name))
+    }
+
+    /// The URI `uri` is read as: the declared resource it names or aliases,
+    /// or itself when a declared template matches it.
+    pub fn resolve_uri<'a>(&'a self, uri: &'a str) -> Option<&'a str> {
+        let resource = self.resources().iter().find(|resource| {
+            resource.uri == uri || resource.aliases.iter().any(|alias| alias == uri)
+        });
+        match resource {
+            Some(resource) => Some(&resource.uri),
+            None => self
+                .resource_templates()
+                .iter()
+                .any(|template| template.matches(uri))
+                .then_some(uri),
+        }
+    }
+
+    /// The static file holding the resource `uri`: its `pathTemplate` when
+    /// the manifest declares one for it or a template matching it, else
+    /// what a rewrite rule maps it to, otherwise the path resolver's; then
+    /// as the path rewrite rules leave it.
+    pub fn uri_to_path(&self, uri: &str) -> String {
+        let declared = self
+            .resources()
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .and_then(|resource| resource.path_template.clone());
+        let path = declared
+            .or_else(|| {
+                self.resource_templates()
+                    .iter()
+                    .find_map(|template| template.path(uri))
+            })
+            .or_else(|| self.rewrite(Rewrite::UriToPath, uri))
+            .unwrap_or_else(|| self.path_resolver.uri_to_path(uri));
+        self.rewrite_path(path)
+    }
+
+    /// The static file answering a call of `tool_name` with `args`. An alias
+    /// maps to its tool's files, and arguments the call omits are first set
+    /// to their schema's `default`, so leaving one out maps to the same file
+    /// as passing its default. A tool's `pathTemplate` is filled in from the
+    /// arguments; otherwise the path resolver maps the call. Either path is
+    /// then rewritten by the path rewrite rules.
+    pub fn tool_to_path(
+        &self,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+    ) -> anyhow::Result<String> {
+        let mut args = Cow::Borrowed(args);
+        let tool = self.find_tool(tool_name);
+        if let Some(tool) = tool {
+            for (name, default) in tool.defaults() {
+                if !args.contains_key(name) {
+                    args.to_mut().insert(name.to_string(), default.clone());
+                }
+            }
+        }
+        if self.normalize {
+            for value in args.to_mut().values_mut() {
+                if let Value::String(text) = value {
+                    *text = normalize::normalize(text);
+                }
+            }
+        }
+        let path = match tool.and_then(|tool| tool.path_template.as_ref()) {
+            Some(path_template) => Self::expand_tool_path(path_template, &args)?,
+            None => {
+                let tool_name = tool.map_or(tool_name, |tool| tool.name.as_str());
+                self.path_resolver.tool_to_path(tool, tool_name, &args)?
+            }
+        };
+        Ok(self.rewrite_path(path))
+    }
+
+    /// Fills `path_template` in from `args`. Values are encoded as one path
+    /// segment each, so a call can't reach files outside the tool's.
+    fn expand_tool_path(
+        path_template: &str,
+        args: &HashMap<String, Value>,
+    ) -> anyhow::Result<String> {
+        let mut unusable = None;
+        let path = path_template::expand(path_template, |name| {
+            match encode_segment(&path_value(args.get(name)?)) {
+                Ok(segment) => Some(segment),
+                Err(e) => {
+                    unusable.get_or_insert_with(|| format!("argument `{name}`: {e}"));
+                    Some(String::new())
+                }
+            }
+        })
+        .map_err(|name| anyhow::anyhow!("missing argument `{name}` for path {path_template}"))?;
+        if let Some(message) = unusable {
+            anyhow::bail!(message);
+        }
+        Ok(path)
+    }
+
+    pub async fn handle_request(&self, request: MCPRequest) -> MCPResponse {
+        self.reply(request).await.into_response().await
+    }
+
+    /// [`Self::handle_request`], leaving a read of a file larger than
+    /// [`Self::stream_threshold`] to be sent in chunks. With [`Self::hooks`],
+    /// every other reply is built whole for them to see.
+    pub async fn reply(&self, request: MCPRequest) -> MCPReply {
+        let Some(metrics) = &self.metrics else {
+            return self.hooked(request).await;
+        };
+        let method = request.method.clone();
+        metrics.request_started(&method);
+        let started = Instant::now();
+        let reply = self.hooked(request).await;
+        let error = match &reply {
+            MCPReply::Response(response) => response.error.as_ref().map(|error| error.code),
+            _ => None,
+        };
+        metrics.request_finished(&method, started.elapsed(), error);
+        reply
+    }
+
+    /// [`Self::reply`] without the metrics.
+    async fn hooked(&self, mut request: MCPRequest) -> MCPReply {
+        if self.hooks.is_empty() {
+            return self.answer(request).await;
+        }
+        let mut answered = None;
+        for hook in &self.hooks {
+            match hook.before_request(&mut request).await {
+                Ok(None) => continue,
+                Ok(Some(response)) => answered = Some(response),
+                Err(error) => {
+                    let mut response = MCPResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id.clone(),
+                        result: None,
+                        error: Some(error),
+                    };
+                    Self::tag_error(&mut response);
+                    answered = Some(response);
+                }
+            }
+            break;
+        }
+        let mut response = match answered {
+            Some(response) => response,
+            None => match self.answer(request.clone()).await {
+                reply @ MCPReply::Chunked { .. } => return reply,
+                reply => reply.into_response().await,
+            },
+        };
+        for hook in &self.hooks {
+            match response.error.as_mut() {
+                Some(error) => hook.on_error(&request, error).await,
+                None => hook.after_response(&request, &mut response).await,
+            }
+        }
+        MCPReply::Response(response)
+    }
+
+    /// [`Self::reply`] without the hooks.
+    async fn answer(&self, request: MCPRequest) -> MCPReply {
+        let span = tracing::info_span!(
+            "mcp_request",
+            id = %request.id.as_ref().map(|id| id.to_string()).unwrap_or_default(),
+            method = %request.method,
+            path = tracing::field::Empty,
+            client = tracing::field::Empty,
+        );
+        if let Some(client) = Self::client_info() {
+            span.record("client", tracing::field::display(client));
+        }
+        let version = Self::protocol_version();
+        #[cfg(feature = "server")]
+        let cached = self.response_cache.as_ref().and_then(|cache| {
+            Some((
+                cache,
+                server::response_cache::ResponseCache::key(&request, version)?,
+            ))
+        });
+        #[cfg(feature = "server")]
+        if let Some((cache, key)) = &cached
+            && let Some(result) = cache.get(key)
+        {
+            debug!(method = %request.method, "answered from the response cache");
+            self.audit_cached(&request);
+            return MCPReply::Verbatim {
+                id: request.id,
+                result,
+            };
+        }
+        let structured = request.method == "tools/call"
+            && request
+                .params
+                .as_ref()
+                .and_then(|params| params.get("name"))
+                .and_then(Value::as_str)
+                .and_then(|name| self.find_tool(name))
+                .is_some_and(|tool| tool.extra.contains_key("outputSchema"));
+        let method = request.method.clone();
+        let mut reply = self.dispatch(request).instrument(span).await;
+        // Streamed contents are a raw file's, reshaped only in the fields the
+        // manifest describes them with.
+        let streamed = matches!(reply, MCPReply::Chunked { .. });
+        if let MCPReply::Chunked { contents, .. } = &mut reply
+            && protocol::reshapes(version, &method, structured)
+        {
+            let description = std::mem::take(&mut contents.description);
+            let mut result = json!({ "contents": [description] });
+            protocol::adapt(version, &method, &mut result, structured);
+            if let Some(Value::Object(description)) = result["contents"].get_mut(0).map(Value::take)
+            {
+                contents.description = description;
+            }
+        }
+        if protocol::reshapes(version, &method, structured) && !streamed {
+            let mut response = reply.into_response().await;
+            if let Some(result) = response.result.as_mut() {
+                protocol::adapt(version, &method, result, structured);
+            }
+            reply = MCPReply::Response(response);
+        }
+        if let MCPReply::Response(response) = &mut reply {
+            Self::tag_error(response);
+        }
+        #[cfg(feature = "server")]
+        if let Some((cache, key)) = cached {
+            match &reply {
+                MCPReply::Response(response) => cache.insert_response(key, response),
+                MCPReply::Verbatim { result, .. } => cache.insert(key, result.clone()),
+                MCPReply::Chunked { .. } => {}
+            }
+        }
+        reply
+    }
+
+    /// Audits `request`, answered from the response cache, as reading its
+    /// file would have been.
+    #[cfg(feature = "server")]
+    fn audit_cached(&self, request: &MCPRequest) {
+        let param = |name| request.params.as_ref().and_then(|params| params.get(name));
+        self.record_audit(
+            &request.method,
+            |entry| match request.method.as_str() {
+                "tools/call" => {
+                    let name = param("name").and_then(Value::as_str).unwrap_or("");
+                    let tool = self.find_tool(name).map_or(name, |tool| &tool.name);
+                    entry.tool = Some(tool.to_string());
+                    entry.arguments = Some(param("arguments").cloned().unwrap_or(json!({})));
+                }
+                _ => entry.uri = param("uri").and_then(Value::as_str).map(str::to_string),
+            },
+            None,
+        );
+    }
+
+    #[cfg(feature = "server")]
+    pub(crate) fn tag_error(response: &mut MCPResponse) {
+        if let (Some(error), Some(request_id)) =
+            (response.error.as_mut(), server::request_id::current())
+        {
+            error.attach_request_id(request_id);
         }
+    }
 
-        Ok(())
+    #[cfg(not(feature = "server"))]
+    pub(crate) fn tag_error(_response: &mut MCPResponse) {}
+
+    /// Caller of the request being handled, as authenticated by the server.
+    #[cfg(feature = "server")]
+    fn principal() -> Option<Principal> {
+        server::principal::current()
     }
 
-    pub fn get_manifest(&self) -> Option<&MCPManifest> {
-        self.manifest.as_ref()
+    #[cfg(not(feature = "server"))]
+    fn principal() -> Option<Principal> {
+        None
     }
 
-    pub fn uri_to_path(&self, uri: &str) -> String {
-        if uri.starts_with("file://") {
-            format!("resources/{}.json", uri.strip_prefix("file://").unwrap())
-        } else if uri.contains("://") {
-            let parts: Vec<&str> = uri.split("://").collect();
-            if parts.len() == 2 {
-                format!("resources/{}.json", parts[1])
-            } else {
-                format!("{uri}.json")
-            }
-        } else if uri.ends_with(".json") {
-            uri.to_string()
-        } else {
-            format!("{uri}.json")
-        }
+    /// Protocol version of the client whose request is being handled, as
+    /// its session agreed or its transport said.
+    #[cfg(feature = "server")]
+    fn protocol_version() -> &'static str {
+        server::protocol::current().unwrap_or(protocol::LATEST)
     }
 
-    pub fn tool_to_path(&self, tool_name: &str, args: &HashMap<String, Value>) -> String {
-        let tool_dir = format!("tools/{tool_name}");
+    #[cfg(not(feature = "server"))]
+    fn protocol_version() -> &'static str {
+        protocol::LATEST
+    }
 
-        if args.is_empty() {
-            return format!("{tool_dir}.json");
-        }
+    /// The MCP client whose request is being handled, as its session's
+    /// `initialize` described it.
+    #[cfg(feature = "server")]
+    fn client_info() -> Option<Arc<ClientInfo>> {
+        server::client::current()
+    }
 
-        if args.len() == 1 {
-            let arg_value = args.values().next().unwrap();
-            let arg_str = match arg_value {
-                Value::String(s) => s.clone(),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                _ => serde_json::to_string(arg_value).unwrap_or_default(),
-            };
-            return format!("{tool_dir}/{arg_str}.json");
-        }
+    #[cfg(not(feature = "server"))]
+    fn client_info() -> Option<Arc<ClientInfo>> {
+        None
+    }
 
-        if args.len() == 2 {
-            let mut values: Vec<String> = args
-                .values()
-                .map(|v| match v {
-                    Value::String(s) => s.clone(),
-                    Value::Number(n) => n.to_string(),
-                    Value::Bool(b) => b.to_string(),
-                    _ => serde_json::to_string(v).unwrap_or_default(),
-                })
-                .collect();
-            values.sort();
-            return format!("{}/{}/{}.json", tool_dir, values[0], values[1]);
-        }
+    /// The `/events` session the request being handled was posted to.
+    #[cfg(feature = "server")]
+    fn session_id() -> Option<String> {
+        server::session::current()
+    }
 
-        // Multiple arguments - create a hash-like path
-        let mut sorted_args: Vec<(String, String)> = args
-            .iter()
-            .map(|(k, v)| {
-                let val_str = match v {
-                    Value::String(s) => s.clone(),
-                    Value::Number(n) => n.to_string(),
-                    Value::Bool(b) => b.to_string(),
-                    _ => serde_json::to_string(v).unwrap_or_default(),
-                };
-                (k.clone(), val_str)
-            })
-            .collect();
-        sorted_args.sort_by(|a, b| a.0.cmp(&b.0));
+    #[cfg(not(feature = "server"))]
+    fn session_id() -> Option<String> {
+        None
+    }
 
-        let arg_string = sorted_args
-            .iter()
-            .map(|(k, v)| format!("{k}={v}"))
-            .collect::<Vec<_>>()
-            .join("&");
+    /// When the server gives up on the request being handled.
+    #[cfg(feature = "server")]
+    fn deadline() -> Option<Instant> {
+        server::timeout::deadline()
+    }
 
-        let hash = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            arg_string.as_bytes(),
-        )
-        .replace(['/', '+', '='], "_");
+    #[cfg(not(feature = "server"))]
+    fn deadline() -> Option<Instant> {
+        None
+    }
 
-        format!("{tool_dir}/{hash}.json")
+    /// Locales the request being handled asked for through the transport,
+    /// best first.
+    #[cfg(feature = "server")]
+    fn requested_locales() -> Vec<String> {
+        server::locale::current()
     }
 
-    pub async fn handle_request(&self, request: MCPRequest) -> MCPResponse {
-        match request.method.as_str() {
-            "initialize" => self.handle_initialize(request.id),
+    #[cfg(not(feature = "server"))]
+    fn requested_locales() -> Vec<String> {
+        Vec::new()
+    }
+
+    fn permits_tool(&self, name: &str) -> bool {
+        self.policy
+            .as_ref()
+            .is_none_or(|policy| policy.may_call_tool(Self::principal().as_ref(), name))
+    }
+
+    fn permits_resource(&self, uri: &str) -> bool {
+        self.policy
+            .as_ref()
+            .is_none_or(|policy| policy.may_read_resource(Self::principal().as_ref(), uri))
+    }
+
+    fn resource_not_found(id: Option<Value>, uri: &str) -> MCPResponse {
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(MCPError {
+                code: -32002,
+                message: "Resource not found".to_string(),
+                data: Some(json!({ "uri": uri })),
+            }),
+        }
+    }
+
+    fn access_denied(id: Option<Value>, message: String) -> MCPResponse {
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(MCPError {
+                code: -32003,
+                message,
+                data: None,
+            }),
+        }
+    }
+
+    async fn dispatch(&self, request: MCPRequest) -> MCPReply {
+        let response = match request.method.as_str() {
+            "initialize" => {
+                if let Some(client) = ClientInfo::from_initialize(&request) {
+                    info!(%client, "client initialized");
+                }
+                self.handle_initialize(request.id, request.params.as_ref())
+            }
             "resources/list" => self.handle_list_resources(request.id).await,
             "resources/read" => {
-                self.handle_read_resource(request.id, request.params.unwrap_or(json!({})))
-                    .await
+                return self
+                    .handle_read_resource(request.id, request.params.unwrap_or(json!({})))
+                    .await;
             }
+            "resources/templates/list" => self.handle_list_resource_templates(request.id),
             "tools/list" => self.handle_list_tools(request.id).await,
             "tools/call" => {
-                self.handle_call_tool(request.id, request.params.unwrap_or(json!({})))
+                return self
+                    .handle_call_tool(request.id, request.params.unwrap_or(json!({})))
+                    .await;
+            }
+            "prompts/list" if self.declares_prompts() => self.handle_list_prompts(request.id),
+            "prompts/get" if self.declares_prompts() => {
+                self.handle_get_prompt(request.id, request.params.unwrap_or(json!({})))
+                    .await
+            }
+            "completion/complete" if self.declares_completions() => {
+                self.handle_complete(request.id, request.params.unwrap_or(json!({})))
                     .await
             }
             _ => MCPResponse {
@@ -263,17 +2198,32 @@ impl MCPBridge {
                     data: None,
                 }),
             },
-        }
+        };
+        MCPReply::Response(response)
     }
 
-    pub fn create_sse_stream(_bridge: Arc<Self>) -> impl Stream<Item = Result<Event, axum::Error>> {
+    /// How often event streams send a `ping` unless told otherwise.
+    #[cfg(feature = "sse")]
+    pub const PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+    /// The event stream: a `ready` message, then a `ping` event every
+    /// `ping_interval`, so proxies don't close the connection as idle. A
+    /// zero interval sends no pings.
+    #[cfg(feature = "sse")]
+    pub fn create_sse_stream(
+        _bridge: Arc<Self>,
+        ping_interval: std::time::Duration,
+    ) -> impl Stream<Item = Result<Event, axum::Error>> {
         async_stream::stream! {
             yield Ok(Event::default()
                 .event("message")
                 .data(r#"{"jsonrpc":"2.0","method":"ready","params":{}}"#));
 
+            if ping_interval.is_zero() {
+                futures::future::pending::<()>().await;
+            }
             loop {
-                tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                tokio::time::sleep(ping_interval).await;
                 yield Ok(Event::default()
                     .event("ping")
                     .data(""));
@@ -281,22 +2231,48 @@ impl MCPBridge {
         }
     }
 
-    fn handle_initialize(&self, id: Option<Value>) -> MCPResponse {
+    /// [`Self::create_sse_stream`] preceded by an `endpoint` event carrying the
+    /// URL clients should POST their messages to.
+    #[cfg(feature = "sse")]
+    pub fn create_sse_stream_with_endpoint(
+        bridge: Arc<Self>,
+        endpoint: String,
+        ping_interval: std::time::Duration,
+    ) -> impl Stream<Item = Result<Event, axum::Error>> {
+        use futures::StreamExt;
+
+        futures::stream::once(async move { Ok(Event::default().event("endpoint").data(endpoint)) })
+            .chain(Self::create_sse_stream(bridge, ping_interval))
+    }
+
+    fn handle_initialize(&self, id: Option<Value>, params: Option<&Value>) -> MCPResponse {
+        let version = protocol::negotiate(
+            params
+                .and_then(|params| params.get("protocolVersion"))
+                .and_then(Value::as_str),
+        );
         if let Some(manifest) = &self.manifest {
             let capabilities = manifest
                 .capabilities
                 .as_ref()
                 .map(|caps| {
                     let mut result = json!({});
-                    if let Some(resources) = &caps.resources {
-                        if !resources.is_empty() {
-                            result["resources"] = json!({"listChanged": true});
-                        }
+                    let resources = caps.resources.iter().flatten().count()
+                        + caps.resource_templates.iter().flatten().count();
+                    if resources > 0 {
+                        result["resources"] = json!({"listChanged": true});
                     }
-                    if let Some(tools) = &caps.tools {
-                        if !tools.is_empty() {
-                            result["tools"] = json!({"listChanged": true});
-                        }
+                    if let Some(tools) = &caps.tools
+                        && !tools.is_empty()
+                    {
+                        result["tools"] = json!({"listChanged": true});
+                    }
+                    if caps.prompts.is_some() {
+                        result["prompts"] = json!({});
+                    }
+                    // Completions came with `2025-03-26`.
+                    if caps.completions.is_some() && version >= "2025-03-26" {
+                        result["completions"] = json!({});
                     }
                     result
                 })
@@ -322,7 +2298,7 @@ impl MCPBridge {
                 jsonrpc: "2.0".to_string(),
                 id,
                 result: Some(json!({
-                    "protocolVersion": "2025-06-18",
+                    "protocolVersion": version,
                     "capabilities": capabilities,
                     "serverInfo": server_info
                 })),
@@ -333,7 +2309,7 @@ impl MCPBridge {
                 jsonrpc: "2.0".to_string(),
                 id,
                 result: Some(json!({
-                    "protocolVersion": "2025-06-18",
+                    "protocolVersion": version,
                     "capabilities": {
                         "resources": {},
                         "tools": {}
@@ -348,16 +2324,208 @@ impl MCPBridge {
         }
     }
 
+    fn declares_prompts(&self) -> bool {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .is_some_and(|capabilities| capabilities.prompts.is_some())
+    }
+
+    fn declares_completions(&self) -> bool {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.capabilities.as_ref())
+            .is_some_and(|capabilities| capabilities.completions.is_some())
+    }
+
+    fn invalid_params(id: Option<Value>, message: String) -> MCPResponse {
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(MCPError {
+                code: -32602,
+                message,
+                data: None,
+            }),
+        }
+    }
+
+    fn handle_list_prompts(&self, id: Option<Value>) -> MCPResponse {
+        let prompts = self
+            .prompts()
+            .iter()
+            .map(|prompt| {
+                let mut listed = json!(prompt);
+                if let Some(listed) = listed.as_object_mut() {
+                    listed.remove("pathTemplate");
+                }
+                listed
+            })
+            .collect::<Vec<_>>();
+        debug!(count = prompts.len(), "listed prompts");
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({ "prompts": prompts })),
+            error: None,
+        }
+    }
+
+    async fn handle_get_prompt(&self, id: Option<Value>, params: Value) -> MCPResponse {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+        let args: HashMap<String, Value> = params
+            .get("arguments")
+            .and_then(Value::as_object)
+            .map(|arguments| {
+                arguments
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let Some(prompt) = self.prompts().iter().find(|prompt| prompt.name == name) else {
+            return Self::invalid_params(id, format!("Unknown prompt: {name}"));
+        };
+        if let Some(missing) = prompt
+            .arguments
+            .iter()
+            .find(|argument| argument.required && !args.contains_key(&argument.name))
+        {
+            return Self::invalid_params(
+                id,
+                format!(
+                    "Missing required argument `{}` for prompt {name}",
+                    missing.name
+                ),
+            );
+        }
+        let path = match self.prompt_to_path(prompt, &args) {
+            Ok(path) => path,
+            Err(e) => return Self::invalid_params(id, format!("Invalid arguments: {e}")),
+        };
+        info!(name, path, "getting prompt");
+        let result = self.load_json(&path).await;
+        self.record_audit(
+            "prompts/get",
+            |entry| {
+                entry.arguments = Some(json!(args));
+                entry.path = Some(path.clone());
+            },
+            result.as_ref().err(),
+        );
+        match result {
+            Ok(mut prompt) => {
+                template::render(&mut prompt, &template::arguments(&args));
+                // A file can hold just the messages.
+                if prompt.is_array() {
+                    prompt = json!({ "messages": prompt });
+                }
+                MCPResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: Some(prompt),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                warn!(name, error = %e, "failed to get prompt");
+                if is_not_found(&e) {
+                    return Self::invalid_params(
+                        id,
+                        format!("No pre-generated prompt {name} for these arguments"),
+                    );
+                }
+                MCPResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: None,
+                    error: Some(MCPError {
+                        code: -32603,
+                        message: format!("Failed to get prompt {name}"),
+                        data: None,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Offers the values the site lists for the argument, none when it
+    /// lists none.
+    async fn handle_complete(&self, id: Option<Value>, params: Value) -> MCPResponse {
+        let Some(reference) = completion::Reference::of(&params) else {
+            return Self::invalid_params(
+                id,
+                "Expected `ref` to name a prompt or a resource template".to_string(),
+            );
+        };
+        let unknown = match reference {
+            completion::Reference::Prompt(name) => {
+                (!self.prompts().iter().any(|prompt| prompt.name == name))
+                    .then(|| format!("Unknown prompt: {name}"))
+            }
+            completion::Reference::Resource(uri_template) => (!self
+                .resource_templates()
+                .iter()
+                .any(|template| template.uri_template == uri_template))
+            .then(|| format!("Unknown resource template: {uri_template}")),
+        };
+        if let Some(message) = unknown {
+            return Self::invalid_params(id, message);
+        }
+        let argument = params.get("argument");
+        let Some(name) = argument
+            .and_then(|argument| argument.get("name"))
+            .and_then(Value::as_str)
+        else {
+            return Self::invalid_params(id, "Missing `argument.name`".to_string());
+        };
+        let prefix = argument
+            .and_then(|argument| argument.get("value"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let path = match reference.path(name) {
+            Ok(path) => path,
+            Err(e) => return Self::invalid_params(id, format!("Invalid argument: {e}")),
+        };
+        let values = match self.load_json(&path).await {
+            Ok(values) => values,
+            Err(e) if is_not_found(&e) => {
+                debug!(path, "no completion file");
+                json!([])
+            }
+            Err(e) => {
+                warn!(path, error = %e, "failed to read completions");
+                json!([])
+            }
+        };
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(completion::complete(&values, prefix)),
+            error: None,
+        }
+    }
+
     async fn handle_list_resources(&self, id: Option<Value>) -> MCPResponse {
         if let Some(manifest) = &self.manifest {
-            let resources = manifest
+            let mut resources = manifest
                 .capabilities
                 .as_ref()
                 .and_then(|c| c.resources.as_ref())
-                .cloned()
-                .unwrap_or_default();
+                .into_iter()
+                .flatten()
+                .filter(|resource| self.permits_resource(&resource.uri))
+                .map(|resource| json!(resource))
+                .collect::<Vec<_>>();
+            let upstream = self.upstream_entries("resources/list", "resources").await;
+            resources.extend(upstream.into_iter().filter(|resource| {
+                resource["uri"].as_str().is_some_and(|uri| {
+                    self.resolve_uri(uri).is_none() && self.permits_resource(uri)
+                })
+            }));
 
-            eprintln!("📋 Listed {} resources", resources.len());
+            debug!(count = resources.len(), "listed resources");
 
             MCPResponse {
                 jsonrpc: "2.0".to_string(),
@@ -379,32 +2547,107 @@ impl MCPBridge {
         }
     }
 
-    async fn handle_read_resource(&self, id: Option<Value>, params: Value) -> MCPResponse {
-        let uri = params.get("uri").and_then(|u| u.as_str()).unwrap_or("");
-        eprintln!("📖 Reading resource: {uri}");
+    fn handle_list_resource_templates(&self, id: Option<Value>) -> MCPResponse {
+        let templates: Vec<&ResourceTemplateDef> = self
+            .resource_templates()
+            .iter()
+            .filter(|template| self.permits_resource(&template.uri_template))
+            .collect();
+        debug!(count = templates.len(), "listed resource templates");
+        MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({ "resourceTemplates": templates })),
+            error: None,
+        }
+    }
 
-        let resource_path = self.uri_to_path(uri);
+    async fn handle_read_resource(&self, id: Option<Value>, params: Value) -> MCPReply {
+        let requested = params.get("uri").and_then(|u| u.as_str()).unwrap_or("");
+        let rewritten = self.rewrite(Rewrite::UriToUri, requested);
+        let mut uri = rewritten.as_deref().unwrap_or(requested);
+        // Only declared URIs are looked up, so a read can't probe for other
+        // files on the site.
+        if self.manifest.is_some() {
+            match self.resolve_uri(uri) {
+                Some(canonical) => uri = canonical,
+                None => {
+                    if self.permits_resource(uri)
+                        && let Some(response) = self
+                            .forward(id.clone(), "resources/read", &params, |entry| {
+                                entry.uri = Some(uri.to_string())
+                            })
+                            .await
+                    {
+                        return response.into();
+                    }
+                    warn!(uri, "read of undeclared resource");
+                    let error = anyhow::anyhow!("unknown resource");
+                    self.record_audit(
+                        "resources/read",
+                        |entry| entry.uri = Some(uri.to_string()),
+                        Some(&error),
+                    );
+                    return Self::resource_not_found(id, uri).into();
+                }
+            }
+        }
+        if !self.permits_resource(uri) {
+            warn!(uri, "resource read denied by access policy");
+            let error = anyhow::anyhow!("access denied");
+            self.record_audit(
+                "resources/read",
+                |entry| entry.uri = Some(uri.to_string()),
+                Some(&error),
+            );
+            return Self::access_denied(id, format!("Access denied: resource {uri}")).into();
+        }
+        let forwarded = self
+            .resources()
+            .iter()
+            .any(|resource| resource.uri == uri && resource.upstream);
+        if forwarded
+            && let Some(response) = self
+                .forward(id.clone(), "resources/read", &params, |entry| {
+                    entry.uri = Some(uri.to_string())
+                })
+                .await
+        {
+            return response.into();
+        }
+        let requested = match params.get("locale").and_then(Value::as_str) {
+            Some(locale) => vec![locale.to_string()],
+            None => Self::requested_locales(),
+        };
+        let locales = locale::negotiate(&self.locales, &requested);
+        info!(uri, ?locales, "reading resource");
+        let accept = params.get("accept").and_then(Value::as_str);
+        let (resource_path, result) = self.load_resource(uri, &locales, accept).await;
+        tracing::Span::current().record("path", resource_path.as_str());
+        // Checked first, so no borrow of the result is held while forwarding.
+        let missing = matches!(&result, Err(e) if is_not_found(e));
+        if missing
+            && let Some(response) = self
+                .forward(id.clone(), "resources/read", &params, |entry| {
+                    entry.uri = Some(uri.to_string())
+                })
+                .await
+        {
+            return response.into();
+        }
+        self.record_audit(
+            "resources/read",
+            |entry| {
+                entry.uri = Some(uri.to_string());
+                entry.path = Some(resource_path.clone());
+            },
+            result.as_ref().err(),
+        );
 
-        match self.data_source.load_json(&resource_path).await {
-            Ok(resource) => {
-                let contents = if let Some(contents) = resource.get("contents") {
-                    contents.clone()
-                } else if resource.get("uri").is_some()
-                    && resource.get("mimeType").is_some()
-                    && resource.get("text").is_some()
-                {
-                    json!([{
-                        "uri": resource["uri"],
-                        "mimeType": resource["mimeType"],
-                        "text": resource["text"]
-                    }])
-                } else {
-                    json!([{
-                        "uri": uri,
-                        "mimeType": "application/json",
-                        "text": serde_json::to_string_pretty(&resource).unwrap_or_default()
-                    }])
-                };
+        match result {
+            Ok(Loaded::Whole(resource)) => {
+                let mut contents = resource_contents(uri, &resource);
+                self.describe_contents(uri, &mut contents);
 
                 MCPResponse {
                     jsonrpc: "2.0".to_string(),
@@ -412,33 +2655,126 @@ impl MCPBridge {
                     result: Some(json!({ "contents": contents })),
                     error: None,
                 }
+                .into()
             }
+            Ok(Loaded::Chunked(mut contents)) => {
+                contents.description = self.description(uri);
+                MCPReply::Chunked { id, contents }
+            }
+            Ok(Loaded::Verbatim(result)) => MCPReply::Verbatim { id, result },
+            // The error names the file behind the URI, which is logged but
+            // not told to the client.
             Err(e) => {
-                eprintln!("❌ Error reading resource {uri}: {e}");
+                warn!(uri, error = %e, "failed to read resource");
+                if is_not_found(&e) {
+                    return Self::resource_not_found(id, uri).into();
+                }
                 MCPResponse {
                     jsonrpc: "2.0".to_string(),
                     id,
                     result: None,
                     error: Some(MCPError {
                         code: -32603,
-                        message: format!("Failed to read resource {uri}: {e}"),
+                        message: format!("Failed to read resource {uri}"),
                         data: None,
                     }),
                 }
+                .into()
+            }
+        }
+    }
+
+    /// The `title` and `annotations` the manifest entry for `uri` declares.
+    fn description(&self, uri: &str) -> serde_json::Map<String, Value> {
+        let Some(resource) = self.resources().iter().find(|resource| resource.uri == uri) else {
+            return serde_json::Map::new();
+        };
+        ["title", "annotations"]
+            .into_iter()
+            .filter_map(|key| Some((key.to_string(), resource.extra.get(key)?.clone())))
+            .collect()
+    }
+
+    /// Gives the `contents` entries for `uri` the [`Self::description`] its
+    /// manifest entry declares, where its file doesn't set them.
+    fn describe_contents(&self, uri: &str, contents: &mut Value) {
+        let declared = self.description(uri);
+        if declared.is_empty() {
+            return;
+        }
+        let entries = contents
+            .as_array_mut()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object_mut)
+            .filter(|entry| entry.get("uri").and_then(Value::as_str) == Some(uri));
+        for entry in entries {
+            for (key, value) in &declared {
+                match (entry.get_mut(key), value) {
+                    (None, _) => {
+                        entry.insert(key.clone(), value.clone());
+                    }
+                    // The file's own annotations win, key by key.
+                    (Some(Value::Object(own)), Value::Object(value)) => {
+                        for (key, value) in value {
+                            own.entry(key.clone()).or_insert_with(|| value.clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Adds the [`Self::tool_annotations`] `tool` doesn't set itself.
+    fn annotate(&self, tool: &mut Value) {
+        let Some(tool) = tool.as_object_mut() else {
+            return;
+        };
+        let annotations = tool.entry("annotations").or_insert_with(|| json!({}));
+        if let Some(annotations) = annotations.as_object_mut() {
+            for (key, value) in &self.tool_annotations {
+                annotations
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
             }
         }
     }
 
     async fn handle_list_tools(&self, id: Option<Value>) -> MCPResponse {
         if let Some(manifest) = &self.manifest {
-            let tools = manifest
+            let mut tools = manifest
                 .capabilities
                 .as_ref()
                 .and_then(|c| c.tools.as_ref())
-                .cloned()
-                .unwrap_or_default();
+                .into_iter()
+                .flatten()
+                .filter(|tool| self.permits_tool(&tool.name))
+                .map(|tool| json!(tool))
+                .collect::<Vec<_>>();
+            #[cfg(feature = "search")]
+            if self.search_index().is_some() && self.permits_tool(search::TOOL_NAME) {
+                tools.push(search::tool());
+            }
+            if self.meta_tools {
+                tools.extend(meta::tools().into_iter().filter(|tool| {
+                    let name = tool["name"].as_str().unwrap_or_default();
+                    self.find_tool(name).is_none() && self.permits_tool(name)
+                }));
+            }
+            if !self.tool_annotations.is_empty() {
+                for tool in &mut tools {
+                    self.annotate(tool);
+                }
+            }
+            let upstream = self.upstream_entries("tools/list", "tools").await;
+            tools.extend(upstream.into_iter().filter(|tool| {
+                tool["name"]
+                    .as_str()
+                    .is_some_and(|name| self.find_tool(name).is_none() && self.permits_tool(name))
+            }));
 
-            eprintln!("🔧 Listed {} tools", tools.len());
+            debug!(count = tools.len(), "listed tools");
 
             MCPResponse {
                 jsonrpc: "2.0".to_string(),
@@ -460,8 +2796,8 @@ impl MCPBridge {
         }
     }
 
-    async fn handle_call_tool(&self, id: Option<Value>, params: Value) -> MCPResponse {
-        let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
+    async fn handle_call_tool(&self, id: Option<Value>, params: Value) -> MCPReply {
+        let mut name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
         let arguments = params
             .get("arguments")
             .and_then(|a| a.as_object())
@@ -470,11 +2806,143 @@ impl MCPBridge {
 
         let args_map: HashMap<String, Value> = arguments.into_iter().collect();
 
-        eprintln!("🛠️  Calling tool: {name} with args: {args_map:?}");
+        #[cfg(feature = "search")]
+        if name == search::TOOL_NAME
+            && let Some(index) = self.search_index()
+        {
+            return self.call_search(id, index, &args_map).into();
+        }
+
+        if self.meta_tools
+            && [meta::LIST_PATHS, meta::DESCRIBE_TOOL].contains(&name)
+            && self.find_tool(name).is_none()
+        {
+            return self.call_meta(id, name, &args_map).await.into();
+        }
+
+        // Only declared tools are looked up, so a call can't probe for other
+        // files under `tools/`. Aliases are called as the tool they name.
+        if let Some(tool) = self.find_tool(name) {
+            name = &tool.name;
+        } else if self.manifest.is_some() {
+            if self.permits_tool(name)
+                && let Some(response) = self
+                    .forward(id.clone(), "tools/call", &params, |entry| {
+                        entry.tool = Some(name.to_string());
+                        entry.arguments = Some(json!(args_map));
+                    })
+                    .await
+            {
+                return response.into();
+            }
+            warn!(tool = name, "call to undeclared tool");
+            let error = anyhow::anyhow!("unknown tool");
+            self.record_audit(
+                "tools/call",
+                |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args_map));
+                },
+                Some(&error),
+            );
+            let tools: Vec<&str> = self
+                .tools()
+                .iter()
+                .map(|tool| tool.name.as_str())
+                .filter(|name| self.permits_tool(name))
+                .collect();
+            return MCPResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(MCPError {
+                    code: -32602,
+                    message: format!("Unknown tool: {name}"),
+                    data: Some(json!({ "tools": tools })),
+                }),
+            }
+            .into();
+        }
+
+        if !self.permits_tool(name) {
+            warn!(tool = name, "tool call denied by access policy");
+            let error = anyhow::anyhow!("access denied");
+            self.record_audit(
+                "tools/call",
+                |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args_map));
+                },
+                Some(&error),
+            );
+            return Self::access_denied(id, format!("Access denied: tool {name}")).into();
+        }
+
+        let forwarded = self.find_tool(name).is_some_and(|tool| tool.upstream);
+        if forwarded
+            && let Some(response) = self
+                .forward(id.clone(), "tools/call", &params, |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args_map));
+                })
+                .await
+        {
+            return response.into();
+        }
+
+        info!(tool = name, args = ?args_map, "calling tool");
 
-        let tool_path = self.tool_to_path(name, &args_map);
+        let handler = self
+            .find_tool(name)
+            .and_then(|tool| tool.handler.as_deref());
+        let (tool_path, result) = if let Some(handler) = handler {
+            tracing::Span::current().record("path", handler);
+            let result = handler::call(
+                self.data_source.as_ref(),
+                handler,
+                &args_map,
+                self.handler_limits,
+            )
+            .await;
+            (Some(handler.to_string()), result.map(Loaded::Whole))
+        } else {
+            match self.tool_to_path(name, &args_map) {
+                Ok(tool_path) => {
+                    let (tool_path, result) =
+                        self.load_tool_response(name, &args_map, tool_path).await;
+                    tracing::Span::current().record("path", tool_path.as_str());
+                    (Some(tool_path), result)
+                }
+                Err(e) => (None, Err(e)),
+            }
+        };
+        if let Err(e) = &result
+            && is_not_found(e)
+            && let Some(response) = self
+                .forward(id.clone(), "tools/call", &params, |entry| {
+                    entry.tool = Some(name.to_string());
+                    entry.arguments = Some(json!(args_map));
+                })
+                .await
+        {
+            return response.into();
+        }
+        self.record_audit(
+            "tools/call",
+            |entry| {
+                entry.tool = Some(name.to_string());
+                entry.arguments = Some(json!(args_map));
+                entry.path = tool_path;
+            },
+            result.as_ref().err(),
+        );
 
-        match self.data_source.load_json(&tool_path).await {
+        let result = match result {
+            Ok(Loaded::Verbatim(result)) => return MCPReply::Verbatim { id, result },
+            Ok(loaded) => loaded.into_value().await,
+            Err(e) => Err(e),
+        };
+        let response = match result {
             Ok(result) => {
                 let content = if result.get("content").is_some() || result.get("contents").is_some()
                 {
@@ -496,7 +2964,7 @@ impl MCPBridge {
                 }
             }
             Err(e) => {
-                eprintln!("❌ Error calling tool {name}: {e}");
+                warn!(tool = name, error = %e, "tool call failed");
                 MCPResponse {
                     jsonrpc: "2.0".to_string(),
                     id,
@@ -510,11 +2978,108 @@ impl MCPBridge {
                     error: None,
                 }
             }
+        };
+        response.into()
+    }
+}
+
+/// The `contents` of a `resources/read` result for the file `resource`
+/// holding `uri`: its own `contents`, its single text entry, or the whole
+/// file as JSON text.
+pub(crate) fn resource_contents(uri: &str, resource: &Value) -> Value {
+    if let Some(contents) = resource.get("contents") {
+        contents.clone()
+    } else if resource.get("uri").is_some()
+        && resource.get("mimeType").is_some()
+        && resource.get("text").is_some()
+    {
+        // The file is the one entry, with its `title`, `annotations` and the
+        // rest kept.
+        json!([resource])
+    } else {
+        json!([{
+            "uri": uri,
+            "mimeType": "application/json",
+            "text": serde_json::to_string_pretty(resource).unwrap_or_default()
+        }])
+    }
+}
+
+/// A resource file holding `bytes` of type `mime`, as `resources/read`
+/// contents carrying them base64-encoded in `blob`.
+fn blob_contents(uri: &str, mime: &str, bytes: &[u8]) -> anyhow::Result<Value> {
+    Ok(json!({ "contents": [blob_entry(uri, mime, bytes)?] }))
+}
+
+/// One `contents` entry carrying `bytes` of type `mime` base64-encoded.
+#[cfg(feature = "base64")]
+pub(crate) fn blob_entry(uri: &str, mime: &str, bytes: &[u8]) -> anyhow::Result<Value> {
+    let blob = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes);
+    Ok(json!({ "uri": uri, "mimeType": mime, "blob": blob }))
+}
+
+#[cfg(not(feature = "base64"))]
+pub(crate) fn blob_entry(_uri: &str, _mime: &str, _bytes: &[u8]) -> anyhow::Result<Value> {
+    anyhow::bail!("Binary resources require the `base64` feature")
+}
+
+/// A resource file's answer: parsed whole, or a raw file too large to hold,
+/// still to be read.
+pub(crate) enum Loaded {
+    Whole(Value),
+    Chunked(ChunkedContents),
+    /// A file that is already the whole result, as it was read.
+    Verbatim(Box<RawValue>),
+}
+
+impl Loaded {
+    /// The answer read whole.
+    pub(crate) async fn into_value(self) -> anyhow::Result<Value> {
+        match self {
+            Self::Whole(value) => Ok(value),
+            Self::Chunked(contents) => Ok(json!({ "contents": contents.collect().await? })),
+            Self::Verbatim(raw) => Ok(serde_json::from_str(raw.get())?),
         }
     }
 }
 
+/// Whether a tool's response file with these top-level keys is already a
+/// `tools/call` result, rather than data to be wrapped in one.
+fn is_tool_result(keys: &[&str]) -> bool {
+    keys.iter().any(|key| ["content", "contents"].contains(key))
+}
+
+/// Parses a JSON file a data source read whole, for every source alike.
+#[cfg(not(feature = "simd"))]
+pub(crate) fn parse_json(bytes: &[u8]) -> anyhow::Result<Value> {
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Parses a JSON file a data source read whole, for every source alike,
+/// with simd-json. It parses in place, so it works on a copy of `bytes`.
+#[cfg(feature = "simd")]
+pub(crate) fn parse_json(bytes: &[u8]) -> anyhow::Result<Value> {
+    Ok(simd_json::serde::from_slice(&mut bytes.to_vec())?)
+}
+
+/// Checks a JSON file a data source read whole, keeping its bytes as they
+/// are. Bytes the source holds alone are taken over without copying. Only
+/// serde_json checks files kept as they are, `simd` or not.
+pub(crate) fn parse_raw_json(bytes: Bytes) -> anyhow::Result<Box<RawValue>> {
+    let text = String::from_utf8(Vec::from(bytes)).map_err(|e| e.utf8_error())?;
+    Ok(RawValue::from_string(text)?)
+}
+
+/// Whether a data source error means the file doesn't exist, as opposed to
+/// the source failing.
+pub(crate) fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
+
 // Convenience functions to create bridges
+#[cfg(feature = "local")]
 pub async fn create_local_bridge(path: PathBuf) -> anyhow::Result<MCPBridge> {
     let data_source = Box::new(LocalDataSource::new(path));
     let mut bridge = MCPBridge::new(data_source);
@@ -522,6 +3087,7 @@ pub async fn create_local_bridge(path: PathBuf) -> anyhow::Result<MCPBridge> {
     Ok(bridge)
 }
 
+#[cfg(feature = "remote")]
 pub async fn create_remote_bridge(url: String) -> anyhow::Result<MCPBridge> {
     let data_source = Box::new(RemoteDataSource::new(url));
     let mut bridge = MCPBridge::new(data_source);
@@ -529,17 +3095,253 @@ pub async fn create_remote_bridge(url: String) -> anyhow::Result<MCPBridge> {
     Ok(bridge)
 }
 
-pub async fn create_bridge(source_path: String) -> anyhow::Result<MCPBridge> {
+/// Remote source for `http(s)://` URLs, local directory otherwise.
+#[cfg(all(feature = "local", feature = "remote"))]
+pub fn create_data_source(source_path: String) -> Box<dyn MCPDataSource> {
+    create_data_source_with_client(source_path, reqwest::Client::new())
+}
+
+/// [`create_data_source`], fetching remote sites with `client`.
+#[cfg(all(feature = "local", feature = "remote"))]
+pub fn create_data_source_with_client(
+    source_path: String,
+    client: reqwest::Client,
+) -> Box<dyn MCPDataSource> {
     if source_path.starts_with("http://") || source_path.starts_with("https://") {
-        create_remote_bridge(source_path).await
+        Box::new(RemoteDataSource::with_client(source_path, client))
     } else {
-        create_local_bridge(PathBuf::from(source_path)).await
+        Box::new(LocalDataSource::new(PathBuf::from(source_path)))
     }
 }
 
+#[cfg(all(feature = "local", feature = "remote"))]
+pub async fn create_bridge(source_path: String) -> anyhow::Result<MCPBridge> {
+    let mut bridge = MCPBridge::new(create_data_source(source_path));
+    bridge.initialize().await?;
+    Ok(bridge)
+}
+
+#[cfg(all(feature = "remote", feature = "sse"))]
 pub async fn create_sse_stream_for_url(
     url: String,
 ) -> anyhow::Result<impl Stream<Item = Result<Event, axum::Error>>> {
     let bridge = create_remote_bridge(url).await?;
-    Ok(MCPBridge::create_sse_stream(Arc::new(bridge)))
+    Ok(MCPBridge::create_sse_stream(
+        Arc::new(bridge),
+        MCPBridge::PING_INTERVAL,
+    ))
+}
+
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+pub async fn create_fetch_bridge(url: String) -> anyhow::Result<MCPBridge> {
+    let data_source = Box::new(FetchDataSource::new(url));
+    let mut bridge = MCPBridge::new(data_source);
+    bridge.initialize().await?;
+    Ok(bridge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    /// A site held in memory: JSON files by path, and raw ones as bytes.
+    struct Site {
+        manifest: Value,
+        json: HashMap<&'static str, Value>,
+        raw: HashMap<&'static str, &'static str>,
+    }
+
+    #[async_trait]
+    impl MCPDataSource for Site {
+        async fn load_json(&self, path: &str, _context: &RequestContext) -> anyhow::Result<Value> {
+            match self.json.get(path) {
+                Some(value) => Ok(value.clone()),
+                None => Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+            }
+        }
+
+        async fn load_manifest(&self) -> anyhow::Result<MCPManifest> {
+            manifest::parse(self.manifest.clone())
+        }
+
+        async fn load_bytes(&self, path: &str) -> anyhow::Result<Bytes> {
+            match self.raw.get(path) {
+                Some(text) => Ok(Bytes::from_static(text.as_bytes())),
+                None => Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+            }
+        }
+    }
+
+    fn bridge(site: Site, stream_threshold: Option<u64>) -> MCPBridge {
+        let mut bridge = MCPBridge::builder(Box::new(site))
+            .stream_threshold(stream_threshold)
+            .build();
+        block_on(bridge.initialize()).unwrap();
+        bridge
+    }
+
+    fn read(bridge: &MCPBridge, uri: &str) -> Value {
+        let request = MCPRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: "resources/read".to_string(),
+            params: Some(json!({ "uri": uri })),
+        };
+        let response = block_on(async { bridge.reply(request).await.into_response().await });
+        response.result.unwrap()
+    }
+
+    fn manifest(resources: Value) -> Value {
+        json!({
+            "protocolVersion": "2024-11-05",
+            "serverInfo": { "name": "test", "version": "1.0.0" },
+            "capabilities": { "resources": resources },
+        })
+    }
+
+    #[test]
+    fn reads_of_contents_files_carry_manifest_annotations() {
+        let site = Site {
+            manifest: manifest(json!([
+                {
+                    "uri": "file://guide",
+                    "name": "Guide",
+                    "title": "The Guide",
+                    "annotations": { "audience": ["user"], "priority": 0.5 },
+                },
+                { "uri": "file://plain", "name": "Plain" },
+            ])),
+            json: HashMap::from([
+                (
+                    "resources/guide.json",
+                    json!({ "contents": [{
+                        "uri": "file://guide",
+                        "text": "Read me",
+                        "annotations": { "priority": 1.0 },
+                    }] }),
+                ),
+                (
+                    "resources/plain.json",
+                    json!({ "contents": [{ "uri": "file://plain", "text": "As it is" }] }),
+                ),
+            ]),
+            raw: HashMap::new(),
+        };
+        let bridge = bridge(site, None);
+        assert_eq!(
+            read(&bridge, "file://guide"),
+            json!({ "contents": [{
+                "uri": "file://guide",
+                "text": "Read me",
+                "title": "The Guide",
+                "annotations": { "audience": ["user"], "priority": 1.0 },
+            }] })
+        );
+        assert_eq!(
+            read(&bridge, "file://plain"),
+            json!({ "contents": [{ "uri": "file://plain", "text": "As it is" }] })
+        );
+    }
+
+    #[cfg(feature = "local")]
+    #[test]
+    fn parses_large_local_files_as_values_and_as_they_are() {
+        let path =
+            std::env::temp_dir().join(format!("staticmcp-large-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"contents": [{"uri": "file://big", "text": "é"}]}"#,
+        )
+        .unwrap();
+        let value = LocalDataSource::parse_value_file(&path);
+        let raw: anyhow::Result<Box<RawValue>> = LocalDataSource::parse_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            value.unwrap(),
+            json!({ "contents": [{ "uri": "file://big", "text": "é" }] })
+        );
+        assert_eq!(
+            raw.unwrap().get(),
+            r#"{"contents": [{"uri": "file://big", "text": "é"}]}"#
+        );
+    }
+
+    #[test]
+    fn chunked_reads_carry_manifest_annotations() {
+        let site = Site {
+            manifest: manifest(json!([{
+                "uri": "file://notes.md",
+                "name": "Notes",
+                "title": "Release notes",
+                "annotations": { "audience": ["assistant"] },
+            }])),
+            json: HashMap::new(),
+            raw: HashMap::from([("resources/notes.md", "# Notes")]),
+        };
+        let bridge = bridge(site, Some(1));
+        assert_eq!(
+            read(&bridge, "file://notes.md"),
+            json!({ "contents": [{
+                "uri": "file://notes.md",
+                "mimeType": "text/markdown",
+                "text": "# Notes",
+                "title": "Release notes",
+                "annotations": { "audience": ["assistant"] },
+            }] })
+        );
+    }
+
+    /// A site answering `body` to every request after `delay`.
+    #[cfg(feature = "remote")]
+    async fn slow_site(body: &'static str, delay: std::time::Duration) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let _ = stream.read(&mut [0; 1024]).await;
+                    tokio::time::sleep(delay).await;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    #[cfg(feature = "remote")]
+    #[tokio::test]
+    async fn shared_fetches_keep_each_callers_deadline() {
+        let delay = std::time::Duration::from_millis(300);
+        let source = RemoteDataSource::new(slow_site(r#"{"a":1}"#, delay).await);
+        let within = |millis| RequestContext {
+            deadline: Some(Instant::now() + std::time::Duration::from_millis(millis)),
+            ..RequestContext::default()
+        };
+        let (hurried, patient) = (within(50), within(5_000));
+        let (hurried, patient) = tokio::join!(
+            source.load_json("tools/a.json", &hurried),
+            source.load_json("tools/a.json", &patient),
+        );
+        let error = hurried.unwrap_err();
+        assert!(
+            error
+                .chain()
+                .any(|cause| cause.is::<tokio::time::error::Elapsed>())
+        );
+        assert_eq!(patient.unwrap(), json!({ "a": 1 }));
+
+        let (hurried, patient) = (within(50), within(5_000));
+        let (hurried, patient) = tokio::join!(
+            source.load_raw_json("tools/a.json", &hurried),
+            source.load_raw_json("tools/a.json", &patient),
+        );
+        assert!(hurried.is_err());
+        assert_eq!(patient.unwrap().get(), r#"{"a":1}"#);
+    }
 }
@@ -0,0 +1,73 @@
+//! Translated resources: a site serving several languages keeps each
+//! resource's translation in `resources/{locale}/{path}`, next to the
+//! untranslated `resources/{path}` answering every other locale.
+//!
+//! Only the locales the bridge is configured with are looked up, so a
+//! requested locale never becomes part of a path by itself.
+
+/// The configured `locales` answering `requested`, best first: each
+/// requested tag in turn, matched exactly and then by its language alone,
+/// so `fr-CA` also reads `fr`. Matching ignores case.
+pub fn negotiate<'a>(locales: &'a [String], requested: &[String]) -> Vec<&'a str> {
+    let mut chosen: Vec<&str> = Vec::new();
+    for tag in requested {
+        let language = tag.split(['-', '_']).next().unwrap_or(tag);
+        for candidate in [tag.as_str(), language] {
+            if let Some(locale) = locales
+                .iter()
+                .find(|locale| locale.eq_ignore_ascii_case(candidate))
+                && !chosen.contains(&locale.as_str())
+            {
+                chosen.push(locale);
+            }
+        }
+    }
+    chosen
+}
+
+/// The tags of an `Accept-Language` header, highest weight first, leaving
+/// out `*` and those weighted `0`.
+pub fn accept_language(header: &str) -> Vec<String> {
+    let mut weighted: Vec<(f32, &str)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && weight > 0.0).then_some((weight, tag))
+        })
+        .collect();
+    // Stable, so equally weighted tags keep the client's order.
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    weighted
+        .into_iter()
+        .map(|(_, tag)| tag.to_string())
+        .collect()
+}
+
+/// Where the `locale` translation of the file `path` lives: in a
+/// `{locale}` directory under `resources/`, or at the site's root for
+/// resources mapped elsewhere.
+pub fn localized_path(path: &str, locale: &str) -> String {
+    match path.strip_prefix("resources/") {
+        Some(rest) => format!("resources/{locale}/{rest}"),
+        None => format!("{locale}/{path}"),
+    }
+}
+
+/// Checks a configured locale is a plain tag such as `fr` or `pt-BR`, safe
+/// to use as a directory name.
+pub fn parse(value: &str) -> Result<String, String> {
+    let valid = !value.is_empty()
+        && value.len() <= 35
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!("`{value}` is not a locale tag such as fr or pt-BR"))
+    }
+}
@@ -0,0 +1,872 @@
+//! Checks of `mcp.json` against the shape the bridge and MCP clients expect,
+//! reported precisely enough to fix: each problem names the JSON pointer it
+//! was found at, what was expected there and what was found, and misspelled
+//! keys such as `server_info` are matched to the key that was meant.
+
+use crate::rewrite::RewriteRule;
+use crate::{MCPManifest, format, path_template};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The manifest can't be served as written.
+    Error,
+    /// The manifest is served, but clients will likely misbehave.
+    Warning,
+}
+
+/// One problem found in a manifest.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// JSON pointer to the offending value, `""` for the whole document.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "mcp.json {pointer}: {}", self.message)
+    }
+}
+
+const ROOT_KEYS: &[&str] = &[
+    "staticmcpVersion",
+    "serverInfo",
+    "capabilities",
+    "protocolVersion",
+    "instructions",
+    "checksums",
+    "rewrites",
+];
+const SERVER_INFO_KEYS: &[&str] = &["name", "version", "title"];
+const CAPABILITY_KEYS: &[&str] = &[
+    "resources",
+    "resourceTemplates",
+    "tools",
+    "prompts",
+    "completions",
+];
+const RESOURCE_KEYS: &[&str] = &[
+    "uri",
+    "aliases",
+    "pathTemplate",
+    "variants",
+    "upstream",
+    "name",
+    "title",
+    "description",
+    "mimeType",
+    "size",
+    "annotations",
+];
+const RESOURCE_ANNOTATION_KEYS: &[&str] = &["audience", "priority", "lastModified"];
+const RESOURCE_TEMPLATE_KEYS: &[&str] = &[
+    "uriTemplate",
+    "pathTemplate",
+    "name",
+    "title",
+    "description",
+    "mimeType",
+    "annotations",
+];
+const TOOL_KEYS: &[&str] = &[
+    "name",
+    "aliases",
+    "pathTemplate",
+    "argumentHash",
+    "argumentOrder",
+    "handler",
+    "upstream",
+    "title",
+    "description",
+    "inputSchema",
+    "outputSchema",
+    "annotations",
+    "icons",
+    "_meta",
+];
+const TOOL_ANNOTATION_KEYS: &[&str] = &[
+    "title",
+    "readOnlyHint",
+    "destructiveHint",
+    "idempotentHint",
+    "openWorldHint",
+];
+const PROMPT_KEYS: &[&str] = &[
+    "name",
+    "pathTemplate",
+    "title",
+    "description",
+    "arguments",
+    "icons",
+    "_meta",
+];
+const REWRITE_KEYS: &[&str] = &["uri", "path", "to", "toPath"];
+const PROMPT_ARGUMENT_KEYS: &[&str] = &["name", "title", "description", "required"];
+const ARGUMENT_HASHES: &[&str] = &["base64", "sha256"];
+const ARGUMENT_ORDERS: &[&str] = &["value", "key"];
+
+/// Every problem found in `manifest`, errors and warnings in document order.
+pub fn check(manifest: &Value) -> Vec<Diagnostic> {
+    let mut checker = Checker::default();
+    checker.root(manifest);
+    checker.diagnostics
+}
+
+/// Whether any of `diagnostics` is an error.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+/// Reads `raw` as a manifest of the [`format`] version it declares, without
+/// what that version lacks.
+pub fn load(mut raw: Value) -> anyhow::Result<MCPManifest> {
+    let version = format::version(&raw).map_err(|message| anyhow::anyhow!(message))?;
+    format::strip(&mut raw, version);
+    Ok(serde_json::from_value(raw)?)
+}
+
+/// Parses `raw` as a manifest after checking it, logging its warnings and
+/// failing with every error when there are any.
+pub fn parse(raw: Value) -> anyhow::Result<MCPManifest> {
+    let diagnostics = check(&raw);
+    let mut errors = Vec::new();
+    for diagnostic in &diagnostics {
+        match diagnostic.severity {
+            Severity::Error => errors.push(diagnostic.to_string()),
+            Severity::Warning => warn!(pointer = %diagnostic.pointer, "{}", diagnostic.message),
+        }
+    }
+    if !errors.is_empty() {
+        anyhow::bail!("invalid manifest:\n  {}", errors.join("\n  "));
+    }
+    load(raw)
+}
+
+/// The JSON type of `value`, as named in messages.
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// The key in `known` that `key` was probably meant to be: the same once
+/// case, `_` and `-` are ignored, or within two edits of it.
+fn suggestion(key: &str, known: &[&'static str]) -> Option<&'static str> {
+    let normalize = |key: &str| key.replace(['_', '-'], "").to_lowercase();
+    let normalized = normalize(key);
+    known
+        .iter()
+        .find(|candidate| normalize(candidate) == normalized)
+        .or_else(|| {
+            known
+                .iter()
+                .find(|candidate| edit_distance(&normalize(candidate), &normalized) <= 2)
+        })
+        .copied()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Escapes a key for use in a JSON pointer.
+fn segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[derive(Default)]
+struct Checker {
+    diagnostics: Vec<Diagnostic>,
+    /// The manifest's [`format`] version.
+    version: u64,
+}
+
+impl Checker {
+    fn report(&mut self, severity: Severity, pointer: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            pointer: pointer.to_string(),
+            message,
+        });
+    }
+
+    fn error(&mut self, pointer: &str, message: String) {
+        self.report(Severity::Error, pointer, message);
+    }
+
+    fn warning(&mut self, pointer: &str, message: String) {
+        self.report(Severity::Warning, pointer, message);
+    }
+
+    /// `value` as an object, reporting anything else with `severity`.
+    fn object<'a>(
+        &mut self,
+        severity: Severity,
+        pointer: &str,
+        value: &'a Value,
+    ) -> Option<&'a Map<String, Value>> {
+        if let Value::Object(object) = value {
+            return Some(object);
+        }
+        let message = format!("expected an object, found {}", kind(value));
+        self.report(severity, pointer, message);
+        None
+    }
+
+    /// Warns about keys outside `known`, naming the likely intended one.
+    fn unknown_keys(&mut self, pointer: &str, object: &Map<String, Value>, known: &[&'static str]) {
+        for key in object.keys().filter(|key| !known.contains(&key.as_str())) {
+            let key_pointer = format!("{pointer}/{}", segment(key));
+            let message = match suggestion(key, known) {
+                Some(meant) if !object.contains_key(meant) => {
+                    format!("unknown key `{key}` is ignored; did you mean `{meant}`?")
+                }
+                _ => format!("unknown key `{key}` is ignored"),
+            };
+            self.warning(&key_pointer, message);
+        }
+    }
+
+    /// Checks that `key`, when present, holds a string, reporting it missing
+    /// with `missing`. Anything but a string is an error, as the bridge
+    /// can't load it.
+    fn string_field(
+        &mut self,
+        pointer: &str,
+        object: &Map<String, Value>,
+        key: &str,
+        missing: Option<Severity>,
+    ) -> Option<String> {
+        let field_pointer = format!("{pointer}/{key}");
+        match object.get(key) {
+            Some(Value::String(value)) => Some(value.clone()),
+            None | Some(Value::Null) => {
+                self.report(
+                    missing?,
+                    pointer,
+                    format!("missing required string `{key}`"),
+                );
+                None
+            }
+            Some(value) => {
+                let message = format!("expected `{key}` to be a string, found {}", kind(value));
+                self.error(&field_pointer, message);
+                None
+            }
+        }
+    }
+
+    /// Checks that `key`, when present, holds a boolean.
+    fn bool_field(&mut self, pointer: &str, object: &Map<String, Value>, key: &str) {
+        match object.get(key) {
+            None | Some(Value::Bool(_)) => {}
+            Some(value) => {
+                let message = format!("expected `{key}` to be a boolean, found {}", kind(value));
+                self.error(&format!("{pointer}/{key}"), message);
+            }
+        }
+    }
+
+    fn root(&mut self, manifest: &Value) {
+        let Some(root) = self.object(Severity::Error, "", manifest) else {
+            return;
+        };
+        // Nothing else is checked against a format the bridge can't read.
+        self.version = match format::version(manifest) {
+            Ok(version) => version,
+            Err(message) => {
+                self.error(&format!("/{}", format::FIELD), message);
+                return;
+            }
+        };
+        self.unknown_keys("", root, ROOT_KEYS);
+        for &(parent, key, introduced) in format::INTRODUCED {
+            if self.version < introduced
+                && manifest
+                    .pointer(parent)
+                    .is_some_and(|object| object.get(key).is_some())
+            {
+                self.warning(
+                    &format!("{parent}/{key}"),
+                    format!(
+                        "`{key}` needs `\"{}\": {introduced}` and is ignored",
+                        format::FIELD
+                    ),
+                );
+            }
+        }
+        if self.version >= 2
+            && let Some(checksums) = root.get("checksums")
+        {
+            self.checksums(checksums);
+        }
+        if self.version >= 2
+            && let Some(rewrites) = root.get("rewrites")
+        {
+            self.rewrites(rewrites);
+        }
+
+        match root.get("serverInfo") {
+            None | Some(Value::Null) => self.warning(
+                "",
+                "missing `serverInfo`; clients will see a placeholder name".to_string(),
+            ),
+            Some(info) => self.server_info(info),
+        }
+        match root.get("capabilities") {
+            None | Some(Value::Null) => self.warning(
+                "",
+                "missing `capabilities`; the site serves nothing".to_string(),
+            ),
+            Some(capabilities) => self.capabilities(capabilities),
+        }
+    }
+
+    fn server_info(&mut self, info: &Value) {
+        let pointer = "/serverInfo";
+        let Some(info) = self.object(Severity::Error, pointer, info) else {
+            return;
+        };
+        self.unknown_keys(pointer, info, SERVER_INFO_KEYS);
+        self.string_field(pointer, info, "name", Some(Severity::Error));
+        self.string_field(pointer, info, "version", Some(Severity::Error));
+    }
+
+    fn capabilities(&mut self, capabilities: &Value) {
+        let pointer = "/capabilities";
+        let Some(capabilities) = self.object(Severity::Error, pointer, capabilities) else {
+            return;
+        };
+        self.unknown_keys(pointer, capabilities, CAPABILITY_KEYS);
+        if let Some(resources) = self.list(capabilities, "resources") {
+            let mut uris = HashSet::new();
+            for (index, resource) in resources.iter().enumerate() {
+                for (uri_pointer, uri) in
+                    self.resource(&format!("{pointer}/resources/{index}"), resource)
+                {
+                    if !uris.insert(uri.clone()) {
+                        let message = format!("resource `{uri}` is declared more than once");
+                        self.error(&uri_pointer, message);
+                    }
+                }
+            }
+        }
+        if let Some(templates) = self.list(capabilities, "resourceTemplates") {
+            for (index, template) in templates.iter().enumerate() {
+                self.resource_template(&format!("{pointer}/resourceTemplates/{index}"), template);
+            }
+        }
+        if let Some(tools) = self.list(capabilities, "tools") {
+            let mut names = HashSet::new();
+            for (index, tool) in tools.iter().enumerate() {
+                for (name_pointer, name) in self.tool(&format!("{pointer}/tools/{index}"), tool) {
+                    if !names.insert(name.clone()) {
+                        let message = format!("tool `{name}` is declared more than once");
+                        self.error(&name_pointer, message);
+                    }
+                }
+            }
+        }
+        if self.version < 2 {
+            return;
+        }
+        if let Some(prompts) = self.list(capabilities, "prompts") {
+            let mut names = HashSet::new();
+            for (index, prompt) in prompts.iter().enumerate() {
+                let prompt_pointer = format!("{pointer}/prompts/{index}");
+                if let Some(name) = self.prompt(&prompt_pointer, prompt)
+                    && !names.insert(name.clone())
+                {
+                    let message = format!("prompt `{name}` is declared more than once");
+                    self.error(&format!("{prompt_pointer}/name"), message);
+                }
+            }
+        }
+        if let Some(completions) = capabilities.get("completions") {
+            self.object(
+                Severity::Error,
+                &format!("{pointer}/completions"),
+                completions,
+            );
+        }
+    }
+
+    /// Checks one prompt, returning its name.
+    fn prompt(&mut self, pointer: &str, prompt: &Value) -> Option<String> {
+        let prompt = self.object(Severity::Error, pointer, prompt)?;
+        self.unknown_keys(pointer, prompt, PROMPT_KEYS);
+        self.string_field(pointer, prompt, "description", None);
+        let mut arguments = Vec::new();
+        match prompt.get("arguments") {
+            None | Some(Value::Null) => {}
+            Some(Value::Array(entries)) => {
+                for (index, argument) in entries.iter().enumerate() {
+                    let argument_pointer = format!("{pointer}/arguments/{index}");
+                    let Some(argument) = self.object(Severity::Error, &argument_pointer, argument)
+                    else {
+                        continue;
+                    };
+                    self.unknown_keys(&argument_pointer, argument, PROMPT_ARGUMENT_KEYS);
+                    self.string_field(&argument_pointer, argument, "description", None);
+                    self.bool_field(&argument_pointer, argument, "required");
+                    arguments.extend(self.string_field(
+                        &argument_pointer,
+                        argument,
+                        "name",
+                        Some(Severity::Error),
+                    ));
+                }
+            }
+            Some(value) => {
+                let message = format!("expected an array of arguments, found {}", kind(value));
+                self.error(&format!("{pointer}/arguments"), message);
+            }
+        }
+        if let Some(path) = self.string_field(pointer, prompt, "pathTemplate", None) {
+            for name in path_template::variables(&path) {
+                if !arguments.iter().any(|argument| argument == name) {
+                    self.warning(
+                        &format!("{pointer}/pathTemplate"),
+                        format!("`{{{name}}}` is not one of the prompt's `arguments`; gets fail unless they pass it"),
+                    );
+                }
+            }
+        }
+        if let Some(icons) = prompt.get("icons") {
+            self.icons(&format!("{pointer}/icons"), icons);
+        }
+        self.string_field(pointer, prompt, "name", Some(Severity::Error))
+    }
+
+    /// Checks that `checksums` maps paths to `sha256:` and 64 hex digits.
+    fn checksums(&mut self, checksums: &Value) {
+        let pointer = "/checksums";
+        let Some(checksums) = self.object(Severity::Error, pointer, checksums) else {
+            return;
+        };
+        for (path, checksum) in checksums {
+            let valid = checksum
+                .as_str()
+                .and_then(|checksum| checksum.strip_prefix("sha256:"))
+                .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()));
+            if !valid {
+                self.error(
+                    &format!("{pointer}/{}", segment(path)),
+                    format!("expected `sha256:` and 64 hex digits, found {checksum}"),
+                );
+            }
+        }
+    }
+
+    /// Checks that each of `rewrites` is a rule the bridge can apply.
+    fn rewrites(&mut self, rewrites: &Value) {
+        let Value::Array(rules) = rewrites else {
+            let message = format!("expected an array, found {}", kind(rewrites));
+            self.error("/rewrites", message);
+            return;
+        };
+        for (index, rule) in rules.iter().enumerate() {
+            let pointer = format!("/rewrites/{index}");
+            let Some(object) = self.object(Severity::Error, &pointer, rule) else {
+                continue;
+            };
+            self.unknown_keys(&pointer, object, REWRITE_KEYS);
+            let mut strings = true;
+            for key in REWRITE_KEYS {
+                strings &= object.get(*key).is_none_or(Value::is_string);
+                self.string_field(&pointer, object, key, None);
+            }
+            if strings && let Err(e) = serde_json::from_value::<RewriteRule>(rule.clone()) {
+                self.error(&pointer, e.to_string());
+            }
+        }
+    }
+
+    /// The array under `key`, when present and an array.
+    fn list<'a>(&mut self, capabilities: &'a Map<String, Value>, key: &str) -> Option<&'a [Value]> {
+        match capabilities.get(key)? {
+            Value::Null => None,
+            Value::Array(entries) => Some(entries),
+            value => {
+                let message = format!("expected `{key}` to be an array, found {}", kind(value));
+                self.error(&format!("/capabilities/{key}"), message);
+                None
+            }
+        }
+    }
+
+    /// Checks one resource, returning its URI and aliases with their pointers.
+    fn resource(&mut self, pointer: &str, resource: &Value) -> Vec<(String, String)> {
+        let Some(resource) = self.object(Severity::Error, pointer, resource) else {
+            return Vec::new();
+        };
+        self.unknown_keys(pointer, resource, RESOURCE_KEYS);
+        self.string_field(pointer, resource, "name", Some(Severity::Warning));
+        self.string_field(pointer, resource, "mimeType", None);
+        self.string_field(pointer, resource, "description", None);
+        self.bool_field(pointer, resource, "upstream");
+        self.variants(pointer, resource);
+        self.resource_metadata(pointer, resource);
+        if let Some(path) = self.string_field(pointer, resource, "pathTemplate", None)
+            && path.contains('{')
+        {
+            self.error(
+                &format!("{pointer}/pathTemplate"),
+                format!("`{path}` has variables, but a resource has none to fill them; declare a `resourceTemplates` entry instead"),
+            );
+        }
+        let uri = self.string_field(pointer, resource, "uri", Some(Severity::Error));
+        let uri = uri.map(|uri| (format!("{pointer}/uri"), uri));
+        uri.into_iter()
+            .chain(self.aliases(pointer, resource))
+            .collect()
+    }
+
+    /// `title`, `size` and `annotations`, which clients are sent as written,
+    /// so mistakes in them are only warned about.
+    fn resource_metadata(&mut self, pointer: &str, resource: &Map<String, Value>) {
+        if let Some(title) = resource.get("title")
+            && !title.is_string()
+        {
+            self.warning(
+                &format!("{pointer}/title"),
+                format!("expected a string, found {}", kind(title)),
+            );
+        }
+        if let Some(size) = resource.get("size")
+            && !size.is_u64()
+        {
+            self.warning(
+                &format!("{pointer}/size"),
+                format!("expected a whole number of bytes, found {size}"),
+            );
+        }
+        let Some(annotations) = resource.get("annotations") else {
+            return;
+        };
+        let pointer = format!("{pointer}/annotations");
+        let Some(annotations) = self.object(Severity::Warning, &pointer, annotations) else {
+            return;
+        };
+        self.unknown_keys(&pointer, annotations, RESOURCE_ANNOTATION_KEYS);
+        for (key, value) in annotations {
+            let valid = match key.as_str() {
+                "audience" => value.as_array().is_some_and(|roles| {
+                    roles
+                        .iter()
+                        .all(|role| matches!(role.as_str(), Some("user" | "assistant")))
+                }),
+                "priority" => value.as_f64().is_some_and(|p| (0.0..=1.0).contains(&p)),
+                "lastModified" => value.is_string(),
+                _ => continue,
+            };
+            if !valid {
+                let expected = match key.as_str() {
+                    "audience" => "an array of `user` and `assistant`",
+                    "priority" => "a number from 0 to 1",
+                    _ => "an ISO 8601 timestamp",
+                };
+                self.warning(
+                    &format!("{pointer}/{}", segment(key)),
+                    format!("expected `{key}` to be {expected}; clients may ignore it"),
+                );
+            }
+        }
+    }
+
+    /// The strings under `aliases`, with their pointers.
+    fn aliases(&mut self, pointer: &str, object: &Map<String, Value>) -> Vec<(String, String)> {
+        let aliases_pointer = format!("{pointer}/aliases");
+        let aliases = match object.get("aliases") {
+            None | Some(Value::Null) => return Vec::new(),
+            Some(Value::Array(aliases)) => aliases,
+            Some(value) => {
+                let message = format!("expected an array of strings, found {}", kind(value));
+                self.error(&aliases_pointer, message);
+                return Vec::new();
+            }
+        };
+        let mut names = Vec::new();
+        for (index, alias) in aliases.iter().enumerate() {
+            let alias_pointer = format!("{aliases_pointer}/{index}");
+            match alias {
+                Value::String(alias) => names.push((alias_pointer, alias.clone())),
+                _ => self.error(
+                    &alias_pointer,
+                    format!("expected a string, found {}", kind(alias)),
+                ),
+            }
+        }
+        names
+    }
+
+    /// Checks `variants` holds MIME types the bridge knows a file extension
+    /// for.
+    fn variants(&mut self, pointer: &str, resource: &Map<String, Value>) {
+        let variants_pointer = format!("{pointer}/variants");
+        let variants = match resource.get("variants") {
+            None | Some(Value::Null) => return,
+            Some(Value::Array(variants)) => variants,
+            Some(value) => {
+                let message = format!("expected an array of MIME types, found {}", kind(value));
+                self.error(&variants_pointer, message);
+                return;
+            }
+        };
+        for (index, variant) in variants.iter().enumerate() {
+            let variant_pointer = format!("{variants_pointer}/{index}");
+            match variant {
+                Value::String(variant) if crate::mime::extension_for(variant).is_none() => self
+                    .warning(
+                        &variant_pointer,
+                        format!("`{variant}` has no known file extension, so it is never read"),
+                    ),
+                Value::String(_) => {}
+                _ => self.error(
+                    &variant_pointer,
+                    format!("expected a string, found {}", kind(variant)),
+                ),
+            }
+        }
+    }
+
+    fn resource_template(&mut self, pointer: &str, template: &Value) {
+        let Some(template) = self.object(Severity::Error, pointer, template) else {
+            return;
+        };
+        self.unknown_keys(pointer, template, RESOURCE_TEMPLATE_KEYS);
+        self.string_field(pointer, template, "name", Some(Severity::Warning));
+        self.string_field(pointer, template, "mimeType", None);
+        self.string_field(pointer, template, "description", None);
+        let uri_template =
+            self.string_field(pointer, template, "uriTemplate", Some(Severity::Error));
+        if let Some(uri_template) = &uri_template
+            && uri_template.matches('{').count() != uri_template.matches('}').count()
+        {
+            self.warning(
+                &format!("{pointer}/uriTemplate"),
+                format!("`{uri_template}` has unbalanced braces"),
+            );
+        }
+        let path = self.string_field(pointer, template, "pathTemplate", None);
+        if let (Some(path), Some(uri_template)) = (path, uri_template) {
+            let known: Vec<&str> = path_template::variables(&uri_template).collect();
+            for name in path_template::variables(&path).filter(|name| !known.contains(name)) {
+                self.error(
+                    &format!("{pointer}/pathTemplate"),
+                    format!("`{{{name}}}` is not a variable of `uriTemplate`"),
+                );
+            }
+        }
+    }
+
+    /// Checks one tool, returning its name and aliases with their pointers.
+    fn tool(&mut self, pointer: &str, tool: &Value) -> Vec<(String, String)> {
+        let Some(tool) = self.object(Severity::Error, pointer, tool) else {
+            return Vec::new();
+        };
+        self.unknown_keys(pointer, tool, TOOL_KEYS);
+        self.string_field(pointer, tool, "description", None);
+        match tool.get("inputSchema") {
+            None => self.warning(
+                pointer,
+                "missing `inputSchema`; clients can't tell which arguments to send".to_string(),
+            ),
+            Some(schema) => self.input_schema(&format!("{pointer}/inputSchema"), schema),
+        }
+        if let Some(path) = self.string_field(pointer, tool, "pathTemplate", None) {
+            let properties = tool
+                .get("inputSchema")
+                .and_then(|schema| schema.get("properties"));
+            for name in path_template::variables(&path) {
+                if properties
+                    .and_then(|properties| properties.get(name))
+                    .is_none()
+                {
+                    self.warning(
+                        &format!("{pointer}/pathTemplate"),
+                        format!("`{{{name}}}` is not a property of `inputSchema`; calls fail unless they pass it"),
+                    );
+                }
+            }
+        }
+        if let Some(hash) = self.string_field(pointer, tool, "argumentHash", None)
+            && !ARGUMENT_HASHES.contains(&hash.as_str())
+        {
+            self.error(
+                &format!("{pointer}/argumentHash"),
+                format!("expected `\"base64\"` or `\"sha256\"`, found {hash:?}"),
+            );
+        }
+        self.bool_field(pointer, tool, "upstream");
+        if let Some(annotations) = tool.get("annotations") {
+            self.tool_annotations(&format!("{pointer}/annotations"), annotations);
+        }
+        if let Some(icons) = tool.get("icons") {
+            self.icons(&format!("{pointer}/icons"), icons);
+        }
+        if let Some(handler) = self.string_field(pointer, tool, "handler", None) {
+            if handler.ends_with(".rhai") {
+                if !cfg!(feature = "rhai") {
+                    self.warning(
+                        &format!("{pointer}/handler"),
+                        "this bridge was built without the `rhai` feature; calls of this tool fail"
+                            .to_string(),
+                    );
+                }
+            } else if handler.ends_with(".wasm") {
+                if !cfg!(feature = "wasm") {
+                    self.warning(
+                        &format!("{pointer}/handler"),
+                        "this bridge was built without the `wasm` feature; calls of this tool fail"
+                            .to_string(),
+                    );
+                }
+            } else {
+                self.error(
+                    &format!("{pointer}/handler"),
+                    format!("expected a `.rhai` script or a `.wasm` module, found {handler:?}"),
+                );
+            }
+        }
+        if let Some(order) = self.string_field(pointer, tool, "argumentOrder", None)
+            && !ARGUMENT_ORDERS.contains(&order.as_str())
+        {
+            self.error(
+                &format!("{pointer}/argumentOrder"),
+                format!("expected `\"value\"` or `\"key\"`, found {order:?}"),
+            );
+        }
+        let name = self.string_field(pointer, tool, "name", Some(Severity::Error));
+        let name = name.map(|name| (format!("{pointer}/name"), name));
+        name.into_iter()
+            .chain(self.aliases(pointer, tool))
+            .collect()
+    }
+
+    /// Annotations are listed to clients as written, so mistakes in them
+    /// are only warned about.
+    fn tool_annotations(&mut self, pointer: &str, annotations: &Value) {
+        let Some(annotations) = self.object(Severity::Warning, pointer, annotations) else {
+            return;
+        };
+        self.unknown_keys(pointer, annotations, TOOL_ANNOTATION_KEYS);
+        for (key, value) in annotations {
+            let expected = match key.as_str() {
+                "title" if !value.is_string() => "a string",
+                "readOnlyHint" | "destructiveHint" | "idempotentHint" | "openWorldHint"
+                    if !value.is_boolean() =>
+                {
+                    "a boolean"
+                }
+                _ => continue,
+            };
+            self.warning(
+                &format!("{pointer}/{}", segment(key)),
+                format!(
+                    "expected `{key}` to be {expected}, found {}; clients may ignore it",
+                    kind(value)
+                ),
+            );
+        }
+    }
+
+    fn icons(&mut self, pointer: &str, icons: &Value) {
+        let Some(icons) = icons.as_array() else {
+            self.warning(
+                pointer,
+                format!("expected an array of icons, found {}", kind(icons)),
+            );
+            return;
+        };
+        for (index, icon) in icons.iter().enumerate() {
+            let icon_pointer = format!("{pointer}/{index}");
+            let Some(icon) = self.object(Severity::Warning, &icon_pointer, icon) else {
+                continue;
+            };
+            if !icon.get("src").is_some_and(Value::is_string) {
+                self.warning(
+                    &icon_pointer,
+                    "missing string `src`, the icon's URL".to_string(),
+                );
+            }
+        }
+    }
+
+    fn input_schema(&mut self, pointer: &str, schema: &Value) {
+        let Some(schema) = self.object(Severity::Warning, pointer, schema) else {
+            return;
+        };
+        match schema.get("type") {
+            Some(Value::String(kind)) if kind == "object" => {}
+            Some(value) => self.warning(
+                &format!("{pointer}/type"),
+                format!("expected `\"object\"`, found {value}"),
+            ),
+            None => self.warning(pointer, "missing `\"type\": \"object\"`".to_string()),
+        }
+        let properties = match schema.get("properties") {
+            None => None,
+            Some(properties) => self.object(
+                Severity::Warning,
+                &format!("{pointer}/properties"),
+                properties,
+            ),
+        };
+        let Some(required) = schema.get("required") else {
+            return;
+        };
+        let Some(required) = required.as_array() else {
+            self.warning(
+                &format!("{pointer}/required"),
+                format!("expected an array of strings, found {}", kind(required)),
+            );
+            return;
+        };
+        for (index, name) in required.iter().enumerate() {
+            let name_pointer = format!("{pointer}/required/{index}");
+            match name.as_str() {
+                None => self.warning(
+                    &name_pointer,
+                    format!("expected a property name, found {}", kind(name)),
+                ),
+                Some(name)
+                    if !properties.is_some_and(|properties| properties.contains_key(name)) =>
+                {
+                    self.warning(
+                        &name_pointer,
+                        format!("required property `{name}` is not in `properties`"),
+                    )
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
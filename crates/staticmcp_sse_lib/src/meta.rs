@@ -0,0 +1,209 @@
+//! The `__list_available_paths` and `__describe_tool` tools, which let an
+//! agent see which files a site actually has before calling its tools,
+//! from a data source that can list them.
+//!
+//! Only files belonging to declared entries the caller may use are shown,
+//! so the listing can't reveal what reads and calls can't reach either.
+
+use crate::resolver::decode_segment;
+use crate::{MCPBridge, ToolDef, path_template};
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap};
+
+pub const LIST_PATHS: &str = "__list_available_paths";
+pub const DESCRIBE_TOOL: &str = "__describe_tool";
+
+/// Most paths or calls one answer lists.
+const MAX_ENTRIES: usize = 1000;
+
+/// The manifest entries `tools/list` shows for the meta tools.
+pub(crate) fn tools() -> [Value; 2] {
+    [
+        json!({
+            "name": LIST_PATHS,
+            "description": "Lists the files this server has answers for, such as tools/weather/paris.json, so you can see which arguments have pre-generated results",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "prefix": {
+                        "type": "string",
+                        "description": "Only list paths starting with this, such as tools/weather/"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": DESCRIBE_TOOL,
+            "description": "Describes one tool: its declaration, and the argument combinations that have pre-generated results",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "The tool to describe"
+                    }
+                },
+                "required": ["name"]
+            }
+        }),
+    ]
+}
+
+/// The result of a call of the meta tool `name`, or the message of an
+/// `isError` result.
+pub(crate) async fn call(
+    bridge: &MCPBridge,
+    name: &str,
+    args: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let paths = available_paths(bridge).await?;
+    let answer = if name == LIST_PATHS {
+        let prefix = match args.get("prefix") {
+            None => "",
+            Some(prefix) => prefix.as_str().ok_or("`prefix` must be a string")?,
+        };
+        let paths: Vec<&String> = paths
+            .iter()
+            .filter(|path| path.starts_with(prefix))
+            .collect();
+        json!({
+            "paths": paths.iter().take(MAX_ENTRIES).collect::<Vec<_>>(),
+            "truncated": paths.len() > MAX_ENTRIES,
+        })
+    } else {
+        let tool_name = args
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or("`name` must be a string")?;
+        let tool = bridge
+            .find_tool(tool_name)
+            .filter(|tool| bridge.permits_tool(&tool.name))
+            .ok_or_else(|| format!("unknown tool `{tool_name}`"))?;
+        let calls: Vec<Value> = paths
+            .iter()
+            .filter(|path| owns(tool, path))
+            .filter_map(|path| call_for(bridge, tool, path))
+            .collect();
+        json!({
+            "tool": tool,
+            "calls": calls.iter().take(MAX_ENTRIES).collect::<Vec<_>>(),
+            "truncated": calls.len() > MAX_ENTRIES,
+        })
+    };
+    let text = serde_json::to_string_pretty(&answer).unwrap_or_default();
+    Ok(json!({ "content": [{ "type": "text", "text": text }] }))
+}
+
+/// The site's files answering a declared resource or tool the caller may
+/// use, precompressed suffixes dropped, sorted.
+async fn available_paths(bridge: &MCPBridge) -> Result<Vec<String>, String> {
+    let files = bridge
+        .data_source
+        .list("")
+        .await
+        .map_err(|e| format!("this site's files can't be listed: {e}"))?;
+    let resources: BTreeSet<String> = bridge
+        .resources()
+        .iter()
+        .filter(|resource| !resource.upstream && bridge.permits_resource(&resource.uri))
+        .map(|resource| bridge.uri_to_path(&resource.uri))
+        .collect();
+    let tools: Vec<&ToolDef> = bridge
+        .tools()
+        .iter()
+        .filter(|tool| !tool.upstream && tool.handler.is_none() && bridge.permits_tool(&tool.name))
+        .collect();
+    let paths: BTreeSet<String> = files
+        .into_iter()
+        .map(|file| {
+            match file
+                .strip_suffix(".gz")
+                .or_else(|| file.strip_suffix(".br"))
+            {
+                Some(path) => path.to_string(),
+                None => file,
+            }
+        })
+        .filter(|path| resources.contains(path) || tools.iter().any(|tool| owns(tool, path)))
+        .collect();
+    Ok(paths.into_iter().collect())
+}
+
+/// Whether `path` answers some call of `tool`.
+fn owns(tool: &ToolDef, path: &str) -> bool {
+    match &tool.path_template {
+        Some(template) => path_template::captures(template, path).is_some(),
+        None => {
+            let dir = format!("tools/{}", tool.name);
+            path == format!("{dir}.json") || path.starts_with(&format!("{dir}/"))
+        }
+    }
+}
+
+/// The call `path` answers, as far as it can be read back: `arguments` when
+/// each value's argument is known, `values` when only the values are, and
+/// just the `path` for hashed names. Fallback files answer no call of their
+/// own and are left out.
+fn call_for(bridge: &MCPBridge, tool: &ToolDef, path: &str) -> Option<Value> {
+    if let Some(template) = &tool.path_template {
+        let arguments: HashMap<String, String> = path_template::captures(template, path)?
+            .into_iter()
+            .map(|(name, value)| {
+                let value = decode_segment(&value).unwrap_or(value);
+                (name, value)
+            })
+            .collect();
+        return Some(json!({ "path": path, "arguments": arguments }));
+    }
+
+    let dir = format!("tools/{}/", tool.name);
+    let Some(rest) = path.strip_prefix(&dir) else {
+        return Some(json!({ "path": path, "arguments": {} }));
+    };
+    let segments: Vec<String> = rest
+        .strip_suffix(".json")?
+        .split('/')
+        .map(|segment| decode_segment(segment).unwrap_or_else(|| segment.to_string()))
+        .collect();
+    let fallback = (bridge.tool_index_fallback && rest == "index.json")
+        || bridge
+            .tool_default_file
+            .as_ref()
+            .is_some_and(|file| rest == format!("{file}.json"));
+    if fallback {
+        return None;
+    }
+
+    let properties: Vec<&String> = tool
+        .input_schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().collect())
+        .unwrap_or_default();
+    // An assignment of the values to the tool's arguments is reported only
+    // when it is the one call mapped to this path.
+    let mut assignments = Vec::new();
+    if segments.len() == properties.len() && segments.len() <= 2 {
+        let orders: &[[usize; 2]] = if segments.len() == 2 {
+            &[[0, 1], [1, 0]]
+        } else {
+            &[[0, 0]]
+        };
+        for order in orders {
+            let args: HashMap<String, Value> = properties
+                .iter()
+                .zip(order)
+                .map(|(property, &index)| (property.to_string(), json!(segments[index])))
+                .collect();
+            if bridge.tool_to_path(&tool.name, &args).ok().as_deref() == Some(path) {
+                assignments.push(args);
+            }
+        }
+    }
+    Some(match (assignments.len(), segments.len()) {
+        (1, _) => json!({ "path": path, "arguments": assignments[0] }),
+        (_, 1 | 2) => json!({ "path": path, "values": segments }),
+        _ => json!({ "path": path }),
+    })
+}
@@ -0,0 +1,82 @@
+//! Measurements of the bridge's work, reported to a [`BridgeMetrics`] of the
+//! embedder's choosing so they reach whatever telemetry stack it already
+//! runs: requests as they start and finish, lookups in the `--cache-ttl`
+//! cache, and fetches from remote sites.
+//!
+//! Every method does nothing by default, so a recorder implements only
+//! what it tracks. With the `metrics` feature, [`MetricsRecorder`] reports
+//! to the [`metrics`](https://docs.rs/metrics) crate's global recorder.
+
+use std::time::Duration;
+
+/// Receives the bridge's measurements. Calls are made inline on the
+/// request's task, so implementations should only record.
+pub trait BridgeMetrics: Send + Sync {
+    /// A request for `method` is starting.
+    fn request_started(&self, _method: &str) {}
+
+    /// The request for `method` was answered after `elapsed`, with the
+    /// JSON-RPC code of its error when it failed.
+    fn request_finished(&self, _method: &str, _elapsed: Duration, _error: Option<i32>) {}
+
+    /// The file `path` was answered from the cache, fresh, stale or known
+    /// to be missing.
+    fn cache_hit(&self, _path: &str) {}
+
+    /// The file `path` wasn't cached and is read from the site.
+    fn cache_miss(&self, _path: &str) {}
+
+    /// A fetch of `url` from a remote site took `elapsed`, answering with
+    /// `status` or failing without one.
+    fn upstream_fetch(&self, _url: &str, _status: Option<u16>, _elapsed: Duration) {}
+}
+
+/// Reports to the `metrics` crate's recorder:
+///
+/// - `staticmcp_requests_in_flight`, a gauge;
+/// - `staticmcp_requests_total` by `method` and `error` code, `none` for
+///   successes, and `staticmcp_request_duration_seconds` by `method`;
+/// - `staticmcp_cache_hits_total` and `staticmcp_cache_misses_total`;
+/// - `staticmcp_upstream_fetches_total` by `status`, `error` for failed
+///   fetches, and `staticmcp_upstream_fetch_duration_seconds`.
+///
+/// Paths and URLs are left out of the labels, so they can't grow without
+/// bound, and methods the bridge doesn't know are counted as `other`.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsRecorder;
+
+#[cfg(feature = "metrics")]
+impl BridgeMetrics for MetricsRecorder {
+    fn request_started(&self, _method: &str) {
+        metrics::gauge!("staticmcp_requests_in_flight").increment(1.0);
+    }
+
+    fn request_finished(&self, method: &str, elapsed: Duration, error: Option<i32>) {
+        metrics::gauge!("staticmcp_requests_in_flight").decrement(1.0);
+        let method = match error {
+            Some(-32601) => "other".to_string(),
+            _ => method.to_string(),
+        };
+        let error = error.map_or("none".to_string(), |code| code.to_string());
+        metrics::counter!("staticmcp_requests_total", "method" => method.clone(), "error" => error)
+            .increment(1);
+        metrics::histogram!("staticmcp_request_duration_seconds", "method" => method)
+            .record(elapsed.as_secs_f64());
+    }
+
+    fn cache_hit(&self, _path: &str) {
+        metrics::counter!("staticmcp_cache_hits_total").increment(1);
+    }
+
+    fn cache_miss(&self, _path: &str) {
+        metrics::counter!("staticmcp_cache_misses_total").increment(1);
+    }
+
+    fn upstream_fetch(&self, _url: &str, status: Option<u16>, elapsed: Duration) {
+        let status = status.map_or("error".to_string(), |status| status.to_string());
+        metrics::counter!("staticmcp_upstream_fetches_total", "status" => status).increment(1);
+        metrics::histogram!("staticmcp_upstream_fetch_duration_seconds")
+            .record(elapsed.as_secs_f64());
+    }
+}
@@ -0,0 +1,130 @@
+//! MIME types of resource files that aren't JSON, such as Markdown pages
+//! or images, taken from the manifest's `mimeType` or the file's extension,
+//! and the choice between a resource's `variants` a reader accepts.
+
+/// Extensions the bridge recognizes, lowercase, and their MIME types.
+const TYPES: &[(&str, &str)] = &[
+    ("md", "text/markdown"),
+    ("markdown", "text/markdown"),
+    ("txt", "text/plain"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("ico", "image/x-icon"),
+    ("pdf", "application/pdf"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+    ("m4a", "audio/mp4"),
+    ("flac", "audio/flac"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("zip", "application/zip"),
+    ("wasm", "application/wasm"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+];
+
+/// The MIME type of files ending in `.{extension}`, if the bridge knows it.
+pub fn from_extension(extension: &str) -> Option<&'static str> {
+    TYPES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+        .map(|(_, mime)| *mime)
+}
+
+/// The extension of the last segment of `path`, if it has one.
+pub fn extension(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next()?;
+    let (stem, extension) = name.rsplit_once('.')?;
+    (!stem.is_empty() && !extension.is_empty()).then_some(extension)
+}
+
+/// Whether content of type `mime` is text, which MCP carries as `text`
+/// rather than a base64 `blob`.
+pub fn is_text(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or(mime).trim();
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence,
+            "application/json" | "application/xml" | "application/javascript" | "application/yaml"
+        )
+}
+
+/// The extension files of type `mime` are published with, such as `md`
+/// for `text/markdown`.
+pub fn extension_for(mime: &str) -> Option<&'static str> {
+    let essence = mime.split(';').next().unwrap_or(mime).trim();
+    if essence.eq_ignore_ascii_case("application/json") {
+        return Some("json");
+    }
+    TYPES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(essence))
+        .map(|(extension, _)| *extension)
+}
+
+/// `variants`, MIME types in the manifest's order of preference, ranked by
+/// an `accept` hint such as `text/html, text/*;q=0.5`: best weighted first,
+/// the manifest's order breaking ties, leaving out those it weights `0` or
+/// doesn't name. Without a hint, `variants` as they are.
+pub fn negotiate<'a>(variants: &'a [String], accept: Option<&str>) -> Vec<&'a str> {
+    let Some(accept) = accept else {
+        return variants.iter().map(String::as_str).collect();
+    };
+    let ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let range = parts.next()?.trim();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!range.is_empty()).then_some((range, weight))
+        })
+        .collect();
+    let mut weighted: Vec<(f32, &str)> = variants
+        .iter()
+        .filter_map(|variant| {
+            let weight = weight(&ranges, variant)?;
+            (weight > 0.0).then_some((weight, variant.as_str()))
+        })
+        .collect();
+    // Stable, so equally weighted variants keep the manifest's order.
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    weighted.into_iter().map(|(_, variant)| variant).collect()
+}
+
+/// The weight `ranges` give `mime`: that of the most specific range
+/// matching it, `type/subtype` before `type/*` before `*/*`.
+fn weight(ranges: &[(&str, f32)], mime: &str) -> Option<f32> {
+    let essence = mime.split(';').next().unwrap_or(mime).trim();
+    let main = essence.split('/').next().unwrap_or(essence);
+    ranges
+        .iter()
+        .filter_map(|&(range, weight)| {
+            let specificity = if range.eq_ignore_ascii_case(essence) {
+                2
+            } else if range
+                .strip_suffix("/*")
+                .is_some_and(|range_main| range_main.eq_ignore_ascii_case(main))
+            {
+                1
+            } else if range == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, weight))
+        })
+        .max_by_key(|&(specificity, _)| specificity)
+        .map(|(_, weight)| weight)
+}
@@ -0,0 +1,20 @@
+//! The normalized form of tool names and string arguments, so an agent's
+//! `Search` or ` search` is served like `search`.
+
+use std::borrow::Cow;
+
+/// `text` trimmed, NFC-normalized and lowercased.
+pub fn normalize(text: &str) -> String {
+    nfc(text.trim()).to_lowercase()
+}
+
+#[cfg(feature = "normalize")]
+fn nfc(text: &str) -> Cow<'_, str> {
+    icu_normalizer::ComposingNormalizerBorrowed::new_nfc().normalize(text)
+}
+
+/// Without the `normalize` feature text is only trimmed and lowercased.
+#[cfg(not(feature = "normalize"))]
+fn nfc(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
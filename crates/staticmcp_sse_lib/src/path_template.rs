@@ -0,0 +1,62 @@
+//! The `{name}` templates of manifest `uriTemplate`s and `pathTemplate`s:
+//! matching a URI against one to bind its variables, and expanding one with
+//! bound values into a path.
+
+use std::collections::HashMap;
+
+/// The variables binding `uri` to `template`, or `None` when no expansion
+/// of the template produces it. A `{var}` stands for one path segment or
+/// part of one, `{+var}` for any text including `/`.
+pub(crate) fn captures(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let Some(start) = template.find('{') else {
+        return (template == uri).then(HashMap::new);
+    };
+    let uri = uri.strip_prefix(&template[..start])?;
+    let Some(length) = template[start..].find('}') else {
+        return (template[start..] == *uri).then(HashMap::new);
+    };
+    let expression = &template[start + 1..start + length];
+    let (reserved, name) = match expression.strip_prefix('+') {
+        Some(name) => (true, name),
+        None => (false, expression),
+    };
+    let rest = &template[start + length + 1..];
+    (1..=uri.len())
+        .filter(|&end| uri.is_char_boundary(end))
+        .take_while(|&end| reserved || !uri[..end].contains('/'))
+        .find_map(|end| {
+            let mut captures = captures(rest, &uri[end..])?;
+            captures.insert(name.to_string(), uri[..end].to_string());
+            Some(captures)
+        })
+}
+
+/// The names of the variables in `template`, `+` markers dropped.
+pub(crate) fn variables(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}'))
+        .map(|(expression, _)| expression.trim_start_matches('+'))
+}
+
+/// `template` with each `{var}` replaced by `value(var)`, failing with the
+/// name of the first variable it has no value for.
+pub(crate) fn expand(
+    template: &str,
+    mut value: impl FnMut(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let name = rest[start + 1..start + length].trim_start_matches('+');
+        expanded.push_str(&value(name).ok_or_else(|| name.to_string())?);
+        rest = &rest[start + length + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
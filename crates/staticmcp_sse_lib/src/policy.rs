@@ -0,0 +1,104 @@
+//! Access control lists restricting which principals may call which tools
+//! and read which resources.
+//!
+//! A policy is a JSON document:
+//!
+//! ```json
+//! {
+//!   "default": "allow",
+//!   "rules": [
+//!     { "tools": ["admin_*"], "resources": ["file://internal/"], "allow": ["alice", "scope:mcp:admin"] }
+//!   ]
+//! }
+//! ```
+//!
+//! A tool or resource is governed by every rule naming it, tools by exact name
+//! or a trailing-`*` pattern and resources by URI prefix. Governed items are
+//! open only to callers matching an `allow` entry of one of those rules: `*`
+//! for any authenticated caller, `scope:NAME` for tokens carrying that scope,
+//! or a subject. Items no rule names fall back to `default`.
+
+use serde::Deserialize;
+use std::path::Path;
+
+/// Identity established for the current request by the server's
+/// authentication layer.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// The token's `sub`, or its `client_id` for client-credential tokens.
+    pub subject: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    #[default]
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Rule {
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub resources: Vec<String>,
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AccessPolicy {
+    #[serde(default)]
+    pub default: Decision,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+impl AccessPolicy {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn may_call_tool(&self, principal: Option<&Principal>, tool: &str) -> bool {
+        self.decide(principal, |rule| {
+            rule.tools
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => tool.starts_with(prefix),
+                    None => pattern == tool,
+                })
+        })
+    }
+
+    pub fn may_read_resource(&self, principal: Option<&Principal>, uri: &str) -> bool {
+        self.decide(principal, |rule| {
+            rule.resources
+                .iter()
+                .any(|prefix| uri.starts_with(prefix.as_str()))
+        })
+    }
+
+    fn decide(&self, principal: Option<&Principal>, governs: impl Fn(&Rule) -> bool) -> bool {
+        let mut governing = self.rules.iter().filter(|rule| governs(rule)).peekable();
+        if governing.peek().is_none() {
+            return self.default == Decision::Allow;
+        }
+        let Some(principal) = principal else {
+            return false;
+        };
+        governing.any(|rule| rule.allow.iter().any(|entry| matches(entry, principal)))
+    }
+}
+
+fn matches(entry: &str, principal: &Principal) -> bool {
+    if entry == "*" {
+        return true;
+    }
+    match entry.strip_prefix("scope:") {
+        Some(scope) => principal.scopes.iter().any(|s| s == scope),
+        None => entry == principal.subject,
+    }
+}
@@ -0,0 +1,40 @@
+//! Fallback to precompressed copies of StaticMCP files.
+//!
+//! Large sites can ship `foo.json.gz` or `foo.json.br` in place of
+//! `foo.json`; the data sources try these, in order, when the plain file is
+//! missing and decompress them before parsing.
+
+use std::io::Read;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Brotli,
+}
+
+impl Encoding {
+    /// Encodings tried after the plain file, in order.
+    pub const FALLBACKS: [Encoding; 2] = [Encoding::Gzip, Encoding::Brotli];
+
+    /// Suffix appended to the plain file name, e.g. `.gz`.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Encoding::Gzip => ".gz",
+            Encoding::Brotli => ".br",
+        }
+    }
+
+    /// Inflates the raw contents of a file stored with this encoding.
+    pub fn decode(self, compressed: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self {
+            Encoding::Gzip => {
+                flate2::read::GzDecoder::new(compressed).read_to_end(&mut out)?;
+            }
+            Encoding::Brotli => {
+                brotli::Decompressor::new(compressed, 4096).read_to_end(&mut out)?;
+            }
+        }
+        Ok(out)
+    }
+}
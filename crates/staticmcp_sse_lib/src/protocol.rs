@@ -0,0 +1,187 @@
+//! MCP protocol versions: the one agreed at `initialize`, and results built
+//! for the latest version reshaped for clients on an earlier one.
+//!
+//! - `2025-06-18` added `structuredContent` and tools' `outputSchema`,
+//!   `title` on tools, prompts and resources, `resource_link` content, and
+//!   `lastModified` in annotations.
+//! - `2025-03-26` added tool `annotations` and `audio` content.
+//!
+//! Clients on an earlier version are sent results without what their
+//! version lacks, in the shape it knew instead where there was one; those
+//! on the latest get `structuredContent` for tools declaring an
+//! `outputSchema` whose files only hold the JSON as text.
+
+use serde_json::{Value, json};
+
+/// The version the bridge's results are built for.
+pub const LATEST: &str = "2025-06-18";
+
+/// The versions the bridge speaks, latest first.
+pub const SUPPORTED: [&str; 3] = [LATEST, "2025-03-26", "2024-11-05"];
+
+/// `version`, when the bridge speaks it.
+pub fn supported(version: &str) -> Option<&'static str> {
+    SUPPORTED
+        .into_iter()
+        .find(|supported| *supported == version)
+}
+
+/// The version to answer `initialize` with: the client's when the bridge
+/// speaks it, otherwise the latest, for the client to decide whether it
+/// can go on.
+pub fn negotiate(requested: Option<&str>) -> &'static str {
+    requested.and_then(supported).unwrap_or(LATEST)
+}
+
+/// Whether the answer to `method` for a client on `version` differs from
+/// the one built for [`LATEST`]. `structured` says the called tool declares
+/// an `outputSchema`.
+pub fn reshapes(version: &str, method: &str, structured: bool) -> bool {
+    match method {
+        "tools/list"
+        | "prompts/list"
+        | "resources/list"
+        | "resources/templates/list"
+        | "resources/read" => version < LATEST,
+        "tools/call" => version < LATEST || structured,
+        _ => false,
+    }
+}
+
+/// Reshapes `result`, the answer to `method` built for [`LATEST`], for a
+/// client on `version`, as [`reshapes`] says it must be.
+pub fn adapt(version: &str, method: &str, result: &mut Value, structured: bool) {
+    match method {
+        "tools/list" => {
+            for tool in entries(result, "tools") {
+                if version < "2025-06-18" {
+                    tool.remove("outputSchema");
+                    tool.remove("title");
+                }
+                if version < "2025-03-26" {
+                    tool.remove("annotations");
+                }
+            }
+        }
+        "resources/list" | "resources/templates/list" => {
+            let field = match method {
+                "resources/list" => "resources",
+                _ => "resourceTemplates",
+            };
+            for resource in entries(result, field) {
+                if version < "2025-06-18" {
+                    resource.remove("title");
+                    drop_last_modified(resource);
+                }
+            }
+        }
+        "prompts/list" => {
+            for prompt in entries(result, "prompts") {
+                if version < "2025-06-18" {
+                    prompt.remove("title");
+                }
+            }
+        }
+        "resources/read" => {
+            for entry in entries(result, "contents") {
+                if version < "2025-06-18" {
+                    entry.remove("title");
+                    drop_last_modified(entry);
+                }
+            }
+        }
+        "tools/call" => adapt_tool_result(version, result, structured),
+        _ => {}
+    }
+}
+
+/// The objects in the array `result[field]`.
+fn entries<'a>(
+    result: &'a mut Value,
+    field: &str,
+) -> impl Iterator<Item = &'a mut serde_json::Map<String, Value>> {
+    result
+        .get_mut(field)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+fn adapt_tool_result(version: &str, result: &mut Value, structured: bool) {
+    let Some(result) = result.as_object_mut() else {
+        return;
+    };
+    if version >= "2025-06-18" {
+        // Files written for earlier clients hold the JSON as text only.
+        if structured
+            && !result.contains_key("structuredContent")
+            && let Some(Value::Array(content)) = result.get("content")
+            && let [item] = content.as_slice()
+            && item["type"] == "text"
+            && let Some(Ok(Value::Object(structured))) =
+                item["text"].as_str().map(serde_json::from_str::<Value>)
+        {
+            result.insert("structuredContent".into(), Value::Object(structured));
+        }
+        return;
+    }
+
+    // Earlier clients read the structured result from its text instead.
+    if let Some(structured) = result.remove("structuredContent") {
+        let empty = result
+            .get("content")
+            .and_then(Value::as_array)
+            .is_none_or(Vec::is_empty);
+        if empty {
+            result.insert(
+                "content".into(),
+                json!([{ "type": "text", "text": structured.to_string() }]),
+            );
+        }
+    }
+    let content = result
+        .get_mut("content")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for item in content {
+        adapt_content(version, item);
+    }
+}
+
+/// One item of a tool result's `content`, in a type `version` knows.
+fn adapt_content(version: &str, item: &mut serde_json::Map<String, Value>) {
+    drop_last_modified(item);
+    let text = match item.get("type").and_then(Value::as_str) {
+        Some("resource_link") => {
+            let uri = item.get("uri").and_then(Value::as_str).unwrap_or_default();
+            match item.get("name").and_then(Value::as_str) {
+                Some(name) if name != uri => format!("{name}: {uri}"),
+                _ => uri.to_string(),
+            }
+        }
+        Some("audio") if version < "2025-03-26" => {
+            let mime_type = item
+                .get("mimeType")
+                .and_then(Value::as_str)
+                .unwrap_or("audio");
+            format!("[{mime_type} content omitted]")
+        }
+        _ => return,
+    };
+    let annotations = item.remove("annotations");
+    item.clear();
+    item.insert("type".into(), json!("text"));
+    item.insert("text".into(), json!(text));
+    if let Some(annotations) = annotations {
+        item.insert("annotations".into(), annotations);
+    }
+}
+
+fn drop_last_modified(entry: &mut serde_json::Map<String, Value>) {
+    if let Some(Value::Object(annotations)) = entry.get_mut("annotations") {
+        annotations.remove("lastModified");
+    }
+}
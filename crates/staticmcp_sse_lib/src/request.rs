@@ -0,0 +1,175 @@
+//! Reading a JSON-RPC request from its JSON. Strictly, a request that
+//! isn't one is refused with an error naming the field at fault; leniently,
+//! the deviations clients are known to send are corrected, with a warning
+//! logged for each, and only what can't be corrected is refused.
+
+use crate::MCPRequest;
+use serde_json::{Map, Value, json};
+use tracing::warn;
+
+/// Why a JSON value isn't a request, for a JSON-RPC `-32600 Invalid
+/// Request` error.
+#[derive(Debug)]
+pub struct InvalidRequest {
+    /// The request's id, when it had a usable one, to answer under.
+    pub id: Option<Value>,
+    pub message: String,
+    /// The member at fault, or `None` when the value isn't an object.
+    pub field: Option<&'static str>,
+}
+
+impl InvalidRequest {
+    fn new(id: &Option<Value>, field: &'static str, message: String) -> Self {
+        Self {
+            id: id.clone(),
+            message: format!("Invalid Request: {message}"),
+            field: Some(field),
+        }
+    }
+
+    /// The error's `data`: the member at fault.
+    pub fn data(&self) -> Option<Value> {
+        self.field.map(|field| json!({ "field": field }))
+    }
+}
+
+impl std::fmt::Display for InvalidRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for InvalidRequest {}
+
+/// Reads `value` as a request. With `lenient`, a `jsonrpc` that is missing
+/// or the number `2.0`, params sent as a JSON-encoded string, and params
+/// wrapped in a one-element array are accepted as if sent properly.
+pub fn parse(value: Value, lenient: bool) -> Result<MCPRequest, InvalidRequest> {
+    let Value::Object(mut fields) = value else {
+        return Err(InvalidRequest {
+            id: None,
+            message: format!(
+                "Invalid Request: expected a JSON object, not {}",
+                kind(&value)
+            ),
+            field: None,
+        });
+    };
+    let id = id(&mut fields, lenient)?;
+    let method = match fields.remove("method") {
+        Some(Value::String(method)) => method,
+        None => {
+            return Err(InvalidRequest::new(
+                &id,
+                "method",
+                "missing \"method\"".into(),
+            ));
+        }
+        Some(other) => {
+            return Err(InvalidRequest::new(
+                &id,
+                "method",
+                format!("\"method\" must be a string, not {}", kind(&other)),
+            ));
+        }
+    };
+    let jsonrpc = match fields.remove("jsonrpc") {
+        Some(Value::String(version)) => version,
+        None if lenient => {
+            warn!(%method, "request without \"jsonrpc\", read as \"2.0\"");
+            "2.0".to_string()
+        }
+        None => {
+            return Err(InvalidRequest::new(
+                &id,
+                "jsonrpc",
+                "missing \"jsonrpc\": \"2.0\"".into(),
+            ));
+        }
+        Some(Value::Number(version)) if lenient && version.as_f64() == Some(2.0) => {
+            warn!(%method, "request with \"jsonrpc\" as the number {version}, read as \"2.0\"");
+            "2.0".to_string()
+        }
+        Some(other) => {
+            return Err(InvalidRequest::new(
+                &id,
+                "jsonrpc",
+                format!("\"jsonrpc\" must be the string \"2.0\", not {other}"),
+            ));
+        }
+    };
+    let params = params(fields.remove("params"), &id, &method, lenient)?;
+    Ok(MCPRequest {
+        jsonrpc,
+        id,
+        method,
+        params,
+    })
+}
+
+/// The request's id; `null` is no id, as for a notification.
+fn id(fields: &mut Map<String, Value>, lenient: bool) -> Result<Option<Value>, InvalidRequest> {
+    match fields.remove("id") {
+        None | Some(Value::Null) => Ok(None),
+        Some(id @ (Value::String(_) | Value::Number(_))) => Ok(Some(id)),
+        Some(id) if lenient => {
+            warn!(%id, "request id is neither a string nor a number");
+            Ok(Some(id))
+        }
+        Some(other) => Err(InvalidRequest::new(
+            &None,
+            "id",
+            format!(
+                "\"id\" must be a string, a number or null, not {}",
+                kind(&other)
+            ),
+        )),
+    }
+}
+
+fn params(
+    params: Option<Value>,
+    id: &Option<Value>,
+    method: &str,
+    lenient: bool,
+) -> Result<Option<Value>, InvalidRequest> {
+    match params {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(mut items)) if lenient && items.len() == 1 && items[0].is_object() => {
+            warn!(%method, "params wrapped in an array, read as the object inside");
+            Ok(items.pop())
+        }
+        Some(params @ (Value::Object(_) | Value::Array(_))) => Ok(Some(params)),
+        Some(Value::String(encoded)) if lenient => match serde_json::from_str::<Value>(&encoded) {
+            Ok(params @ (Value::Object(_) | Value::Array(_))) => {
+                warn!(%method, "params sent as a JSON string, read as the JSON it holds");
+                self::params(Some(params), id, method, lenient)
+            }
+            _ => Err(InvalidRequest::new(
+                id,
+                "params",
+                "\"params\" must be an object or an array, not a string".into(),
+            )),
+        },
+        Some(other) => Err(InvalidRequest::new(
+            id,
+            "params",
+            format!(
+                "\"params\" must be an object or an array, not {}",
+                kind(&other)
+            ),
+        )),
+    }
+}
+
+/// `value`'s JSON type, as an error message names it.
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
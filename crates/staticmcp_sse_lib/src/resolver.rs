@@ -0,0 +1,489 @@
+//! Mapping of resource URIs and tool calls to the static files answering
+//! them, for sites whose entries don't state their file with `pathTemplate`.
+
+use crate::ToolDef;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Maps requests to site-relative file paths. Set one with
+/// [`crate::MCPBridgeBuilder::path_resolver`] to serve an existing static
+/// layout; aliases, argument defaults and manifest `pathTemplate`s are
+/// applied by the bridge before it is consulted.
+pub trait PathResolver: Send + Sync {
+    /// The file holding the resource `uri`.
+    fn uri_to_path(&self, uri: &str) -> String;
+
+    /// The file answering a call of the tool `tool_name` with `args`; `tool`
+    /// is its manifest entry, when the manifest is loaded.
+    fn tool_to_path(
+        &self,
+        tool: Option<&ToolDef>,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+    ) -> anyhow::Result<String>;
+}
+
+/// How a tool called with more than two arguments names its file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentHash {
+    /// The base64 of the `key=value` pairs, with `/`, `+` and `=` replaced
+    /// by `_`: long, and names differing only there collide.
+    #[default]
+    Base64,
+    /// The first 32 hex digits of the pairs' SHA-256.
+    Sha256,
+}
+
+/// How a tool called with two arguments orders them in its file's path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentOrder {
+    /// Sorted by value, the original layout: calls swapping the two values
+    /// share a file.
+    #[default]
+    Value,
+    /// Sorted by argument name, so each value keeps its place.
+    Key,
+}
+
+/// The StaticMCP layout: `resources/{uri without scheme}.json`, and
+/// `tools/{name}.json`, `tools/{name}/{value}.json`,
+/// `tools/{name}/{first}/{second}.json` with the values sorted by value or
+/// name, or `tools/{name}/{hash}.json` by the number of arguments. Values
+/// are percent-encoded with [`encode_segment`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultPathResolver {
+    /// The hash of tools whose manifest entry doesn't pick one with
+    /// `argumentHash`.
+    pub argument_hash: ArgumentHash,
+    /// The order of tools whose manifest entry doesn't pick one with
+    /// `argumentOrder`.
+    pub argument_order: ArgumentOrder,
+}
+
+impl PathResolver for DefaultPathResolver {
+    fn uri_to_path(&self, uri: &str) -> String {
+        if uri.starts_with("file://") {
+            format!("resources/{}.json", uri.strip_prefix("file://").unwrap())
+        } else if uri.contains("://") {
+            let parts: Vec<&str> = uri.split("://").collect();
+            if parts.len() == 2 {
+                format!("resources/{}.json", parts[1])
+            } else {
+                format!("{uri}.json")
+            }
+        } else if uri.ends_with(".json") {
+            uri.to_string()
+        } else {
+            format!("{uri}.json")
+        }
+    }
+
+    fn tool_to_path(
+        &self,
+        tool: Option<&ToolDef>,
+        tool_name: &str,
+        args: &HashMap<String, Value>,
+    ) -> anyhow::Result<String> {
+        let tool_dir = format!("tools/{tool_name}");
+
+        if args.is_empty() {
+            return Ok(format!("{tool_dir}.json"));
+        }
+
+        if args.len() == 1 {
+            let arg_str = encode_segment(&path_value(args.values().next().unwrap()))?;
+            return Ok(format!("{tool_dir}/{arg_str}.json"));
+        }
+
+        if args.len() == 2 {
+            let order = tool
+                .and_then(|tool| tool.argument_order)
+                .unwrap_or(self.argument_order);
+            let values: Vec<String> = match order {
+                ArgumentOrder::Value => {
+                    let mut values: Vec<String> = args.values().map(path_value).collect();
+                    values.sort();
+                    values
+                }
+                ArgumentOrder::Key => {
+                    let mut pairs: Vec<(&String, &Value)> = args.iter().collect();
+                    pairs.sort_by(|a, b| a.0.cmp(b.0));
+                    pairs
+                        .into_iter()
+                        .map(|(_, value)| path_value(value))
+                        .collect()
+                }
+            };
+            let (first, second) = (encode_segment(&values[0])?, encode_segment(&values[1])?);
+            return Ok(format!("{tool_dir}/{first}/{second}.json"));
+        }
+
+        // Multiple arguments - create a hash-like path
+        let mut sorted_args: Vec<(String, String)> = args
+            .iter()
+            .map(|(k, v)| (k.clone(), path_value(v)))
+            .collect();
+        sorted_args.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let arg_string = sorted_args
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let hash = match tool
+            .and_then(|tool| tool.argument_hash)
+            .unwrap_or(self.argument_hash)
+        {
+            ArgumentHash::Base64 => hash_args(&arg_string)?,
+            ArgumentHash::Sha256 => sha256_args(&arg_string)?,
+        };
+
+        Ok(format!("{tool_dir}/{hash}.json"))
+    }
+}
+
+#[cfg(feature = "base64")]
+fn hash_args(arg_string: &str) -> anyhow::Result<String> {
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        arg_string.as_bytes(),
+    )
+    .replace(['/', '+', '='], "_"))
+}
+
+#[cfg(not(feature = "base64"))]
+fn hash_args(_arg_string: &str) -> anyhow::Result<String> {
+    anyhow::bail!("Tools with more than two arguments require the `base64` feature")
+}
+
+#[cfg(feature = "sha256")]
+fn sha256_args(arg_string: &str) -> anyhow::Result<String> {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(arg_string.as_bytes());
+    Ok(digest[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(not(feature = "sha256"))]
+fn sha256_args(_arg_string: &str) -> anyhow::Result<String> {
+    anyhow::bail!("SHA-256 argument hashes require the `sha256` feature")
+}
+
+/// An argument value as it appears in a path: strings as they are, other
+/// values as JSON.
+pub(crate) fn path_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
+
+/// Longest encoded segment that still fits a 255-byte file name once
+/// `.json` and a precompressed suffix are appended.
+const MAX_SEGMENT_BYTES: usize = 255 - ".json.gz".len();
+
+/// `value` as a single path segment, percent-encoded the way JavaScript's
+/// `encodeURIComponent` does: ASCII letters, digits and `-_.!~*'()` stay
+/// as they are, every other byte of its UTF-8 becomes `%XX`, so `/` can't
+/// leave the directory and the name is the same on disk and in a URL.
+/// `.` and `..` are encoded whole. Empty values and ones too long for a
+/// file name are rejected.
+pub fn encode_segment(value: &str) -> anyhow::Result<String> {
+    if value.is_empty() {
+        anyhow::bail!("an empty argument can't be used in a path");
+    }
+    if value == "." || value == ".." {
+        return Ok(value.replace('.', "%2E"));
+    }
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    if encoded.len() > MAX_SEGMENT_BYTES {
+        anyhow::bail!(
+            "argument is {} bytes once encoded, more than the {MAX_SEGMENT_BYTES} a file name allows",
+            encoded.len()
+        );
+    }
+    Ok(encoded)
+}
+
+/// The value [`encode_segment`] encoded as `segment`, or `None` when it
+/// isn't valid percent-encoded UTF-8.
+pub fn decode_segment(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// `path` as the path of a URL naming the same file: `%`, `?` and `#` are
+/// escaped so hosts don't decode the encoded segments a second time or cut
+/// the path at them.
+pub fn url_path(path: &str) -> Cow<'_, str> {
+    if !path.contains(['%', '?', '#']) {
+        return Cow::Borrowed(path);
+    }
+    Cow::Owned(
+        path.replace('%', "%25")
+            .replace('?', "%3F")
+            .replace('#', "%23"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), Value::from(*value)))
+            .collect()
+    }
+
+    fn tool(entry: Value) -> ToolDef {
+        serde_json::from_value(entry).unwrap()
+    }
+
+    #[cfg(all(feature = "base64", feature = "sha256"))]
+    #[test]
+    fn hashes_many_arguments_as_configured() {
+        let args = args(&[("c", "3"), ("a", "1"), ("b", "2")]);
+        let base64 = DefaultPathResolver::default();
+        assert_eq!(
+            base64.tool_to_path(None, "sum", &args).unwrap(),
+            "tools/sum/YT0xJmI9MiZjPTM_.json"
+        );
+        let sha256 = DefaultPathResolver {
+            argument_hash: ArgumentHash::Sha256,
+            ..DefaultPathResolver::default()
+        };
+        assert_eq!(
+            sha256.tool_to_path(None, "sum", &args).unwrap(),
+            "tools/sum/140533f5d7e20d4b831cf75c613efc8c.json"
+        );
+    }
+
+    #[cfg(all(feature = "base64", feature = "sha256"))]
+    #[test]
+    fn tools_pick_their_own_argument_hash() {
+        let args = args(&[("city", "Paris"), ("days", "3"), ("units", "metric")]);
+        let sha256 = tool(json!({ "name": "forecast", "argumentHash": "sha256" }));
+        let base64 = tool(json!({ "name": "forecast", "argumentHash": "base64" }));
+        let resolver = DefaultPathResolver::default();
+        assert_eq!(
+            resolver
+                .tool_to_path(Some(&sha256), "forecast", &args)
+                .unwrap(),
+            "tools/forecast/af859f788de16c06d5166ba1071b59e4.json"
+        );
+        let resolver = DefaultPathResolver {
+            argument_hash: ArgumentHash::Sha256,
+            ..DefaultPathResolver::default()
+        };
+        assert_eq!(
+            resolver
+                .tool_to_path(Some(&base64), "forecast", &args)
+                .unwrap(),
+            "tools/forecast/Y2l0eT1QYXJpcyZkYXlzPTMmdW5pdHM9bWV0cmlj.json"
+        );
+    }
+
+    #[test]
+    fn orders_two_arguments_as_configured() {
+        let args = args(&[("to", "alice"), ("from", "zed")]);
+        let by_value = DefaultPathResolver::default();
+        assert_eq!(
+            by_value.tool_to_path(None, "send", &args).unwrap(),
+            "tools/send/alice/zed.json"
+        );
+        let by_key = DefaultPathResolver {
+            argument_order: ArgumentOrder::Key,
+            ..DefaultPathResolver::default()
+        };
+        assert_eq!(
+            by_key.tool_to_path(None, "send", &args).unwrap(),
+            "tools/send/zed/alice.json"
+        );
+        let swapped = self::args(&[("to", "zed"), ("from", "alice")]);
+        assert_eq!(
+            by_value.tool_to_path(None, "send", &swapped).unwrap(),
+            "tools/send/alice/zed.json"
+        );
+        assert_eq!(
+            by_key.tool_to_path(None, "send", &swapped).unwrap(),
+            "tools/send/alice/zed.json"
+        );
+    }
+
+    #[test]
+    fn tools_pick_their_own_argument_order() {
+        let args = args(&[("to", "alice"), ("from", "zed b")]);
+        let by_key = tool(json!({ "name": "send", "argumentOrder": "key" }));
+        let by_value = tool(json!({ "name": "send", "argumentOrder": "value" }));
+        assert_eq!(
+            DefaultPathResolver::default()
+                .tool_to_path(Some(&by_key), "send", &args)
+                .unwrap(),
+            "tools/send/zed%20b/alice.json"
+        );
+        let resolver = DefaultPathResolver {
+            argument_order: ArgumentOrder::Key,
+            ..DefaultPathResolver::default()
+        };
+        assert_eq!(
+            resolver
+                .tool_to_path(Some(&by_value), "send", &args)
+                .unwrap(),
+            "tools/send/alice/zed%20b.json"
+        );
+        let unset = tool(json!({ "name": "send" }));
+        assert_eq!(
+            resolver.tool_to_path(Some(&unset), "send", &args).unwrap(),
+            "tools/send/zed%20b/alice.json"
+        );
+    }
+
+    #[test]
+    fn encodes_like_encode_uri_component() {
+        assert_eq!(
+            encode_segment("report-2024_v1.final").unwrap(),
+            "report-2024_v1.final"
+        );
+        assert_eq!(encode_segment("!~*'()").unwrap(), "!~*'()");
+        assert_eq!(encode_segment("a b").unwrap(), "a%20b");
+        assert_eq!(encode_segment("a/b").unwrap(), "a%2Fb");
+        assert_eq!(encode_segment("a\\b").unwrap(), "a%5Cb");
+        assert_eq!(encode_segment("100%").unwrap(), "100%25");
+        assert_eq!(
+            encode_segment("a?b#c&d=e+f").unwrap(),
+            "a%3Fb%23c%26d%3De%2Bf"
+        );
+        assert_eq!(encode_segment("café").unwrap(), "caf%C3%A9");
+        assert_eq!(encode_segment("東京").unwrap(), "%E6%9D%B1%E4%BA%AC");
+        assert_eq!(encode_segment("🦀").unwrap(), "%F0%9F%A6%80");
+    }
+
+    #[test]
+    fn encodes_dot_segments_whole() {
+        assert_eq!(encode_segment(".").unwrap(), "%2E");
+        assert_eq!(encode_segment("..").unwrap(), "%2E%2E");
+        assert_eq!(encode_segment("../etc").unwrap(), "..%2Fetc");
+        assert_eq!(encode_segment("...").unwrap(), "...");
+        assert_eq!(encode_segment(".hidden").unwrap(), ".hidden");
+    }
+
+    #[test]
+    fn refuses_empty_values() {
+        assert!(encode_segment("").is_err());
+    }
+
+    #[test]
+    fn refuses_segments_longer_than_a_file_name_allows() {
+        assert_eq!(MAX_SEGMENT_BYTES, 247);
+        assert_eq!(encode_segment(&"a".repeat(247)).unwrap().len(), 247);
+        assert!(encode_segment(&"a".repeat(248)).is_err());
+        // The limit counts encoded bytes: 41 `é` are 246 once encoded, 42
+        // are 252.
+        assert!(encode_segment(&"é".repeat(41)).is_ok());
+        assert!(encode_segment(&"é".repeat(42)).is_err());
+        assert_eq!(encode_segment(&" ".repeat(82)).unwrap().len(), 246);
+        assert!(encode_segment(&" ".repeat(83)).is_err());
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        for value in [
+            "plain", "a b", "a/b", ".", "..", "100%", "a?b#c", "café", "東京", "🦀", "!~*'()",
+        ] {
+            let encoded = encode_segment(value).unwrap();
+            assert_eq!(
+                decode_segment(&encoded).as_deref(),
+                Some(value),
+                "{encoded}"
+            );
+        }
+        assert_eq!(decode_segment("%2e%2e").as_deref(), Some(".."));
+        assert_eq!(decode_segment("").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn refuses_invalid_encodings() {
+        assert_eq!(decode_segment("%"), None);
+        assert_eq!(decode_segment("%2"), None);
+        assert_eq!(decode_segment("%zz"), None);
+        assert_eq!(decode_segment("%+1"), None);
+        assert_eq!(decode_segment("%C3"), None);
+        assert_eq!(decode_segment("%FF"), None);
+    }
+
+    #[test]
+    fn escapes_url_paths() {
+        assert!(matches!(
+            url_path("tools/search/rust.json"),
+            Cow::Borrowed("tools/search/rust.json")
+        ));
+        assert_eq!(
+            url_path("tools/search/a%20b.json"),
+            "tools/search/a%2520b.json"
+        );
+        assert_eq!(url_path("tools/q/why?.json"), "tools/q/why%3F.json");
+        assert_eq!(url_path("tools/q/c#.json"), "tools/q/c%23.json");
+        assert_eq!(url_path("%?#"), "%25%3F%23");
+    }
+
+    #[test]
+    fn tool_paths_encode_each_value() {
+        let resolver = DefaultPathResolver::default();
+        let args = HashMap::from([("path".to_string(), Value::from("../../etc/passwd"))]);
+        assert_eq!(
+            resolver.tool_to_path(None, "read", &args).unwrap(),
+            "tools/read/..%2F..%2Fetc%2Fpasswd.json"
+        );
+        let args = HashMap::from([("dir".to_string(), Value::from(".."))]);
+        assert_eq!(
+            resolver.tool_to_path(None, "list", &args).unwrap(),
+            "tools/list/%2E%2E.json"
+        );
+        let args = HashMap::from([("query".to_string(), Value::from(""))]);
+        assert!(resolver.tool_to_path(None, "search", &args).is_err());
+    }
+}
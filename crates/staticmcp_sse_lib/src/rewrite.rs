@@ -0,0 +1,190 @@
+//! Rules rewriting the URIs resources are read by and the paths requests
+//! resolve to, given by `--rewrite-*` flags or a manifest's `rewrites`, so
+//! a site can move its files or retire old URIs without code changes.
+//!
+//! A rule's pattern is a `{name}` template, as in `uriTemplate`s, whose
+//! variables its replacement uses; or a regular expression when it starts
+//! with `^`, whose replacement refers to its groups as `$1` or `${name}`.
+//! A rule rewrites one of:
+//!
+//! - a URI read into another, before it is looked up, such as
+//!   `legacy://{+page}` into `docs://{page}`;
+//! - a URI into the path it is read from, in place of the path resolver's,
+//!   such as `docs://{+page}` into `resources/documentation/{page}.json`;
+//! - a resolved path, of a resource, tool call or prompt, into another,
+//!   such as `^v1/(.*)$` into `$1`.
+//!
+//! The first rule matching wins; flags' rules come before the manifest's.
+//! A rewrite producing a `..` segment is ignored, so rules can't climb out
+//! of the site.
+
+use crate::path_template;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// What a rule matches and what it produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rewrite {
+    UriToUri,
+    UriToPath,
+    PathToPath,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Template(String),
+    Regex(Regex),
+}
+
+/// One rewrite rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RuleFields", into = "RuleFields")]
+pub struct RewriteRule {
+    pub kind: Rewrite,
+    pattern: Pattern,
+    replacement: String,
+}
+
+impl RewriteRule {
+    /// A rule rewriting what matches `pattern` into `replacement`, or why
+    /// the pair can't be one.
+    pub fn new(kind: Rewrite, pattern: &str, replacement: &str) -> Result<Self, String> {
+        let pattern = if pattern.starts_with('^') {
+            Pattern::Regex(Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?)
+        } else {
+            let captured: Vec<&str> = path_template::variables(pattern).collect();
+            if let Some(unknown) =
+                path_template::variables(replacement).find(|name| !captured.contains(name))
+            {
+                return Err(format!("`{{{unknown}}}` isn't a variable of {pattern}"));
+            }
+            Pattern::Template(pattern.to_string())
+        };
+        Ok(Self {
+            kind,
+            pattern,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    /// `input` rewritten, when the rule matches it.
+    pub fn apply(&self, input: &str) -> Option<String> {
+        let rewritten = match &self.pattern {
+            Pattern::Template(template) => {
+                let captures = path_template::captures(template, input)?;
+                path_template::expand(&self.replacement, |name| captures.get(name).cloned()).ok()?
+            }
+            Pattern::Regex(regex) => {
+                let captures = regex.captures(input)?;
+                let mut rewritten = String::new();
+                captures.expand(&self.replacement, &mut rewritten);
+                rewritten
+            }
+        };
+        if rewritten.split('/').any(|segment| segment == "..") {
+            tracing::warn!(input, rewritten, "rewrite leaving the site ignored");
+            return None;
+        }
+        Some(rewritten)
+    }
+
+    fn pattern(&self) -> &str {
+        match &self.pattern {
+            Pattern::Template(template) => template,
+            Pattern::Regex(regex) => regex.as_str(),
+        }
+    }
+}
+
+/// The first of `rules` of `kind` rewriting `input`, and what it rewrote it
+/// into.
+pub fn first<'a>(
+    rules: impl IntoIterator<Item = &'a RewriteRule>,
+    kind: Rewrite,
+    input: &str,
+) -> Option<String> {
+    rules
+        .into_iter()
+        .filter(|rule| rule.kind == kind)
+        .find_map(|rule| rule.apply(input))
+}
+
+/// A rule as a manifest writes it: the URI or path pattern it matches, and
+/// what it rewrites that into.
+#[derive(Serialize, Deserialize)]
+struct RuleFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to: Option<String>,
+    #[serde(rename = "toPath", default, skip_serializing_if = "Option::is_none")]
+    to_path: Option<String>,
+}
+
+impl TryFrom<RuleFields> for RewriteRule {
+    type Error = String;
+
+    fn try_from(fields: RuleFields) -> Result<Self, String> {
+        let (kind, pattern, replacement) = match fields {
+            RuleFields {
+                uri: Some(uri),
+                path: None,
+                to: Some(to),
+                to_path: None,
+            } => (Rewrite::UriToUri, uri, to),
+            RuleFields {
+                uri: Some(uri),
+                path: None,
+                to: None,
+                to_path: Some(to_path),
+            } => (Rewrite::UriToPath, uri, to_path),
+            RuleFields {
+                uri: None,
+                path: Some(path),
+                to: Some(to),
+                to_path: None,
+            } => (Rewrite::PathToPath, path, to),
+            _ => {
+                return Err("expected `uri` with `to` or `toPath`, or `path` with `to`".to_string());
+            }
+        };
+        Self::new(kind, &pattern, &replacement)
+    }
+}
+
+impl From<RewriteRule> for RuleFields {
+    fn from(rule: RewriteRule) -> Self {
+        let pattern = Some(rule.pattern().to_string());
+        let replacement = Some(rule.replacement);
+        match rule.kind {
+            Rewrite::UriToUri => Self {
+                uri: pattern,
+                path: None,
+                to: replacement,
+                to_path: None,
+            },
+            Rewrite::UriToPath => Self {
+                uri: pattern,
+                path: None,
+                to: None,
+                to_path: replacement,
+            },
+            Rewrite::PathToPath => Self {
+                uri: None,
+                path: pattern,
+                to: replacement,
+                to_path: None,
+            },
+        }
+    }
+}
+
+/// A `FROM=TO` flag value as a rule of `kind`.
+pub fn parse(kind: Rewrite, value: &str) -> Result<RewriteRule, String> {
+    let Some((pattern, replacement)) = value.split_once('=') else {
+        return Err("expected FROM=TO, such as legacy://{+page}=docs://{page}".into());
+    };
+    RewriteRule::new(kind, pattern, replacement)
+}
@@ -0,0 +1,410 @@
+//! Full-text search over a site's resources, answering the synthetic
+//! `search` tool: a tantivy index of the resources' names and text, built
+//! when the bridge loads its manifest or opened from a directory built
+//! earlier, ranked with BM25.
+
+use crate::{MCPBridge, resource_contents};
+use futures::{StreamExt, stream};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, STORED, STRING, Schema, TEXT, Value as _};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{DocAddress, Index, IndexReader, IndexWriter, TantivyDocument, doc};
+use tracing::{info, warn};
+
+/// Name of the tool answering searches.
+pub const TOOL_NAME: &str = "search";
+
+/// Results returned when a call doesn't set `limit`, and the most it may set.
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+
+/// Most characters of text in a snippet.
+const SNIPPET_CHARS: usize = 160;
+
+/// Resource files read at once while indexing.
+const BUILD_CONCURRENCY: usize = 8;
+
+/// Memory the index writer may buffer before flushing a segment.
+const WRITER_BYTES: usize = 50 << 20;
+
+/// How much more a match in a resource's name counts than one in its text.
+const NAME_BOOST: f32 = 2.0;
+
+/// The indexed resources, searchable by the words of their name and text.
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    fields: Fields,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    uri: Field,
+    name: Field,
+    text: Field,
+}
+
+impl Fields {
+    fn schema() -> Schema {
+        let mut schema = Schema::builder();
+        schema.add_text_field("uri", STRING | STORED);
+        schema.add_text_field("name", TEXT | STORED);
+        schema.add_text_field("text", TEXT | STORED);
+        schema.build()
+    }
+
+    fn of(schema: &Schema) -> tantivy::Result<Self> {
+        Ok(Self {
+            uri: schema.get_field("uri")?,
+            name: schema.get_field("name")?,
+            text: schema.get_field("text")?,
+        })
+    }
+}
+
+/// A resource matching a search.
+#[derive(Debug, Serialize)]
+pub struct Hit {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub score: f64,
+    /// The passage of text best matching the query, whitespace collapsed.
+    pub snippet: String,
+}
+
+impl SearchIndex {
+    /// Indexes the text of every resource `bridge` declares. Binary
+    /// resources, those answered by its upstream, and files that fail to
+    /// load are left out.
+    pub async fn build(bridge: &MCPBridge) -> anyhow::Result<Self> {
+        // Owned, so the future stays `Send` for any caller.
+        let resources: Vec<(String, Option<String>)> = bridge
+            .resources()
+            .iter()
+            .filter(|resource| !resource.upstream)
+            .filter(|resource| {
+                bridge
+                    .raw_resource_file(&resource.uri, &bridge.uri_to_path(&resource.uri))
+                    .is_none_or(|(_, mime)| crate::mime::is_text(&mime))
+            })
+            .map(|resource| (resource.uri.clone(), resource.name.clone()))
+            .collect();
+        let documents: Vec<(String, Option<String>, String)> = stream::iter(resources)
+            .map(|(uri, name)| async move {
+                let (path, result) = bridge.load_resource(&uri, &[], None).await;
+                let result = match result {
+                    Ok(loaded) => loaded.into_value().await,
+                    Err(e) => Err(e),
+                };
+                match (path, result) {
+                    (_, Ok(value)) => {
+                        let text = text(&resource_contents(&uri, &value));
+                        Some((uri, name, text))
+                    }
+                    (path, Err(e)) => {
+                        warn!(uri, path, error = %e, "failed to index resource");
+                        None
+                    }
+                }
+            })
+            .buffered(BUILD_CONCURRENCY)
+            .filter_map(std::future::ready)
+            .collect()
+            .await;
+
+        let index = Self::from_documents(Index::create_in_ram(Fields::schema()), documents)?;
+        info!(documents = index.len(), "built search index");
+        Ok(index)
+    }
+
+    /// `index`, empty, filled with `documents` of a URI, name and text.
+    fn from_documents(
+        index: Index,
+        documents: impl IntoIterator<Item = (String, Option<String>, String)>,
+    ) -> anyhow::Result<Self> {
+        let fields = Fields::of(&index.schema())?;
+        let mut writer: IndexWriter = index.writer(WRITER_BYTES)?;
+        for (uri, name, text) in documents {
+            let mut document = doc!(fields.uri => uri, fields.text => text);
+            if let Some(name) = name {
+                document.add_text(fields.name, name);
+            }
+            writer.add_document(document)?;
+        }
+        writer.commit()?;
+        Self::open_index(index)
+    }
+
+    /// The index built earlier in the directory `path`.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Self::open_index(Index::open_in_dir(path)?)
+    }
+
+    fn open_index(index: Index) -> anyhow::Result<Self> {
+        let fields = Fields::of(&index.schema())?;
+        let reader = index.reader()?;
+        Ok(Self {
+            index,
+            reader,
+            fields,
+        })
+    }
+
+    /// Writes a copy of the index into the directory `path`, which is
+    /// created, for [`Self::open`] to read in later runs.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(path)?;
+        let searcher = self.reader.searcher();
+        let mut documents = Vec::new();
+        for (segment, reader) in searcher.segment_readers().iter().enumerate() {
+            for id in reader.doc_ids_alive() {
+                let document: TantivyDocument =
+                    searcher.doc(DocAddress::new(segment as u32, id))?;
+                documents.push((
+                    self.stored(&document, self.fields.uri).unwrap_or_default(),
+                    self.stored(&document, self.fields.name),
+                    self.stored(&document, self.fields.text).unwrap_or_default(),
+                ));
+            }
+        }
+        Self::from_documents(Index::create_in_dir(path, Fields::schema())?, documents)?;
+        Ok(())
+    }
+
+    fn stored(&self, document: &TantivyDocument, field: Field) -> Option<String> {
+        document
+            .get_first(field)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    }
+
+    /// Number of indexed resources.
+    pub fn len(&self) -> usize {
+        self.reader.searcher().num_docs() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Up to `limit` resources matching any word of `query`, best first,
+    /// leaving out those `readable` refuses.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        readable: impl Fn(&str) -> bool,
+    ) -> anyhow::Result<Vec<Hit>> {
+        let searcher = self.reader.searcher();
+        let mut parser =
+            QueryParser::for_index(&self.index, vec![self.fields.name, self.fields.text]);
+        parser.set_field_boost(self.fields.name, NAME_BOOST);
+        // Syntax errors in what an agent typed shouldn't fail the search;
+        // the parts that did parse are looked up.
+        let (query, _) = parser.parse_query_lenient(query);
+        let mut snippets = SnippetGenerator::create(&searcher, &*query, self.fields.text)?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        // Resources the caller can't read are dropped after ranking, so ask
+        // for more until enough are left or none remain.
+        let total = searcher.num_docs() as usize;
+        let mut wanted = limit;
+        loop {
+            let ranked = searcher.search(&query, &TopDocs::with_limit(wanted).order_by_score())?;
+            let exhausted = ranked.len() < wanted || wanted >= total;
+            let mut hits = Vec::new();
+            for (score, address) in ranked {
+                let document: TantivyDocument = searcher.doc(address)?;
+                let uri = self.stored(&document, self.fields.uri).unwrap_or_default();
+                if !readable(&uri) {
+                    continue;
+                }
+                let snippet = snippets.snippet_from_doc(&document);
+                let snippet = match snippet.fragment() {
+                    "" => self
+                        .stored(&document, self.fields.text)
+                        .unwrap_or_default()
+                        .chars()
+                        .take(SNIPPET_CHARS)
+                        .collect(),
+                    fragment => fragment.to_string(),
+                };
+                hits.push(Hit {
+                    uri,
+                    name: self.stored(&document, self.fields.name),
+                    score: (f64::from(score) * 1000.0).round() / 1000.0,
+                    snippet: snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+                });
+                if hits.len() == limit {
+                    return Ok(hits);
+                }
+            }
+            if exhausted {
+                return Ok(hits);
+            }
+            wanted = (wanted * 4).min(total);
+        }
+    }
+
+    /// The result of a `search` call with `args`, or the message of an
+    /// `isError` result when its arguments are invalid.
+    pub(crate) fn call(
+        &self,
+        args: &HashMap<String, Value>,
+        readable: impl Fn(&str) -> bool,
+    ) -> Result<Value, String> {
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .filter(|query| !query.trim().is_empty())
+            .ok_or("`query` must be a non-empty string")?;
+        let limit = match args.get("limit") {
+            None => DEFAULT_LIMIT,
+            Some(limit) => limit
+                .as_u64()
+                .filter(|limit| (1..=MAX_LIMIT as u64).contains(limit))
+                .ok_or(format!("`limit` must be an integer from 1 to {MAX_LIMIT}"))?
+                as usize,
+        };
+        let hits = self
+            .search(query, limit, readable)
+            .map_err(|e| format!("search failed: {e:#}"))?;
+        let text = serde_json::to_string_pretty(&json!({ "results": hits })).unwrap_or_default();
+        Ok(json!({ "content": [{ "type": "text", "text": text }] }))
+    }
+}
+
+/// The manifest entry `tools/list` shows for the search tool.
+pub(crate) fn tool() -> Value {
+    json!({
+        "name": TOOL_NAME,
+        "description": "Full-text search over this server's resources, returning the URIs that best match the query with a snippet of each",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Words to look for"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": format!("Most results to return, at most {MAX_LIMIT}"),
+                    "default": DEFAULT_LIMIT
+                }
+            },
+            "required": ["query"]
+        }
+    })
+}
+
+/// The text entries of resource `contents`, one per line.
+fn text(contents: &Value) -> String {
+    contents
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|content| content["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        let documents = [
+            (
+                "docs://install",
+                Some("Installing"),
+                "Download the installer, then run it. The installer asks where to put the files.",
+            ),
+            (
+                "docs://tuning",
+                Some("Tuning the cache"),
+                "The cache keeps files for a while. Set the cache size and the cache lifetime to \
+                 suit the site; a larger cache serves more files from memory.",
+            ),
+            (
+                "docs://faq",
+                None,
+                "Frequently asked questions about licensing, support and the cache.",
+            ),
+        ];
+        SearchIndex::from_documents(
+            Index::create_in_ram(Fields::schema()),
+            documents.map(|(uri, name, text)| {
+                (uri.to_string(), name.map(str::to_string), text.to_string())
+            }),
+        )
+        .unwrap()
+    }
+
+    fn uris(hits: &[Hit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.uri.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_by_relevance() {
+        let hits = index().search("cache", 10, |_| true).unwrap();
+        assert_eq!(uris(&hits), ["docs://tuning", "docs://faq"]);
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(hits[0].name.as_deref(), Some("Tuning the cache"));
+    }
+
+    #[test]
+    fn matches_any_word_case_insensitively() {
+        let hits = index().search("INSTALLER licensing", 10, |_| true).unwrap();
+        assert_eq!(uris(&hits), ["docs://install", "docs://faq"]);
+        assert!(
+            index()
+                .search("nothing matches", 10, |_| true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn snippets_show_the_match() {
+        let hits = index().search("lifetime", 10, |_| true).unwrap();
+        assert_eq!(uris(&hits), ["docs://tuning"]);
+        assert!(hits[0].snippet.contains("lifetime"), "{}", hits[0].snippet);
+        assert!(hits[0].snippet.chars().count() <= SNIPPET_CHARS);
+        assert!(!hits[0].snippet.contains("  "));
+    }
+
+    #[test]
+    fn leaves_out_unreadable_resources_and_keeps_the_limit() {
+        let index = index();
+        let hits = index
+            .search("cache", 10, |uri| uri != "docs://tuning")
+            .unwrap();
+        assert_eq!(uris(&hits), ["docs://faq"]);
+        let hits = index.search("the cache", 1, |_| true).unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn tolerates_query_syntax() {
+        let hits = index().search("cache AND (", 10, |_| true).unwrap();
+        assert!(!hits.is_empty());
+    }
+
+    #[test]
+    fn opens_a_saved_index() {
+        let directory = std::env::temp_dir().join(format!("search-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        index().save(&directory).unwrap();
+        let opened = SearchIndex::open(&directory).unwrap();
+        assert_eq!(opened.len(), 3);
+        let hits = opened.search("installer", 10, |_| true).unwrap();
+        assert_eq!(uris(&hits), ["docs://install"]);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
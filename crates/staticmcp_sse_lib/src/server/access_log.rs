@@ -0,0 +1,105 @@
+//! Per-request access logs, written to stdout one line per request.
+
+use super::RequestId;
+use super::client_ip::client_ip;
+use super::envelope::McpEnvelope;
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use clap::builder::BoolishValueParser;
+use clap::{Args, ValueEnum};
+use serde_json::json;
+use std::io::Write;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AccessLogFormat {
+    /// Apache combined log format, followed by latency and MCP method.
+    #[default]
+    Combined,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct AccessLogArgs {
+    /// Write an access log line to stdout for every HTTP request
+    #[arg(long, env = "ACCESS_LOG", value_parser = BoolishValueParser::new())]
+    pub access_log: bool,
+
+    /// Access log line format
+    #[arg(long, value_enum, default_value_t, env = "ACCESS_LOG_FORMAT")]
+    pub access_log_format: AccessLogFormat,
+}
+
+pub async fn log(State(format): State<AccessLogFormat>, request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let version = request.version();
+    let client_ip = client_ip(&request).map(|ip| ip.to_string());
+    let user_agent = header_string(&request, header::USER_AGENT);
+    let referer = header_string(&request, header::REFERER);
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone());
+    let mcp_method = request
+        .extensions()
+        .get::<McpEnvelope>()
+        .and_then(|envelope| envelope.method.clone());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let bytes = response.body().size_hint().exact();
+    let now = chrono::Utc::now();
+
+    let line = match format {
+        AccessLogFormat::Combined => format!(
+            "{} - - [{}] \"{} {} {:?}\" {} {} \"{}\" \"{}\" {:.1}ms mcp={} request_id={}",
+            client_ip.as_deref().unwrap_or("-"),
+            now.format("%d/%b/%Y:%H:%M:%S %z"),
+            method,
+            path,
+            version,
+            status,
+            bytes.map_or_else(|| "-".to_string(), |b| b.to_string()),
+            referer.as_deref().unwrap_or("-"),
+            user_agent.as_deref().unwrap_or("-"),
+            latency_ms,
+            mcp_method.as_deref().unwrap_or("-"),
+            request_id.as_deref().unwrap_or("-"),
+        ),
+        AccessLogFormat::Json => json!({
+            "timestamp": now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "client_ip": client_ip,
+            "method": method.as_str(),
+            "path": path,
+            "status": status,
+            "bytes": bytes,
+            "latency_ms": latency_ms,
+            "user_agent": user_agent,
+            "referer": referer,
+            "mcp_method": mcp_method,
+            "request_id": request_id,
+        })
+        .to_string(),
+    };
+
+    let _ = writeln!(std::io::stdout().lock(), "{line}");
+    response
+}
+
+fn header_string(request: &Request, name: header::HeaderName) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
@@ -0,0 +1,179 @@
+//! HTTPS with certificates obtained and renewed from an ACME directory
+//! (Let's Encrypt by default), validated over HTTP-01. Clients negotiate
+//! HTTP/2 or HTTP/1.1 through ALPN.
+
+use axum::{
+    Router,
+    extract::Request,
+    http::{StatusCode, Uri, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use clap::Args;
+use futures::StreamExt;
+use rustls_acme::{AcmeConfig, UseChallenge, caches::DirCache};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Flags enabling automatic certificates.
+#[derive(Debug, Clone, Args)]
+pub struct AcmeArgs {
+    /// Serve HTTPS for this domain with an automatically managed certificate; repeat for more names
+    #[arg(
+        long,
+        value_name = "DOMAIN",
+        env = "ACME_DOMAIN",
+        value_delimiter = ','
+    )]
+    pub acme_domain: Vec<String>,
+
+    /// Contact address given to the ACME directory for expiry notices
+    #[arg(long, value_name = "EMAIL", env = "ACME_EMAIL")]
+    pub acme_email: Option<String>,
+
+    /// Directory keeping the account key and issued certificates across restarts
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = "acme-cache",
+        env = "ACME_CACHE"
+    )]
+    pub acme_cache: PathBuf,
+
+    /// Use Let's Encrypt's staging directory, whose certificates aren't trusted
+    /// but which has far looser rate limits
+    #[arg(long, env = "ACME_STAGING")]
+    pub acme_staging: bool,
+
+    /// Port the HTTPS listener binds when ACME is enabled
+    #[arg(long, value_name = "PORT", default_value_t = 443, env = "HTTPS_PORT")]
+    pub https_port: u16,
+
+    /// Port answering HTTP-01 challenges and redirecting everything else to HTTPS
+    #[arg(
+        long,
+        value_name = "PORT",
+        default_value_t = 80,
+        env = "ACME_HTTP_PORT"
+    )]
+    pub acme_http_port: u16,
+
+    /// Also serve HTTP/3 over QUIC on the HTTPS port's UDP side
+    /// (experimental), advertised to HTTPS clients with `Alt-Svc`
+    #[cfg(feature = "http3")]
+    #[arg(long, env = "HTTP3")]
+    pub http3: bool,
+}
+
+impl AcmeArgs {
+    pub fn enabled(&self) -> bool {
+        !self.acme_domain.is_empty()
+    }
+
+    /// Serves `app` over HTTPS until either listener fails, while a
+    /// background task keeps the certificate issued and renewed.
+    pub async fn serve(&self, app: Router) -> anyhow::Result<()> {
+        let mut state = AcmeConfig::new(&self.acme_domain)
+            .contact(
+                self.acme_email
+                    .iter()
+                    .map(|email| format!("mailto:{email}")),
+            )
+            .cache(DirCache::new(self.acme_cache.clone()))
+            .directory_lets_encrypt(!self.acme_staging)
+            .challenge_type(UseChallenge::Http01)
+            .state();
+        let tls = state.default_rustls_config();
+        let mut https_tls = (*tls).clone();
+        https_tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        let acceptor = state.axum_acceptor(Arc::new(https_tls));
+        let challenges = state.http01_challenge_tower_service();
+
+        tokio::spawn(async move {
+            while let Some(event) = state.next().await {
+                match event {
+                    Ok(event) => info!(?event, "acme"),
+                    Err(err) => error!(error = ?err, "acme"),
+                }
+            }
+        });
+
+        let https_port = self.https_port;
+        let http_app = Router::new()
+            .route_service("/.well-known/acme-challenge/:token", challenges)
+            .fallback(move |request: Request| async move { redirect(request, https_port) });
+
+        let https_addr = SocketAddr::from(([0, 0, 0, 0], self.https_port));
+        let http_addr = SocketAddr::from(([0, 0, 0, 0], self.acme_http_port));
+        info!(
+            domains = ?self.acme_domain,
+            https = %https_addr,
+            http = %http_addr,
+            staging = self.acme_staging,
+            http3 = self.http3(),
+            "serving HTTPS with ACME certificates"
+        );
+
+        #[cfg(feature = "http3")]
+        let app = match self.http3 {
+            true => super::http3::advertise(app, self.https_port),
+            false => app,
+        };
+
+        let https = axum_server::bind(https_addr).acceptor(acceptor).serve(
+            app.clone()
+                .into_make_service_with_connect_info::<SocketAddr>(),
+        );
+        let http = axum_server::bind(http_addr).serve(http_app.into_make_service());
+
+        #[cfg(feature = "http3")]
+        if self.http3 {
+            let http3 = super::http3::serve(https_addr, (*tls).clone(), app);
+            tokio::try_join!(async { Ok(https.await?) }, async { Ok(http.await?) }, http3)?;
+            return Ok(());
+        }
+        tokio::try_join!(https, http)?;
+        Ok(())
+    }
+
+    fn http3(&self) -> bool {
+        #[cfg(feature = "http3")]
+        return self.http3;
+        #[cfg(not(feature = "http3"))]
+        false
+    }
+}
+
+/// Sends plain-HTTP requests to the same path on the HTTPS listener.
+fn redirect(request: Request, https_port: u16) -> Response {
+    let Some(host) = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let authority = match https_port {
+        443 => host.to_string(),
+        port => format!("{host}:{port}"),
+    };
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+
+    match Uri::builder()
+        .scheme("https")
+        .authority(authority)
+        .path_and_query(path)
+        .build()
+    {
+        Ok(uri) => Redirect::permanent(&uri.to_string()).into_response(),
+        Err(_) => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
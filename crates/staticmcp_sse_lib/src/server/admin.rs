@@ -0,0 +1,171 @@
+//! Operator endpoints under `/admin`, served only when `--admin-token` is
+//! set and only to requests bearing that token.
+
+use super::cache::Cache;
+use super::response_cache::{ResponseCache, hit_rate};
+use axum::{
+    Json, Router,
+    extract::{Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use clap::Args;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Flags enabling the admin endpoints.
+#[derive(Debug, Clone, Args)]
+pub struct AdminArgs {
+    /// Serve `/admin` endpoints to requests with `Authorization: Bearer TOKEN`
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "ADMIN_TOKEN",
+        hide_env_values = true
+    )]
+    pub admin_token: Option<String>,
+}
+
+/// One bridge's caches, labelled for the admin listing.
+#[derive(Clone)]
+pub struct CacheRoute {
+    /// Where the bridge is served: `/`, a `--mount` prefix or a `--vhost` host.
+    pub route: String,
+    pub source: String,
+    /// Its `--cache-ttl` files.
+    pub cache: Option<Arc<Cache>>,
+    /// Its `--response-cache-ttl` results.
+    pub responses: Option<Arc<ResponseCache>>,
+}
+
+impl AdminArgs {
+    /// The `/admin` routes over `caches`, when `--admin-token` is set.
+    pub fn router(&self, caches: Vec<CacheRoute>) -> Option<Router> {
+        let token: Arc<str> = self.admin_token.as_deref()?.into();
+        Some(
+            Router::new()
+                .route("/admin/cache", get(cache_report).delete(purge_cache))
+                .with_state(Arc::new(caches))
+                .route_layer(middleware::from_fn_with_state(token, require_token)),
+        )
+    }
+
+    /// `router` served only to requests bearing the token when
+    /// `--admin-token` is set, and to any otherwise.
+    pub fn guard(&self, router: Router) -> Router {
+        match self.admin_token.as_deref() {
+            Some(token) => router.route_layer(middleware::from_fn_with_state(
+                Arc::<str>::from(token),
+                require_token,
+            )),
+            None => router,
+        }
+    }
+}
+
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if presented.is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes())) {
+        return next.run(request).await;
+    }
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(json!({ "error": "admin token required" })),
+    )
+        .into_response()
+}
+
+/// Compares without stopping at the first differing byte, so response times
+/// don't reveal how much of the token a guess got right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// `GET /admin/cache`: every bridge's cached files and results, and
+/// aggregate totals of each.
+async fn cache_report(State(caches): State<Arc<Vec<CacheRoute>>>) -> Json<Value> {
+    let mut totals = json!({
+        "entries": 0,
+        "bytes": 0,
+        "hits": 0,
+        "misses": 0,
+        "notFound": 0,
+        "notFoundHits": 0,
+    });
+    let mut response_totals = json!({
+        "entries": 0,
+        "bytes": 0,
+        "hits": 0,
+        "misses": 0,
+        "evictions": 0,
+    });
+    let caches: Vec<Value> = caches
+        .iter()
+        .map(|cache| {
+            let mut report = match &cache.cache {
+                Some(files) => {
+                    let report = files.report();
+                    add(&mut totals, &report["totals"]);
+                    report
+                }
+                None => json!({}),
+            };
+            if let Some(responses) = &cache.responses {
+                let responses = responses.report();
+                add(&mut response_totals, &responses);
+                report["responses"] = responses;
+            }
+            report["route"] = json!(cache.route);
+            report["source"] = json!(cache.source);
+            report
+        })
+        .collect();
+    for totals in [&mut totals, &mut response_totals] {
+        let (hits, misses) = (totals["hits"].as_u64(), totals["misses"].as_u64());
+        totals["hitRate"] = json!(hit_rate(hits.unwrap_or(0), misses.unwrap_or(0)));
+    }
+    totals["responses"] = response_totals;
+    Json(json!({ "caches": caches, "totals": totals }))
+}
+
+/// Adds each of `report`'s counts to the one of that name in `totals`.
+fn add(totals: &mut Value, report: &Value) {
+    for (key, total) in totals.as_object_mut().into_iter().flatten() {
+        let value = report[key].as_u64().unwrap_or(0);
+        *total = json!(total.as_u64().unwrap_or(0) + value);
+    }
+}
+
+#[derive(Deserialize)]
+struct PurgeParams {
+    prefix: Option<String>,
+}
+
+/// `DELETE /admin/cache[?prefix=tools/search]`: drops every bridge's cached
+/// files, or those whose path starts with `prefix`. Cached results can't be
+/// told apart by the files they came from, so all of them are dropped.
+async fn purge_cache(
+    State(caches): State<Arc<Vec<CacheRoute>>>,
+    Query(params): Query<PurgeParams>,
+) -> Json<Value> {
+    let purged: usize = caches
+        .iter()
+        .filter_map(|cache| cache.cache.as_ref())
+        .map(|cache| cache.purge(params.prefix.as_deref()))
+        .sum();
+    let responses: usize = caches
+        .iter()
+        .filter_map(|cache| cache.responses.as_ref())
+        .map(|responses| responses.purge())
+        .sum();
+    tracing::info!(purged, responses, prefix = ?params.prefix, "purged cache");
+    Json(json!({ "purged": purged, "responses": responses }))
+}
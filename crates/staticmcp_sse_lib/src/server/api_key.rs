@@ -0,0 +1,129 @@
+//! Static API keys, for callers handed a key by the operator rather than
+//! an OAuth token or a signing secret.
+//!
+//! A request carries its key in the `X-API-Key` header, and the id the key
+//! was given under with `--api-key ID=KEY` becomes the request's principal
+//! for access policies, the proxy's target restrictions and the audit log.
+
+use super::{envelope, principal};
+use crate::policy::Principal;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use clap::Args;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Flags enabling API key authentication.
+#[derive(Debug, Clone, Args)]
+pub struct ApiKeyArgs {
+    /// Accept requests whose `X-API-Key` header is this key, as `ID=KEY`;
+    /// repeat to give several callers their own keys or to rotate one
+    #[arg(
+        long,
+        value_name = "ID=KEY",
+        env = "API_KEYS",
+        value_delimiter = ',',
+        value_parser = parse_key,
+        hide_env_values = true
+    )]
+    pub api_key: Vec<(String, String)>,
+}
+
+impl ApiKeyArgs {
+    /// The key checker, when any `--api-key` is set. With `optional`,
+    /// requests without a key are passed on for another layer to
+    /// authenticate instead of being refused.
+    pub fn checker(&self, optional: bool) -> Option<Arc<Checker>> {
+        if self.api_key.is_empty() {
+            return None;
+        }
+        Some(Arc::new(Checker {
+            keys: self
+                .api_key
+                .iter()
+                .map(|(id, key)| (id.clone(), digest(key)))
+                .collect(),
+            optional,
+        }))
+    }
+}
+
+fn parse_key(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((id, key)) if !id.is_empty() && !key.is_empty() => {
+            Ok((id.to_string(), key.to_string()))
+        }
+        _ => Err("expected ID=KEY".into()),
+    }
+}
+
+/// Matches presented keys against the configured ones.
+pub struct Checker {
+    /// Each key's id and the SHA-256 of the key, so comparing digests of
+    /// equal length takes the same time whichever key was guessed at.
+    keys: Vec<(String, [u8; 32])>,
+    optional: bool,
+}
+
+impl Checker {
+    /// The id of `key`, when it is one of the configured keys.
+    fn identify(&self, key: &str) -> Option<&str> {
+        let presented = digest(key);
+        self.keys
+            .iter()
+            .find(|(_, digest)| {
+                digest
+                    .iter()
+                    .zip(&presented)
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+            })
+            .map(|(id, _)| id.as_str())
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// Rejects requests without a known key, unless another layer already
+/// authenticated them, and runs the rest as the key's [`Principal`].
+pub async fn require(
+    State(checker): State<Arc<Checker>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<Principal>().is_some() {
+        return next.run(request).await;
+    }
+    let presented = request
+        .headers()
+        .get(&API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let Some(presented) = presented else {
+        if checker.optional {
+            return next.run(request).await;
+        }
+        return reject(&request, "API key required");
+    };
+    let Some(id) = checker.identify(presented) else {
+        tracing::debug!("rejected unknown API key");
+        return reject(&request, "Invalid API key");
+    };
+    let caller = Principal {
+        subject: id.to_string(),
+        scopes: Vec::new(),
+    };
+    request.extensions_mut().insert(caller.clone());
+    principal::scope(caller, next.run(request)).await
+}
+
+fn reject(request: &Request, message: &str) -> Response {
+    envelope::of(request).reject(StatusCode::UNAUTHORIZED, -32000, message, None)
+}
@@ -0,0 +1,325 @@
+//! OAuth 2.0 protected-resource support from the MCP authorization spec.
+//!
+//! With `--auth-issuer`, the MCP endpoints require a bearer JWT issued by
+//! that authorization server for this server's `--auth-resource`. Clients
+//! without one get `401` and a `WWW-Authenticate` header pointing at the
+//! RFC 9728 metadata document served under `/.well-known/`, which names the
+//! authorization server to obtain a token from.
+
+use super::{envelope, principal};
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::Response,
+    routing::get,
+};
+use clap::Args;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+pub use crate::policy::Principal;
+
+/// A stale key set is refetched at most this often when a token names an
+/// unknown key, so forged `kid`s can't hammer the authorization server.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Flags enabling token validation.
+#[derive(Debug, Clone, Args)]
+pub struct AuthArgs {
+    /// Require access tokens issued by this OAuth authorization server on the MCP endpoints
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "AUTH_ISSUER",
+        requires = "auth_resource",
+        value_parser = parse_url
+    )]
+    pub auth_issuer: Option<String>,
+
+    /// Canonical URL of this server; tokens must name it as their audience
+    #[arg(long, value_name = "URL", env = "AUTH_RESOURCE", value_parser = parse_url)]
+    pub auth_resource: Option<String>,
+
+    /// Key set to verify tokens with, instead of the `jwks_uri` from the
+    /// issuer's metadata
+    #[arg(long, value_name = "URL", env = "AUTH_JWKS_URL")]
+    pub auth_jwks_url: Option<Url>,
+
+    /// Scope every token must carry; repeat to require several
+    #[arg(long, value_name = "SCOPE", env = "AUTH_SCOPE", value_delimiter = ',')]
+    pub auth_scope: Vec<String>,
+}
+
+impl AuthArgs {
+    /// The token validator, when `--auth-issuer` is set.
+    pub fn authenticator(&self) -> Option<Arc<Authenticator>> {
+        let issuer = self.auth_issuer.clone()?;
+        let resource = self.auth_resource.clone()?;
+        let resource_url = Url::parse(&resource).ok()?;
+        Some(Arc::new(Authenticator {
+            issuer,
+            resource,
+            resource_url,
+            jwks_url: self.auth_jwks_url.clone(),
+            scopes: self.auth_scope.clone(),
+            client: reqwest::Client::new(),
+            keys: RwLock::new(None),
+        }))
+    }
+
+    /// The protected-resource metadata route, to merge at the root of the
+    /// host, when `--auth-issuer` is set.
+    pub fn metadata_routes(&self) -> Option<Router> {
+        let authenticator = self.authenticator()?;
+        Some(Router::new().route(
+            &authenticator.metadata_path(),
+            get(move || async move { Json(authenticator.metadata()) }),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: Option<String>,
+    client_id: Option<String>,
+    #[serde(default)]
+    scope: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerMetadata {
+    jwks_uri: String,
+}
+
+/// Why a request was refused, mapped onto the RFC 6750 error codes.
+enum Rejection {
+    Missing,
+    Invalid(String),
+    InsufficientScope,
+}
+
+/// Validates bearer tokens against the issuer's published keys.
+pub struct Authenticator {
+    issuer: String,
+    resource: String,
+    resource_url: Url,
+    jwks_url: Option<Url>,
+    scopes: Vec<String>,
+    client: reqwest::Client,
+    keys: RwLock<Option<(JwkSet, Instant)>>,
+}
+
+impl Authenticator {
+    /// Path of the metadata document: RFC 9728 inserts the well-known
+    /// segment before the resource's own path.
+    fn metadata_path(&self) -> String {
+        let path = self.resource_url.path().trim_end_matches('/');
+        format!("/.well-known/oauth-protected-resource{path}")
+    }
+
+    fn metadata_url(&self) -> String {
+        let mut url = self.resource_url.clone();
+        url.set_path(&self.metadata_path());
+        url.set_query(None);
+        url.to_string()
+    }
+
+    fn metadata(&self) -> Value {
+        json!({
+            "resource": self.resource,
+            "authorization_servers": [self.issuer],
+            "bearer_methods_supported": ["header"],
+            "scopes_supported": self.scopes,
+        })
+    }
+
+    /// `jwks_uri` from RFC 8414 metadata, falling back to OpenID discovery.
+    async fn discover_jwks_url(&self) -> anyhow::Result<Url> {
+        if let Some(url) = &self.jwks_url {
+            return Ok(url.clone());
+        }
+        let issuer = self.issuer.trim_end_matches('/');
+        let mut last_error = None;
+        for suffix in [
+            "/.well-known/oauth-authorization-server",
+            "/.well-known/openid-configuration",
+        ] {
+            let response = self.client.get(format!("{issuer}{suffix}")).send().await;
+            match response.and_then(|r| r.error_for_status()) {
+                Ok(response) => {
+                    let metadata: ServerMetadata = response.json().await?;
+                    return Ok(Url::parse(&metadata.jwks_uri)?);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.map_or_else(
+            || anyhow::anyhow!("no authorization server metadata"),
+            Into::into,
+        ))
+    }
+
+    async fn fetch_keys(&self) -> anyhow::Result<JwkSet> {
+        let url = self.discover_jwks_url().await?;
+        let keys = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(keys)
+    }
+
+    /// Key for `kid`, refetching the key set when it is missing and the
+    /// cached set is old enough.
+    async fn key(&self, kid: Option<&str>) -> Result<DecodingKey, Rejection> {
+        let find = |keys: &JwkSet| match kid {
+            Some(kid) => keys.find(kid).cloned(),
+            None => keys.keys.first().cloned(),
+        };
+
+        let stale = {
+            let cached = self.keys.read().await;
+            match &*cached {
+                Some((keys, _)) if let Some(jwk) = find(keys) => {
+                    return DecodingKey::from_jwk(&jwk)
+                        .map_err(|e| Rejection::Invalid(e.to_string()));
+                }
+                Some((_, fetched)) => fetched.elapsed() >= JWKS_REFRESH_INTERVAL,
+                None => true,
+            }
+        };
+        if !stale {
+            return Err(Rejection::Invalid("unknown signing key".into()));
+        }
+
+        let keys = self.fetch_keys().await.map_err(|e| {
+            tracing::warn!(error = %e, "failed to fetch authorization server keys");
+            Rejection::Invalid("signing keys unavailable".into())
+        })?;
+        let jwk = find(&keys);
+        *self.keys.write().await = Some((keys, Instant::now()));
+        match jwk {
+            Some(jwk) => DecodingKey::from_jwk(&jwk).map_err(|e| Rejection::Invalid(e.to_string())),
+            None => Err(Rejection::Invalid("unknown signing key".into())),
+        }
+    }
+
+    async fn verify(&self, token: &str) -> Result<Principal, Rejection> {
+        let header =
+            jsonwebtoken::decode_header(token).map_err(|e| Rejection::Invalid(e.to_string()))?;
+        // Keys are public, so a shared-secret algorithm can only be a forgery.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(Rejection::Invalid("unsupported algorithm".into()));
+        }
+        let key = self.key(header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        // Tolerate a trailing-slash difference, which URL normalization
+        // easily introduces on either side.
+        validation.set_issuer(&[&self.issuer, self.issuer.trim_end_matches('/')]);
+        validation.set_audience(&[&self.resource, self.resource.trim_end_matches('/')]);
+        let claims = jsonwebtoken::decode::<Claims>(token, &key, &validation)
+            .map_err(|e| Rejection::Invalid(e.to_string()))?
+            .claims;
+
+        let scopes: Vec<String> = claims
+            .scope
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        if !self.scopes.iter().all(|required| scopes.contains(required)) {
+            return Err(Rejection::InsufficientScope);
+        }
+        let subject = claims
+            .sub
+            .or(claims.client_id)
+            .ok_or_else(|| Rejection::Invalid("token has no subject".into()))?;
+        Ok(Principal { subject, scopes })
+    }
+
+    fn challenge(&self, rejection: &Rejection) -> String {
+        let mut challenge = format!("Bearer resource_metadata=\"{}\"", self.metadata_url());
+        match rejection {
+            Rejection::Missing => {}
+            Rejection::Invalid(_) => challenge.push_str(", error=\"invalid_token\""),
+            Rejection::InsufficientScope => challenge.push_str(", error=\"insufficient_scope\""),
+        }
+        if !self.scopes.is_empty() {
+            challenge.push_str(&format!(", scope=\"{}\"", self.scopes.join(" ")));
+        }
+        challenge
+    }
+}
+
+fn parse_url(value: &str) -> Result<String, String> {
+    Url::parse(value).map_err(|e| e.to_string())?;
+    Ok(value.to_string())
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    let value = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
+
+/// Rejects requests without a valid access token and runs the rest with the
+/// token's [`Principal`] available through [`principal::current`]. Requests
+/// an outer layer already authenticated pass through.
+pub async fn require(
+    State(authenticator): State<Arc<Authenticator>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<Principal>().is_some() {
+        return next.run(request).await;
+    }
+
+    let result = match bearer_token(&request) {
+        Some(token) => authenticator.verify(token).await,
+        None => Err(Rejection::Missing),
+    };
+
+    let rejection = match result {
+        Ok(caller) => {
+            request.extensions_mut().insert(caller.clone());
+            return principal::scope(caller, next.run(request)).await;
+        }
+        Err(rejection) => rejection,
+    };
+
+    let (status, message) = match &rejection {
+        Rejection::Missing => (
+            StatusCode::UNAUTHORIZED,
+            "Authorization required".to_string(),
+        ),
+        Rejection::Invalid(reason) => {
+            tracing::debug!(%reason, "rejected access token");
+            (StatusCode::UNAUTHORIZED, "Invalid access token".to_string())
+        }
+        Rejection::InsufficientScope => (StatusCode::FORBIDDEN, "Insufficient scope".to_string()),
+    };
+    let mut response = envelope::of(&request).reject(status, -32000, message, None);
+    if let Ok(value) = HeaderValue::from_str(&authenticator.challenge(&rejection)) {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, value);
+    }
+    response
+}
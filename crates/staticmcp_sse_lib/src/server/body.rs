@@ -0,0 +1,71 @@
+//! The JSON-RPC body of a posted message, refused with a JSON-RPC error
+//! rather than axum's plain-text rejection, so MCP clients are told what
+//! went wrong in the protocol they speak.
+
+use super::envelope::{self, McpEnvelope};
+use crate::{MCPRequest, request};
+use async_trait::async_trait;
+use axum::{
+    Json,
+    extract::{FromRequest, Request, rejection::JsonRejection},
+    http::StatusCode,
+    response::Response,
+};
+use serde_json::{Value, json};
+
+/// Request extension reading bodies with [`request::parse`]'s lenient
+/// rules, set by `--lenient`.
+#[derive(Debug, Clone, Copy)]
+pub struct Lenient;
+
+/// Like [`Json`], refusing a body that isn't JSON with `-32700 Parse
+/// error` and a null id, and JSON that isn't a request with `-32600
+/// Invalid Request` naming the member at fault.
+pub struct JsonRpc(pub MCPRequest);
+
+#[async_trait]
+impl<S> FromRequest<S> for JsonRpc
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let envelope = envelope::of(&request);
+        let lenient = request.extensions().get::<Lenient>().is_some();
+        let rejection = match Json::<Value>::from_request(request, state).await {
+            Ok(Json(value)) => {
+                return request::parse(value, lenient).map(Self).map_err(|invalid| {
+                    let envelope = McpEnvelope {
+                        id: invalid.id.clone(),
+                        method: envelope.method,
+                    };
+                    envelope.reject(
+                        StatusCode::BAD_REQUEST,
+                        -32600,
+                        invalid.message.clone(),
+                        invalid.data(),
+                    )
+                });
+            }
+            Err(rejection) => rejection,
+        };
+        let detail = Some(json!({ "detail": rejection.body_text() }));
+        Err(match rejection {
+            JsonRejection::JsonSyntaxError(_) => McpEnvelope::default().reject(
+                StatusCode::BAD_REQUEST,
+                -32700,
+                "Parse error",
+                detail,
+            ),
+            JsonRejection::MissingJsonContentType(_) => envelope.reject(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                -32600,
+                "Invalid Request: expected Content-Type: application/json",
+                None,
+            ),
+            // A body too large or cut off.
+            rejection => envelope.reject(rejection.status(), -32600, "Invalid Request", detail),
+        })
+    }
+}
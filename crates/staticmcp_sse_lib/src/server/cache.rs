@@ -0,0 +1,478 @@
+//! In-memory cache of the files a bridge reads from its data source.
+//!
+//! [`CachedDataSource`] wraps a bridge's data source and keeps each file
+//! loaded through it for `--cache-ttl`, so repeated resource reads and tool
+//! calls skip the disk or network. With `--cache-stale-while-revalidate`,
+//! an expired file is still answered from memory for that much longer while
+//! a background fetch replaces it, so callers never wait on a slow source
+//! for a file they recently read. `--cache-not-found-ttl` also remembers
+//! files the source doesn't have, so a caller retrying unsupported arguments
+//! doesn't send a request upstream each time. The manifest is not cached, so
+//! readiness probes still reach the source.
+//!
+//! `--response-cache-ttl` keeps whole results as well, in a
+//! [`ResponseCache`].
+
+use super::progress::Progress;
+use super::response_cache::{ResponseCache, hit_rate};
+use crate::chunked::ByteStream;
+use crate::metrics::BridgeMetrics;
+use crate::{MCPBridge, MCPDataSource, MCPManifest, RequestContext, is_not_found};
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use futures::{StreamExt, stream};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Flags enabling the cache and filling it at startup.
+#[derive(Debug, Clone, Args)]
+pub struct CacheArgs {
+    /// Keep files read from the source in memory for this long; unset
+    /// disables the cache
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        env = "CACHE_TTL"
+    )]
+    pub cache_ttl: Option<Duration>,
+
+    /// Keep answering with an expired file for this long after its TTL while
+    /// it is refetched in the background
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "0s",
+        value_parser = humantime::parse_duration,
+        env = "CACHE_STALE_WHILE_REVALIDATE"
+    )]
+    pub cache_stale_while_revalidate: Duration,
+
+    /// Remember for this long that a file doesn't exist, answering requests
+    /// for it without asking the source; `0s` disables it
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "0s",
+        value_parser = humantime::parse_duration,
+        env = "CACHE_NOT_FOUND_TTL"
+    )]
+    pub cache_not_found_ttl: Duration,
+
+    /// After loading the manifest, fetch every resource and argument-less
+    /// tool file into the cache before serving
+    #[arg(long, env = "PRELOAD", value_parser = BoolishValueParser::new())]
+    pub preload: bool,
+
+    /// Files fetched at once while preloading
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 8,
+        env = "PRELOAD_CONCURRENCY"
+    )]
+    pub preload_concurrency: usize,
+
+    /// Keep whole `resources/read` and `tools/call` results for this long,
+    /// answering repeated requests without reading or reshaping their files;
+    /// unset disables it
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        env = "RESPONSE_CACHE_TTL"
+    )]
+    pub response_cache_ttl: Option<Duration>,
+
+    /// Most bytes of results each bridge's response cache keeps, dropping
+    /// the oldest past it
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 64 * 1024 * 1024,
+        env = "RESPONSE_CACHE_MAX_BYTES"
+    )]
+    pub response_cache_max_bytes: usize,
+}
+
+impl CacheArgs {
+    /// A new, empty cache for one bridge, when `--cache-ttl` is set.
+    pub fn cache(&self) -> Option<Arc<Cache>> {
+        self.cache_ttl.map(|ttl| {
+            Arc::new(Cache::new(
+                ttl,
+                self.cache_stale_while_revalidate,
+                self.cache_not_found_ttl,
+            ))
+        })
+    }
+
+    /// A new, empty response cache for one bridge, when
+    /// `--response-cache-ttl` is set.
+    pub fn response_cache(&self) -> Option<Arc<ResponseCache>> {
+        self.response_cache_ttl
+            .map(|ttl| Arc::new(ResponseCache::new(ttl, self.response_cache_max_bytes)))
+    }
+}
+
+struct Entry {
+    value: Value,
+    stored: Instant,
+    /// Length of the serialized value.
+    bytes: usize,
+    hits: u64,
+    /// A background refresh of this stale entry is under way.
+    refreshing: bool,
+}
+
+/// What the cache holds for a path.
+enum Lookup {
+    Fresh(Value),
+    /// Past its TTL but within the stale window; `refresh` is set for the
+    /// one caller that should fetch the replacement.
+    Stale {
+        value: Value,
+        refresh: bool,
+    },
+    Miss,
+}
+
+/// Remembered missing files are swept once there are more than this many.
+const NOT_FOUND_SWEEP_THRESHOLD: usize = 10_000;
+
+/// Files loaded from one data source, by path.
+pub struct Cache {
+    ttl: Duration,
+    stale_while_revalidate: Duration,
+    not_found_ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Paths the source reported missing, with when it did.
+    not_found: Mutex<HashMap<String, Instant>>,
+    misses: AtomicU64,
+    not_found_hits: AtomicU64,
+}
+
+impl Cache {
+    pub fn new(ttl: Duration, stale_while_revalidate: Duration, not_found_ttl: Duration) -> Self {
+        Self {
+            ttl,
+            stale_while_revalidate,
+            not_found_ttl,
+            entries: Mutex::new(HashMap::new()),
+            not_found: Mutex::new(HashMap::new()),
+            misses: AtomicU64::new(0),
+            not_found_hits: AtomicU64::new(0),
+        }
+    }
+
+    fn not_found(&self) -> std::sync::MutexGuard<'_, HashMap<String, Instant>> {
+        self.not_found.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `path` was recently reported missing.
+    fn known_missing(&self, path: &str) -> bool {
+        let missing = self
+            .not_found()
+            .get(path)
+            .is_some_and(|since| since.elapsed() < self.not_found_ttl);
+        if missing {
+            self.not_found_hits.fetch_add(1, Ordering::Relaxed);
+        }
+        missing
+    }
+
+    fn insert_not_found(&self, path: &str) {
+        if self.not_found_ttl.is_zero() {
+            return;
+        }
+        let mut not_found = self.not_found();
+        if not_found.len() > NOT_FOUND_SWEEP_THRESHOLD {
+            not_found.retain(|_, since| since.elapsed() < self.not_found_ttl);
+        }
+        not_found.insert(path.to_string(), Instant::now());
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn get(&self, path: &str) -> Lookup {
+        let mut entries = self.entries();
+        let Some(entry) = entries.get_mut(path) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return Lookup::Miss;
+        };
+        let age = entry.stored.elapsed();
+        if age >= self.ttl + self.stale_while_revalidate {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return Lookup::Miss;
+        }
+        entry.hits += 1;
+        if age < self.ttl {
+            return Lookup::Fresh(entry.value.clone());
+        }
+        let refresh = !entry.refreshing;
+        entry.refreshing = true;
+        Lookup::Stale {
+            value: entry.value.clone(),
+            refresh,
+        }
+    }
+
+    /// Lets a later caller retry the refresh that just failed.
+    fn refresh_failed(&self, path: &str) {
+        if let Some(entry) = self.entries().get_mut(path) {
+            entry.refreshing = false;
+        }
+    }
+
+    fn insert(&self, path: &str, value: Value) {
+        let (hits, bytes) = {
+            let entries = self.entries();
+            let hits = entries.get(path).map_or(0, |entry| entry.hits);
+            (hits, value.to_string().len())
+        };
+        let entry = Entry {
+            value,
+            stored: Instant::now(),
+            bytes,
+            hits,
+            refreshing: false,
+        };
+        self.entries().insert(path.to_string(), entry);
+    }
+
+    /// Drops the entries whose path starts with `prefix`, or all of them,
+    /// returning how many were dropped.
+    pub fn purge(&self, prefix: Option<&str>) -> usize {
+        // Locked in the same order as `report`.
+        let mut entries = self.entries();
+        let mut not_found = self.not_found();
+        let before = entries.len() + not_found.len();
+        match prefix {
+            Some(prefix) => {
+                entries.retain(|path, _| !path.starts_with(prefix));
+                not_found.retain(|path, _| !path.starts_with(prefix));
+            }
+            None => {
+                entries.clear();
+                not_found.clear();
+            }
+        }
+        before - entries.len() - not_found.len()
+    }
+
+    /// Each entry's path, size, age and hits, most hit first, with totals.
+    pub fn report(&self) -> Value {
+        let entries = self.entries();
+        let mut listed: Vec<(&String, &Entry)> = entries.iter().collect();
+        listed.sort_by(|a, b| b.1.hits.cmp(&a.1.hits).then_with(|| a.0.cmp(b.0)));
+        let hits: u64 = listed.iter().map(|(_, entry)| entry.hits).sum();
+        let misses = self.misses.load(Ordering::Relaxed);
+        let bytes: usize = listed.iter().map(|(_, entry)| entry.bytes).sum();
+        json!({
+            "ttlSeconds": self.ttl.as_secs_f64(),
+            "entries": listed
+                .iter()
+                .map(|(key, entry)| {
+                    let age = entry.stored.elapsed();
+                    json!({
+                        "key": key,
+                        "bytes": entry.bytes,
+                        "ageSeconds": age.as_secs(),
+                        "fresh": age < self.ttl,
+                        "hits": entry.hits,
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "totals": {
+                "entries": listed.len(),
+                "bytes": bytes,
+                "hits": hits,
+                "misses": misses,
+                "hitRate": hit_rate(hits, misses),
+                "notFound": self.not_found().len(),
+                "notFoundHits": self.not_found_hits.load(Ordering::Relaxed),
+            },
+        })
+    }
+}
+
+/// A data source answering from a [`Cache`] while its entries are fresh.
+/// Entries are kept by path alone, so a source answering each caller
+/// differently should not be wrapped in one.
+pub struct CachedDataSource {
+    inner: Arc<dyn MCPDataSource>,
+    cache: Arc<Cache>,
+    metrics: Option<Arc<dyn BridgeMetrics>>,
+}
+
+impl CachedDataSource {
+    pub fn new(inner: Box<dyn MCPDataSource>, cache: Arc<Cache>) -> Self {
+        Self {
+            inner: inner.into(),
+            cache,
+            metrics: None,
+        }
+    }
+
+    /// The source reporting each lookup to `metrics`, as a hit or a miss.
+    pub fn with_metrics(mut self, metrics: Arc<dyn BridgeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn hit(&self, path: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.cache_hit(path);
+        }
+    }
+
+    /// Refetches `path` in the background for the request `context`
+    /// describes, without its deadline, keeping the stale entry when that
+    /// fails.
+    fn revalidate(&self, path: &str, context: &RequestContext) {
+        let (inner, cache, path) = (self.inner.clone(), self.cache.clone(), path.to_string());
+        let context = RequestContext {
+            deadline: None,
+            ..context.clone()
+        };
+        tokio::spawn(async move {
+            match inner.load_json(&path, &context).await {
+                Ok(value) => cache.insert(&path, value),
+                Err(e) => {
+                    tracing::warn!(path, error = %e, "failed to revalidate cached file");
+                    cache.refresh_failed(&path);
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl MCPDataSource for CachedDataSource {
+    async fn load_json(
+        &self,
+        relative_path: &str,
+        context: &RequestContext,
+    ) -> anyhow::Result<Value> {
+        match self.cache.get(relative_path) {
+            Lookup::Fresh(value) => {
+                tracing::trace!(path = relative_path, "cache hit");
+                self.hit(relative_path);
+                return Ok(value);
+            }
+            Lookup::Stale { value, refresh } => {
+                tracing::trace!(path = relative_path, refresh, "stale cache hit");
+                self.hit(relative_path);
+                if refresh {
+                    self.revalidate(relative_path, context);
+                }
+                return Ok(value);
+            }
+            Lookup::Miss => {}
+        }
+        if self.cache.known_missing(relative_path) {
+            tracing::trace!(path = relative_path, "cached not found");
+            self.hit(relative_path);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{relative_path} not found"),
+            )
+            .into());
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.cache_miss(relative_path);
+        }
+        let value = match self.inner.load_json(relative_path, context).await {
+            Ok(value) => value,
+            Err(e) => {
+                if is_not_found(&e) {
+                    self.cache.insert_not_found(relative_path);
+                }
+                return Err(e);
+            }
+        };
+        self.cache.insert(relative_path, value.clone());
+        Ok(value)
+    }
+
+    async fn load_manifest(&self) -> anyhow::Result<MCPManifest> {
+        self.inner.load_manifest().await
+    }
+
+    /// Raw files aren't cached.
+    async fn load_bytes(&self, relative_path: &str) -> anyhow::Result<Bytes> {
+        self.inner.load_bytes(relative_path).await
+    }
+
+    async fn stream_bytes(&self, relative_path: &str) -> anyhow::Result<ByteStream> {
+        self.inner.stream_bytes(relative_path).await
+    }
+
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        self.inner.list(prefix).await
+    }
+}
+
+/// Loads every resource and argument-less tool file the bridge's manifest
+/// declares, at most `concurrency` at once, so a [`CachedDataSource`] holds
+/// them before the first request, logging progress as it goes. Files that
+/// fail to load are logged and skipped.
+pub async fn preload(bridge: &MCPBridge, concurrency: usize) {
+    let Some(capabilities) = bridge
+        .get_manifest()
+        .and_then(|manifest| manifest.capabilities.as_ref())
+    else {
+        return;
+    };
+    let resources = capabilities
+        .resources
+        .iter()
+        .flatten()
+        .map(|resource| (resource, bridge.uri_to_path(&resource.uri)))
+        .filter(|(resource, path)| bridge.raw_resource_file(&resource.uri, path).is_none())
+        .map(|(_, path)| path);
+    let tools = capabilities
+        .tools
+        .iter()
+        .flatten()
+        .filter(|tool| tool.required().next().is_none())
+        .filter_map(|tool| bridge.tool_to_path(&tool.name, &HashMap::new()).ok());
+    let paths: Vec<String> = resources.chain(tools).collect();
+
+    let mut progress = Progress::new(paths.len());
+    let mut loads = stream::iter(&paths)
+        .map(|path| async move {
+            let result = bridge
+                .data_source
+                .load_json(path, &RequestContext::default())
+                .await;
+            if let Err(e) = &result {
+                tracing::warn!(path, error = %e, "failed to preload");
+            }
+            result.is_ok()
+        })
+        .buffer_unordered(concurrency.max(1));
+    while let Some(loaded) = loads.next().await {
+        if progress.record(loaded.then_some(0)) {
+            tracing::info!(
+                loaded = progress.loaded,
+                failed = progress.failed,
+                total = progress.total,
+                "preloading cache"
+            );
+        }
+    }
+    tracing::info!(
+        loaded = progress.loaded,
+        failed = progress.failed,
+        elapsed_ms = progress.elapsed().as_millis() as u64,
+        "preloaded cache"
+    );
+}
@@ -0,0 +1,66 @@
+//! Resource reads answered in chunks over SSE, for files above
+//! `--stream-threshold`.
+//!
+//! The response is one JSON-RPC message, as clients expect, sent as an SSE
+//! `message` event with a `data:` line per `contents` entry. Readers join
+//! an event's lines with newlines, which JSON allows between tokens, so the
+//! message parses as usual while only one chunk of the file is in memory at
+//! a time.
+
+use crate::chunked::ChunkedContents;
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use futures::{StreamExt, stream};
+use serde_json::Value;
+
+/// Whether the client accepts an SSE answer to its POST.
+pub fn accepted(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|range| range.split(';').next().unwrap_or_default().trim() == "text/event-stream")
+}
+
+/// The response to request `id`, its `contents` read and sent one entry at
+/// a time. A file that fails partway ends the stream without the message's
+/// closing line, so the client sees the read fail rather than a short file.
+pub fn sse(id: Option<Value>, contents: ChunkedContents) -> Response {
+    let uri = contents.uri.clone();
+    let id = serde_json::to_string(&id).unwrap_or_else(|_| "null".to_string());
+    // The message up to its `contents` entries.
+    let open = format!(
+        "event: message\ndata: {{\"jsonrpc\":\"2.0\",\"id\":{id},\"result\":{{\"contents\":[\n"
+    );
+    let entries = contents
+        .entries()
+        .enumerate()
+        .map(move |(index, entry)| match entry {
+            Ok(entry) => {
+                let separator = if index == 0 { "" } else { "," };
+                Ok(format!("data: {separator}{entry}\n"))
+            }
+            Err(e) => {
+                tracing::warn!(uri, error = %e, "failed to stream resource");
+                Err(e)
+            }
+        });
+    let body = stream::once(async { Ok(open) })
+        .chain(entries)
+        .chain(stream::once(async { Ok("data: ]}}\n\n".to_string()) }));
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/event-stream"),
+            ),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
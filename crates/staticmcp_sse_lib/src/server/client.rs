@@ -0,0 +1,27 @@
+//! The MCP client whose request is being handled, as its session's
+//! `initialize` described it, for logs, audit records and data sources
+//! that tailor their answers.
+
+use crate::client::ClientInfo;
+use axum::http::HeaderName;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Names the client, as `name/version`, on the requests remote sites are
+/// fetched with.
+pub const MCP_CLIENT_HEADER: HeaderName = HeaderName::from_static("x-mcp-client");
+
+tokio::task_local! {
+    static CURRENT: Arc<ClientInfo>;
+}
+
+/// Client of the request whose handler is currently running, when its
+/// session's `initialize` named one.
+pub fn current() -> Option<Arc<ClientInfo>> {
+    CURRENT.try_with(Arc::clone).ok()
+}
+
+/// Runs `future` with `client` available through [`current`].
+pub async fn scope<F: Future>(client: Arc<ClientInfo>, future: F) -> F::Output {
+    CURRENT.scope(client, future).await
+}
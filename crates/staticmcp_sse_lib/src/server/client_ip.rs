@@ -0,0 +1,365 @@
+//! Resolves the address of the client behind a request, and filters
+//! requests by it.
+
+use super::envelope;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use clap::Args;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Flags controlling which clients may connect and how their address is
+/// found behind reverse proxies.
+#[derive(Debug, Clone, Args)]
+pub struct ClientIpArgs {
+    /// Only serve clients in this range, e.g. `10.8.0.0/16`; repeat for more
+    #[arg(long, value_name = "CIDR", env = "ALLOW_IP", value_delimiter = ',')]
+    pub allow_ip: Vec<Cidr>,
+
+    /// Refuse clients in this range, even when an allowed range contains them
+    #[arg(long, value_name = "CIDR", env = "DENY_IP", value_delimiter = ',')]
+    pub deny_ip: Vec<Cidr>,
+
+    /// Reverse proxy whose `X-Forwarded-For` names the real client; repeat for
+    /// each hop
+    #[arg(
+        long,
+        value_name = "CIDR",
+        env = "TRUSTED_PROXIES",
+        value_delimiter = ','
+    )]
+    pub trusted_proxy: Vec<Cidr>,
+}
+
+impl ClientIpArgs {
+    /// The resolution and filtering rules, when any flag is set.
+    pub fn rules(&self) -> Option<Arc<ClientIpRules>> {
+        if self.allow_ip.is_empty() && self.deny_ip.is_empty() && self.trusted_proxy.is_empty() {
+            return None;
+        }
+        Some(Arc::new(ClientIpRules {
+            allow: self.allow_ip.clone(),
+            deny: self.deny_ip.clone(),
+            trusted_proxies: self.trusted_proxy.clone(),
+        }))
+    }
+}
+
+/// An address range such as `192.168.0.0/16` or `fd00::/8`; a bare address
+/// is a range of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), self.prefix)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_matches(&network.octets(), &ip.octets(), self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = match value.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (value, None),
+        };
+        let network = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("`{address}` is not an IP address"))?
+            .to_canonical();
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| format!("`{prefix}` is not a prefix length from 0 to {max}"))?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+fn prefix_matches(network: &[u8], ip: &[u8], prefix: u8) -> bool {
+    let (bytes, bits) = (usize::from(prefix / 8), prefix % 8);
+    if network[..bytes] != ip[..bytes] {
+        return false;
+    }
+    bits == 0 || (network[bytes] ^ ip[bytes]) >> (8 - bits) == 0
+}
+
+/// Client address resolved by [`resolve`], taking precedence over the peer
+/// address.
+#[derive(Debug, Clone, Copy)]
+struct ClientIp(IpAddr);
+
+pub struct ClientIpRules {
+    allow: Vec<Cidr>,
+    deny: Vec<Cidr>,
+    trusted_proxies: Vec<Cidr>,
+}
+
+impl ClientIpRules {
+    fn trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// Walks `X-Forwarded-For` from the nearest hop while each hop is a
+    /// trusted proxy, so a client can't spoof its address by sending the
+    /// header itself.
+    fn client_behind(&self, peer: IpAddr, request: &Request) -> IpAddr {
+        if !self.trusted(peer) {
+            return peer;
+        }
+        let hops: Vec<&str> = request
+            .headers()
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            let hop = hop.trim().trim_start_matches('[').trim_end_matches(']');
+            let Ok(ip) = hop.parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !self.trusted(ip) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Whether [`filter`] has anything to enforce.
+    pub fn filters(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    fn permits(&self, ip: IpAddr) -> bool {
+        if self.deny.iter().any(|cidr| cidr.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+/// Address of the client: the one [`resolve`] found behind trusted proxies,
+/// else the peer address of the connection, when the server was started with
+/// connect info.
+pub fn client_ip(request: &Request) -> Option<IpAddr> {
+    if let Some(ClientIp(ip)) = request.extensions().get::<ClientIp>() {
+        return Some(*ip);
+    }
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Records the client address found behind any trusted proxies for the
+/// layers below.
+pub async fn resolve(
+    State(rules): State<Arc<ClientIpRules>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(peer) = client_ip(&request) {
+        let client = rules.client_behind(peer, &request);
+        request.extensions_mut().insert(ClientIp(client));
+    }
+    next.run(request).await
+}
+
+/// Answers `403` to clients outside `--allow-ip` or inside `--deny-ip`.
+/// Clients whose address can't be determined are refused too.
+pub async fn filter(
+    State(rules): State<Arc<ClientIpRules>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match client_ip(&request) {
+        Some(ip) if rules.permits(ip) => next.run(request).await,
+        ip => {
+            tracing::debug!(client_ip = ?ip, "refusing client address");
+            envelope::of(&request).reject(StatusCode::FORBIDDEN, -32000, "Forbidden", None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidr(value: &str) -> Cidr {
+        value.parse().unwrap()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn rules(trusted: &[&str]) -> ClientIpRules {
+        ClientIpRules {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            trusted_proxies: trusted.iter().map(|value| cidr(value)).collect(),
+        }
+    }
+
+    fn forwarded(headers: &[&str]) -> Request {
+        let mut request = Request::builder();
+        for value in headers {
+            request = request.header(X_FORWARDED_FOR, *value);
+        }
+        request.body(axum::body::Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn parses_ranges_and_bare_addresses() {
+        assert_eq!(
+            cidr("10.8.0.0/16"),
+            Cidr {
+                network: ip("10.8.0.0"),
+                prefix: 16
+            }
+        );
+        assert_eq!(
+            cidr("192.0.2.7"),
+            Cidr {
+                network: ip("192.0.2.7"),
+                prefix: 32
+            }
+        );
+        assert_eq!(
+            cidr("fd00::/8"),
+            Cidr {
+                network: ip("fd00::"),
+                prefix: 8
+            }
+        );
+        assert_eq!(
+            cidr("::1"),
+            Cidr {
+                network: ip("::1"),
+                prefix: 128
+            }
+        );
+        assert_eq!(cidr("::ffff:10.0.0.1"), cidr("10.0.0.1"));
+        assert_eq!(cidr("0.0.0.0/0").prefix, 0);
+    }
+
+    #[test]
+    fn refuses_bad_addresses_and_prefixes() {
+        for value in [
+            "",
+            "10.8.0.0/",
+            "10.8.0.0/33",
+            "fd00::/129",
+            "10.8/16",
+            "host/8",
+            "10.0.0.0/-1",
+            "10.0.0.0/x",
+        ] {
+            assert!(value.parse::<Cidr>().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn contains_addresses_sharing_the_prefix() {
+        let range = cidr("10.8.0.0/16");
+        assert!(range.contains(ip("10.8.0.1")));
+        assert!(range.contains(ip("10.8.255.255")));
+        assert!(!range.contains(ip("10.9.0.1")));
+        assert!(!range.contains(ip("::1")));
+
+        let odd = cidr("192.168.4.0/22");
+        assert!(odd.contains(ip("192.168.7.255")));
+        assert!(!odd.contains(ip("192.168.8.0")));
+        assert!(!odd.contains(ip("192.168.3.255")));
+
+        let v6 = cidr("fd00::/8");
+        assert!(v6.contains(ip("fdab::1")));
+        assert!(!v6.contains(ip("fe80::1")));
+        assert!(!v6.contains(ip("10.0.0.1")));
+
+        assert!(cidr("0.0.0.0/0").contains(ip("203.0.113.9")));
+        assert!(!cidr("0.0.0.0/0").contains(ip("2001:db8::1")));
+        assert!(cidr("192.0.2.7").contains(ip("192.0.2.7")));
+        assert!(!cidr("192.0.2.7").contains(ip("192.0.2.8")));
+    }
+
+    #[test]
+    fn contains_ipv4_mapped_addresses() {
+        assert!(cidr("10.8.0.0/16").contains(ip("::ffff:10.8.1.2")));
+        assert!(!cidr("10.8.0.0/16").contains(ip("::ffff:10.9.1.2")));
+    }
+
+    #[test]
+    fn ignores_forwarded_for_from_untrusted_peers() {
+        let rules = rules(&["10.0.0.0/8"]);
+        let request = forwarded(&["203.0.113.9"]);
+        assert_eq!(
+            rules.client_behind(ip("198.51.100.1"), &request),
+            ip("198.51.100.1")
+        );
+    }
+
+    #[test]
+    fn walks_forwarded_for_while_hops_are_trusted() {
+        let rules = rules(&["10.0.0.0/8"]);
+        let peer = ip("10.0.0.2");
+        assert_eq!(
+            rules.client_behind(peer, &forwarded(&["203.0.113.9"])),
+            ip("203.0.113.9")
+        );
+        assert_eq!(
+            rules.client_behind(peer, &forwarded(&["203.0.113.9, 10.0.0.3"])),
+            ip("203.0.113.9")
+        );
+        assert_eq!(
+            rules.client_behind(peer, &forwarded(&["203.0.113.9", "10.0.0.3"])),
+            ip("203.0.113.9")
+        );
+        assert_eq!(
+            rules.client_behind(peer, &forwarded(&["[2001:db8::7], 10.0.0.3"])),
+            ip("2001:db8::7")
+        );
+    }
+
+    #[test]
+    fn stops_at_the_first_untrusted_hop() {
+        let rules = rules(&["10.0.0.0/8"]);
+        let peer = ip("10.0.0.2");
+        let spoofed = forwarded(&["192.0.2.66, 203.0.113.9, 10.0.0.3"]);
+        assert_eq!(rules.client_behind(peer, &spoofed), ip("203.0.113.9"));
+        let garbled = forwarded(&["203.0.113.9, unknown, 10.0.0.3"]);
+        assert_eq!(rules.client_behind(peer, &garbled), ip("10.0.0.3"));
+        assert_eq!(rules.client_behind(peer, &forwarded(&[])), peer);
+        assert_eq!(
+            rules.client_behind(peer, &forwarded(&["10.0.0.3"])),
+            ip("10.0.0.3")
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Cap on MCP requests in flight, shedding the excess immediately.
+
+use super::envelope;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Answers with a fast `-32000 Server busy` when every permit is taken rather
+/// than queueing, so bursts can't pile up sockets and memory. A permit is
+/// held until the response head is ready; open SSE streams don't count.
+pub async fn limit(
+    State(permits): State<Arc<Semaphore>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match permits.clone().try_acquire_owned() {
+        Ok(_permit) => next.run(request).await,
+        Err(_) => {
+            tracing::debug!("shedding request, concurrency limit reached");
+            let mut response = envelope::of(&request).reject(
+                StatusCode::SERVICE_UNAVAILABLE,
+                -32000,
+                "Server busy",
+                None,
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+            response
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Peeks at JSON-RPC bodies so layers running before the handler can see
+//! which MCP call a request carries.
+
+use crate::MCPError;
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Same cap axum's `Json` extractor applies by default.
+pub const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// The routing-relevant fields of a JSON-RPC request body, stored in the
+/// request extensions. Fields are `None` when the body isn't a JSON object.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct McpEnvelope {
+    #[serde(default)]
+    pub id: Option<Value>,
+    #[serde(default)]
+    pub method: Option<String>,
+}
+
+impl McpEnvelope {
+    /// A JSON-RPC error reply for this request, for layers that answer
+    /// before the handler runs.
+    pub fn reject(
+        &self,
+        status: StatusCode,
+        code: i32,
+        message: impl Into<String>,
+        data: Option<Value>,
+    ) -> Response {
+        let mut error = MCPError {
+            code,
+            message: message.into(),
+            data,
+        };
+        if let Some(request_id) = super::request_id::current() {
+            error.attach_request_id(request_id);
+        }
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "error": error,
+        });
+        (status, axum::Json(body)).into_response()
+    }
+}
+
+/// Envelope of the current request, or an empty one for bodiless requests.
+pub fn of(request: &Request) -> McpEnvelope {
+    request
+        .extensions()
+        .get::<McpEnvelope>()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Middleware buffering `POST` bodies to insert an [`McpEnvelope`]; the body
+/// is handed on unchanged.
+pub async fn capture(request: Request, next: Next) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    let envelope = serde_json::from_slice::<McpEnvelope>(&bytes).unwrap_or_default();
+    let mut request = Request::from_parts(parts, Body::from(bytes));
+    request.extensions_mut().insert(envelope);
+    next.run(request).await
+}
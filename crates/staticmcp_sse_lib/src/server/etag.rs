@@ -0,0 +1,203 @@
+//! `ETag`s and `If-None-Match` revalidation for answers read from static
+//! files.
+
+use super::{envelope, principal};
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Methods whose results come straight from an immutable file.
+const CACHEABLE_METHODS: [&str; 2] = ["resources/read", "tools/call"];
+
+/// Response bodies larger than this are passed through untagged.
+const MAX_TAGGED_BYTES: usize = 16 * 1024 * 1024;
+
+/// How long tagged answers may be cached, and by whom.
+#[derive(Debug, Clone, Copy)]
+pub struct Caching {
+    pub max_age: Duration,
+    /// Answers may differ per caller, so shared caches must not keep them.
+    pub private: bool,
+}
+
+/// Tags successful `resources/read` and `tools/call` responses with an `ETag`
+/// over their `result` and answers a matching `If-None-Match` with `304`.
+///
+/// The JSON-RPC `id` is left out of the hash, so the same file gives the
+/// same tag whichever request id asked for it.
+pub async fn conditional(State(caching): State<Caching>, request: Request, next: Next) -> Response {
+    let cacheable = envelope::of(&request)
+        .method
+        .is_some_and(|method| CACHEABLE_METHODS.contains(&method.as_str()));
+    if !cacheable {
+        return next.run(request).await;
+    }
+
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+    // Reads answered in chunks are never buffered to be hashed.
+    let streamed = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+    // Bodies of unknown or oversize length are never buffered either.
+    let oversize = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_none_or(|upper| upper > MAX_TAGGED_BYTES as u64);
+    if response.status() != StatusCode::OK || streamed || oversize {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_TAGGED_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let Some(etag) = etag_for(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let private = caching.private || principal::current().is_some();
+    let cache_control = cache_control(caching.max_age, private);
+    if if_none_match.is_some_and(|value| matches(&value, &etag)) {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ETAG, etag);
+        headers.insert(header::CACHE_CONTROL, cache_control);
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    parts.headers.insert(header::ETAG, etag);
+    parts.headers.insert(header::CACHE_CONTROL, cache_control);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Strong tag for a JSON-RPC response body, or `None` when it carries an
+/// error rather than file contents.
+fn etag_for(body: &[u8]) -> Option<HeaderValue> {
+    let response: Value = serde_json::from_slice(body).ok()?;
+    let result = response.get("result")?;
+    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+
+    let digest = Sha256::digest(serde_json::to_vec(result).ok()?);
+    let hex: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+    HeaderValue::from_str(&format!("\"{hex}\"")).ok()
+}
+
+/// Weak comparison of `If-None-Match` against `etag`, as RFC 9110 requires.
+fn matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(candidates) = if_none_match.to_str() else {
+        return false;
+    };
+    let etag = etag.to_str().unwrap_or_default();
+    candidates
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+fn cache_control(max_age: Duration, private: bool) -> HeaderValue {
+    let value = match (max_age.is_zero(), private) {
+        (true, false) => "no-cache".to_string(),
+        (true, true) => "private, no-cache".to_string(),
+        (false, false) => format!("public, max-age={}", max_age.as_secs()),
+        (false, true) => format!("private, max-age={}", max_age.as_secs()),
+    };
+    HeaderValue::from_str(&value).expect("cache-control is ascii")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, middleware, routing::post};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    const READ: &str = r#"{"jsonrpc":"2.0","id":1,"method":"resources/read"}"#;
+
+    fn app(text: String, private: bool) -> Router {
+        let caching = Caching {
+            max_age: Duration::from_secs(60),
+            private,
+        };
+        let answer = json!({"jsonrpc": "2.0", "id": 1, "result": {"contents": [{"text": text}]}});
+        Router::new()
+            .route("/", post(move || async move { axum::Json(answer) }))
+            .layer(middleware::from_fn_with_state(caching, conditional))
+            .layer(middleware::from_fn(envelope::capture))
+    }
+
+    fn read(if_none_match: Option<&HeaderValue>) -> Request {
+        let mut request = Request::post("/");
+        if let Some(etag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        request.body(Body::from(READ)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn tags_answers_and_revalidates_them() {
+        let app = app("hello".into(), false);
+        let response = app.clone().oneshot(read(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=60"
+        );
+        let etag = response.headers()[header::ETAG].clone();
+
+        let response = app.oneshot(read(Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag);
+    }
+
+    #[tokio::test]
+    async fn passes_oversize_answers_through_untagged() {
+        let text = "x".repeat(MAX_TAGGED_BYTES + 1);
+        let response = app(text, false).oneshot(read(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::ETAG));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let answer: Value = serde_json::from_slice(&body).unwrap();
+        let text = answer["result"]["contents"][0]["text"].as_str().unwrap();
+        assert_eq!(text.len(), MAX_TAGGED_BYTES + 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_authenticated_answers_out_of_shared_caches() {
+        let response = app("hello".into(), true).oneshot(read(None)).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, max-age=60"
+        );
+
+        let caller = principal::Principal {
+            subject: "ci".into(),
+            scopes: Vec::new(),
+        };
+        let app = app("hello".into(), false);
+        let response = principal::scope(caller, app.oneshot(read(None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, max-age=60"
+        );
+    }
+
+    #[test]
+    fn sends_no_cache_without_a_max_age() {
+        assert_eq!(cache_control(Duration::ZERO, false), "no-cache");
+        assert_eq!(cache_control(Duration::ZERO, true), "private, no-cache");
+    }
+}
@@ -0,0 +1,168 @@
+//! `--record` and `--replay`: every message and its answer written to a
+//! directory, and answered from there again without reading the site, for
+//! deterministic integration tests and for going over an agent's session
+//! offline.
+//!
+//! Each answer is a file named after the method and a hash of the bridge it
+//! was sent to, the method and the params, so asking the same thing again
+//! finds it whatever the id or the order of the params' keys.
+
+use crate::{MCPError, MCPRequest, MCPResponse};
+use anyhow::Context;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Flags recording messages to, or replaying them from, a fixture
+/// directory.
+#[derive(Debug, Clone, Args)]
+pub struct FixtureArgs {
+    /// Write every message and its answer to a file in DIR, for `--replay`
+    #[arg(long, value_name = "DIR", env = "RECORD", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Answer messages from the files `--record` wrote to DIR, without
+    /// reading the site; a message never recorded gets JSON-RPC error
+    /// `-32603`
+    #[arg(long, value_name = "DIR", env = "REPLAY")]
+    pub replay: Option<PathBuf>,
+}
+
+impl FixtureArgs {
+    /// The fixture directory, when recording or replaying; recording
+    /// creates it.
+    pub fn fixtures(&self) -> anyhow::Result<Option<Arc<Fixtures>>> {
+        if let Some(dir) = &self.replay {
+            anyhow::ensure!(
+                dir.is_dir(),
+                "--replay: {} is not a directory",
+                dir.display()
+            );
+            return Ok(Some(Arc::new(Fixtures {
+                dir: dir.clone(),
+                replay: true,
+            })));
+        }
+        let Some(dir) = &self.record else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("--record: failed to create {}", dir.display()))?;
+        Ok(Some(Arc::new(Fixtures {
+            dir: dir.clone(),
+            replay: false,
+        })))
+    }
+}
+
+/// A message and its answer as kept on disk.
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    /// The bridge the message was sent to, such as a mount or a site URL.
+    scope: String,
+    method: String,
+    params: Value,
+    response: MCPResponse,
+}
+
+/// A fixture directory being recorded to or replayed from.
+pub struct Fixtures {
+    dir: PathBuf,
+    replay: bool,
+}
+
+impl Fixtures {
+    /// Whether answers come from `--replay`, so the sites needn't be read.
+    pub fn replaying(&self) -> bool {
+        self.replay
+    }
+
+    /// The answer to `request` sent to the bridge named `scope`: the
+    /// recorded one when replaying, otherwise what `live` answers, written
+    /// down when it was asked with an id.
+    pub async fn answer<F, Fut>(&self, scope: &str, request: MCPRequest, live: F) -> MCPResponse
+    where
+        F: FnOnce(MCPRequest) -> Fut,
+        Fut: Future<Output = MCPResponse>,
+    {
+        let params = request.params.clone().unwrap_or(Value::Null);
+        let path = self.path(scope, &request.method, &params);
+        if self.replay {
+            return replay(&path, request.id).await;
+        }
+
+        let method = request.method.clone();
+        let notification = request.id.is_none();
+        let response = live(request).await;
+        if notification {
+            return response;
+        }
+        let fixture = Fixture {
+            scope: scope.to_string(),
+            method,
+            params,
+            response,
+        };
+        if let Err(e) = write(&path, &fixture).await {
+            warn!(fixture = %path.display(), error = %e, "failed to record answer");
+        }
+        fixture.response
+    }
+
+    /// `{dir}/{method}-{hash}.json`, with anything but letters and digits in
+    /// the method written as `_`.
+    fn path(&self, scope: &str, method: &str, params: &Value) -> PathBuf {
+        // Object keys serialize sorted, so equal params hash alike.
+        let key = json!([scope, method, params]).to_string();
+        let hash = hex(&Sha256::digest(key.as_bytes())[..8]);
+        let method: String = method
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{method}-{hash}.json"))
+    }
+}
+
+async fn write(path: &Path, fixture: &Fixture) -> anyhow::Result<()> {
+    let json = serde_json::to_vec_pretty(fixture)?;
+    tokio::fs::write(path, json).await?;
+    debug!(fixture = %path.display(), "recorded answer");
+    Ok(())
+}
+
+/// The answer recorded at `path`, sent back under `id`.
+async fn replay(path: &Path, id: Option<Value>) -> MCPResponse {
+    let recorded = match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice::<Fixture>(&bytes)
+            .with_context(|| format!("{} is not a recorded answer", path.display())),
+        Err(e) => Err(anyhow::Error::from(e)),
+    };
+    match recorded {
+        Ok(fixture) => MCPResponse {
+            id,
+            ..fixture.response
+        },
+        Err(e) => {
+            warn!(fixture = %path.display(), error = %e, "no recorded answer");
+            MCPResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(MCPError {
+                    code: -32603,
+                    message: "No recorded answer".to_string(),
+                    data: None,
+                }),
+            }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
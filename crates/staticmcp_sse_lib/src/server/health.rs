@@ -0,0 +1,39 @@
+//! Liveness and readiness probes.
+
+use crate::MCPBridge;
+use axum::{Json, extract::State, http::StatusCode};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long `/readyz` waits for the data source before reporting unready.
+pub const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `GET /healthz`: the process is up and serving HTTP.
+pub async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// `GET /readyz`: the manifest is loaded and the data source still answers.
+pub async fn readyz(State(bridge): State<Arc<MCPBridge>>) -> (StatusCode, Json<Value>) {
+    if bridge.get_manifest().is_none() {
+        return unready("Manifest not loaded");
+    }
+
+    match tokio::time::timeout(READINESS_TIMEOUT, bridge.data_source.load_manifest()).await {
+        Ok(Ok(_)) => (StatusCode::OK, Json(json!({ "status": "ready" }))),
+        Ok(Err(e)) => unready(&format!("Data source unreachable: {e}")),
+        Err(_) => unready(&format!(
+            "Data source did not respond within {}s",
+            READINESS_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+fn unready(reason: &str) -> (StatusCode, Json<Value>) {
+    tracing::warn!(reason, "readiness check failed");
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({ "status": "unready", "reason": reason })),
+    )
+}
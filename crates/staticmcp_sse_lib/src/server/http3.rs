@@ -0,0 +1,106 @@
+//! `--http3`: an experimental HTTP/3 listener on the HTTPS port's UDP side,
+//! answering with the same router and ACME certificates as the HTTPS
+//! listener, which advertises it to clients with `Alt-Svc`.
+//!
+//! Each event stream is a QUIC stream of its own, so a lost packet holds up
+//! only the stream it belonged to rather than every stream on the
+//! connection, as it does over HTTP/2's single TCP connection.
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{ConnectInfo, Request},
+    http::{HeaderValue, header},
+    response::Response,
+};
+use bytes::{Buf, Bytes};
+use futures::StreamExt;
+use h3::server::RequestResolver;
+use rustls_acme::rustls::ServerConfig;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+use tracing::debug;
+
+/// Adds an `Alt-Svc` header to `app`'s responses, telling clients they can
+/// switch to HTTP/3 on UDP `port`.
+pub fn advertise(app: Router, port: u16) -> Router {
+    let alt_svc = HeaderValue::from_str(&format!("h3=\":{port}\"; ma=86400"))
+        .expect("a port makes a valid header");
+    app.layer(axum::middleware::map_response(
+        move |mut response: Response| {
+            let alt_svc = alt_svc.clone();
+            async move {
+                response.headers_mut().insert(header::ALT_SVC, alt_svc);
+                response
+            }
+        },
+    ))
+}
+
+/// Serves `app` over HTTP/3 on UDP `addr` until the endpoint fails, with
+/// `tls` minus its ALPN protocols, which QUIC replaces with `h3`.
+pub async fn serve(addr: SocketAddr, mut tls: ServerConfig, app: Router) -> anyhow::Result<()> {
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+    let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls)?;
+    let config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+    let endpoint = quinn::Endpoint::server(config, addr)?;
+
+    while let Some(incoming) = endpoint.accept().await {
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(err) = connection(incoming, app).await {
+                debug!(error = %err, "http3 connection closed");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answers the requests of one QUIC connection, each on a task of its own.
+async fn connection(incoming: quinn::Incoming, app: Router) -> anyhow::Result<()> {
+    let remote = incoming.remote_address();
+    let quic = h3_quinn::Connection::new(incoming.await?);
+    let mut connection = h3::server::Connection::<_, Bytes>::new(quic).await?;
+    while let Some(resolver) = connection.accept().await? {
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(err) = request(resolver, remote, app).await {
+                debug!(error = %err, "http3 request failed");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Runs one request through `app`, streaming the body each way so event
+/// streams are sent as their events come.
+async fn request(
+    resolver: RequestResolver<h3_quinn::Connection, Bytes>,
+    remote: SocketAddr,
+    app: Router,
+) -> anyhow::Result<()> {
+    let (request, stream) = resolver.resolve_request().await?;
+    let (mut send, recv) = stream.split();
+
+    let body = futures::stream::unfold(Some(recv), |recv| async move {
+        let mut recv = recv?;
+        match recv.recv_data().await {
+            Ok(Some(mut data)) => Some((Ok(data.copy_to_bytes(data.remaining())), Some(recv))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err), None)),
+        }
+    });
+    let (parts, ()) = request.into_parts();
+    let mut request = Request::from_parts(parts, Body::from_stream(body));
+    request.extensions_mut().insert(ConnectInfo(remote));
+
+    let (parts, body) = app.oneshot(request).await?.into_parts();
+    send.send_response(Response::from_parts(parts, ())).await?;
+    let mut body = body.into_data_stream();
+    while let Some(data) = body.next().await {
+        send.send_data(data?).await?;
+    }
+    send.finish().await?;
+    Ok(())
+}
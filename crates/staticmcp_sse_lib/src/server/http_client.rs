@@ -0,0 +1,65 @@
+//! The HTTP client remote sites are fetched with, one per process so every
+//! bridge shares its connection pool, tuned by flags for the hosts behind
+//! it: a CDN holding many connections open, or a load balancer dropping
+//! idle ones.
+
+use clap::Args;
+use std::time::Duration;
+
+/// Flags tuning the connection pool of [`HttpClientArgs::client`].
+#[derive(Debug, Clone, Args)]
+pub struct HttpClientArgs {
+    /// Most idle connections kept open to each site's host; unset keeps as
+    /// many as were used
+    #[arg(long, value_name = "N", env = "HTTP_POOL_MAX_IDLE_PER_HOST")]
+    pub http_pool_max_idle_per_host: Option<usize>,
+
+    /// How long an idle connection to a site is kept open; `0s` keeps it
+    /// until the host closes it
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "90s",
+        value_parser = humantime::parse_duration,
+        env = "HTTP_POOL_IDLE_TIMEOUT"
+    )]
+    pub http_pool_idle_timeout: Duration,
+
+    /// Send TCP keepalive probes on connections to sites after this long
+    /// idle, so middleboxes don't drop them silently
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        env = "HTTP_TCP_KEEPALIVE"
+    )]
+    pub http_tcp_keepalive: Option<Duration>,
+
+    /// Ping HTTP/2 connections to sites this often, idle ones included,
+    /// closing those that don't answer within 20s
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        env = "HTTP2_KEEPALIVE_INTERVAL"
+    )]
+    pub http2_keepalive_interval: Option<Duration>,
+}
+
+impl HttpClientArgs {
+    /// A client configured as the flags say. Clones share its pool.
+    pub fn client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .pool_idle_timeout(Some(self.http_pool_idle_timeout).filter(|idle| !idle.is_zero()))
+            .tcp_keepalive(self.http_tcp_keepalive);
+        if let Some(max) = self.http_pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.http2_keepalive_interval {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        Ok(builder.build()?)
+    }
+}
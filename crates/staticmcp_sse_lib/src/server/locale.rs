@@ -0,0 +1,48 @@
+//! The locales the caller of the request being handled asked for, from a
+//! `locale` query parameter or else its `Accept-Language` header.
+
+use crate::locale::accept_language;
+use axum::{
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+tokio::task_local! {
+    static CURRENT: Vec<String>;
+}
+
+/// Locales requested by the request whose handler is currently running,
+/// best first; empty when it asked for none.
+pub fn current() -> Vec<String> {
+    CURRENT.try_with(Vec::clone).unwrap_or_default()
+}
+
+fn requested(request: &Request) -> Vec<String> {
+    let query = request.uri().query().unwrap_or_default();
+    let from_query: Vec<String> = form_urlencoded::parse(query.as_bytes())
+        .filter(|(key, value)| key == "locale" && !value.is_empty())
+        .map(|(_, value)| value.into_owned())
+        .collect();
+    if !from_query.is_empty() {
+        return from_query;
+    }
+    request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(accept_language)
+        .unwrap_or_default()
+}
+
+/// Makes the request's locales available through [`current`] while it is
+/// handled. Answers depend on `Accept-Language`, so caches are told so.
+pub async fn scope(request: Request, next: Next) -> Response {
+    let locales = requested(&request);
+    let mut response = CURRENT.scope(locales, next.run(request)).await;
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-language"));
+    response
+}
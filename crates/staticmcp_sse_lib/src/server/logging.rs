@@ -0,0 +1,84 @@
+use clap::{Args, ValueEnum};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable, colored when stderr is a terminal.
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log pipelines.
+    Json,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct LoggingArgs {
+    /// Log output format
+    #[arg(long, value_enum, default_value_t, env = "LOG_FORMAT")]
+    pub log_format: LogFormat,
+}
+
+/// Flushes buffered trace exports when dropped; keep it alive for the
+/// lifetime of `main`.
+#[must_use]
+pub struct LoggingGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for LoggingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+impl LoggingArgs {
+    /// Installs the global subscriber. Filtering follows `RUST_LOG`, defaulting to `info`.
+    pub fn init(&self) -> LoggingGuard {
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+        #[cfg(feature = "otel")]
+        let (provider, otel_error) = if super::telemetry::export_enabled() {
+            match super::telemetry::tracer_provider() {
+                Ok(provider) => (Some(provider), None),
+                Err(e) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+        #[cfg(feature = "otel")]
+        let otel_layer = provider.as_ref().map(super::telemetry::layer);
+        #[cfg(not(feature = "otel"))]
+        let otel_layer: Option<tracing_subscriber::layer::Identity> = None;
+
+        let registry = tracing_subscriber::registry().with(otel_layer).with(filter);
+        match self.log_format {
+            LogFormat::Pretty => registry
+                .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+                .init(),
+            LogFormat::Json => registry
+                .with(
+                    tracing_subscriber::fmt::layer()
+                        .json()
+                        .with_current_span(true)
+                        .with_span_list(false)
+                        .with_writer(std::io::stderr),
+                )
+                .init(),
+        }
+
+        #[cfg(feature = "otel")]
+        if let Some(e) = otel_error {
+            tracing::warn!(error = %e, "OTLP export disabled");
+        }
+
+        LoggingGuard {
+            #[cfg(feature = "otel")]
+            provider,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Explicit `OPTIONS` and `HEAD` answers for the MCP routes, and a JSON-RPC
+//! `405` for any other method they don't serve, so browsers and probes get
+//! the same answer whatever the route's handler would do.
+
+use super::envelope;
+use axum::{
+    Router,
+    extract::{Request, State},
+    handler::Handler,
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{MethodRouter, get, post},
+};
+use std::sync::Arc;
+use tower::{Layer, ServiceExt};
+use tower_http::cors::CorsLayer;
+
+/// What the `/sse` routes answer.
+pub const MESSAGE_METHODS: &str = "POST, HEAD, OPTIONS";
+
+/// What the `/events` routes answer.
+pub const EVENT_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// A message route: `handler` for `POST`, and `HEAD` answered `200`
+/// without running it.
+pub fn messages<H, T, S>(handler: H) -> MethodRouter<S>
+where
+    H: Handler<T, S>,
+    T: 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    answering(
+        post(handler).head(|| async { allow(StatusCode::OK, MESSAGE_METHODS) }),
+        MESSAGE_METHODS,
+    )
+}
+
+/// An event stream route: `handler` for `GET`, and `HEAD` answered with
+/// the stream's content type without opening a session.
+pub fn events<H, T, S>(handler: H) -> MethodRouter<S>
+where
+    H: Handler<T, S>,
+    T: 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    answering(
+        get(handler).head(|| async {
+            (
+                [(header::CONTENT_TYPE, "text/event-stream")],
+                allow(StatusCode::OK, EVENT_METHODS),
+            )
+        }),
+        EVENT_METHODS,
+    )
+}
+
+/// `route` answering `OPTIONS` with `204` and the `Allow` list, and other
+/// methods it lacks with `405`.
+pub fn answering<S>(route: MethodRouter<S>, methods: &'static str) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route
+        .options(move || async move { allow(StatusCode::NO_CONTENT, methods) })
+        .fallback(not_allowed)
+}
+
+fn allow(status: StatusCode, methods: &'static str) -> Response {
+    (status, [(header::ALLOW, HeaderValue::from_static(methods))]).into_response()
+}
+
+/// `405`, with the route's `Allow` list added by axum.
+async fn not_allowed(request: Request) -> Response {
+    envelope::of(&request).reject(
+        StatusCode::METHOD_NOT_ALLOWED,
+        -32600,
+        format!("Method {} not allowed", request.method()),
+        None,
+    )
+}
+
+/// Wraps `router` in `cors` for CORS preflights only: tower-http answers
+/// every `OPTIONS` request itself, which would hide the routes' own
+/// answers from clients that aren't browsers.
+pub fn with_cors<S>(router: Router<S>, cors: CorsLayer) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(middleware::from_fn_with_state(Arc::new(cors), preflight))
+}
+
+async fn preflight(State(cors): State<Arc<CorsLayer>>, request: Request, next: Next) -> Response {
+    if request.method() == Method::OPTIONS
+        && !request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        return next.run(request).await;
+    }
+    match cors.layer(next).oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
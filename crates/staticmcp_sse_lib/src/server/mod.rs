@@ -0,0 +1,446 @@
+//! Building blocks shared by the bridge binaries.
+
+pub mod access_log;
+#[cfg(feature = "acme")]
+pub mod acme;
+pub mod admin;
+pub mod api_key;
+#[cfg(feature = "auth")]
+pub mod auth;
+pub mod body;
+pub mod cache;
+pub mod chunked;
+pub mod client;
+pub mod client_ip;
+pub mod concurrency;
+pub mod envelope;
+pub mod etag;
+pub mod fixtures;
+pub mod health;
+#[cfg(feature = "http3")]
+pub mod http3;
+#[cfg(feature = "remote")]
+pub mod http_client;
+pub mod locale;
+pub mod logging;
+pub mod methods;
+pub mod paths;
+pub mod principal;
+pub mod progress;
+pub mod protocol;
+pub mod rate_limit;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod request_id;
+pub mod response_cache;
+pub mod router;
+pub mod runtime;
+#[cfg(feature = "search")]
+pub mod search;
+pub mod service;
+pub mod session;
+pub mod signature;
+pub mod stats;
+pub mod status;
+pub mod telemetry;
+pub mod timeout;
+pub mod vhost;
+
+pub use access_log::{AccessLogArgs, AccessLogFormat};
+#[cfg(feature = "acme")]
+pub use acme::AcmeArgs;
+pub use admin::AdminArgs;
+pub use api_key::ApiKeyArgs;
+#[cfg(feature = "auth")]
+pub use auth::AuthArgs;
+pub use cache::CacheArgs;
+pub use client_ip::ClientIpArgs;
+pub use envelope::McpEnvelope;
+pub use fixtures::FixtureArgs;
+#[cfg(feature = "remote")]
+pub use http_client::HttpClientArgs;
+pub use logging::{LogFormat, LoggingArgs, LoggingGuard};
+pub use paths::PathArgs;
+pub use rate_limit::RateLimitArgs;
+pub use request_id::RequestId;
+pub use runtime::RuntimeArgs;
+#[cfg(feature = "search")]
+pub use search::SearchArgs;
+pub use service::BridgeService;
+pub use signature::SignatureArgs;
+pub use vhost::VirtualHosts;
+
+use crate::audit::{AuditSink, JsonlAuditLog};
+use crate::policy::AccessPolicy;
+use axum::{
+    Extension, Router, extract::NestedPath, http::HeaderValue, middleware, response::Redirect,
+    routing::get,
+};
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tower_http::compression::CompressionLayer;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Flags common to every HTTP-serving binary.
+#[derive(Debug, Clone, Args)]
+pub struct ServerArgs {
+    /// Address to listen on, such as `127.0.0.1:3000`; overrides `--host` and the port
+    #[arg(long, value_name = "ADDR", env = "BIND")]
+    pub bind: Option<String>,
+
+    /// Interface to listen on when `--bind` isn't given
+    #[arg(long, value_name = "HOST", default_value = "0.0.0.0", env = "HOST")]
+    pub host: String,
+
+    /// Mount every route under this prefix, e.g. `/mcp-bridge`, for serving
+    /// behind a reverse proxy that forwards a sub-path
+    #[arg(long, value_name = "PATH", env = "BASE_PATH", value_parser = parse_base_path)]
+    pub base_path: Option<String>,
+
+    #[command(flatten)]
+    pub logging: LoggingArgs,
+
+    #[command(flatten)]
+    pub access_log: AccessLogArgs,
+
+    #[command(flatten)]
+    pub rate_limit: RateLimitArgs,
+
+    #[command(flatten)]
+    pub client_ip: ClientIpArgs,
+
+    #[cfg(feature = "acme")]
+    #[command(flatten)]
+    pub acme: AcmeArgs,
+
+    #[cfg(feature = "auth")]
+    #[command(flatten)]
+    pub auth: AuthArgs,
+
+    #[command(flatten)]
+    pub signature: SignatureArgs,
+
+    #[command(flatten)]
+    pub api_keys: ApiKeyArgs,
+
+    #[command(flatten)]
+    pub paths: PathArgs,
+
+    #[command(flatten)]
+    pub fixtures: FixtureArgs,
+
+    #[cfg(feature = "remote")]
+    #[command(flatten)]
+    pub http_client: HttpClientArgs,
+
+    /// Time allowed to answer an MCP request before it fails with a JSON-RPC error
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "30s",
+        value_parser = humantime::parse_duration,
+        env = "REQUEST_TIMEOUT"
+    )]
+    pub request_timeout: Duration,
+
+    /// How long clients and caches may reuse a tagged resource or tool result
+    /// without revalidating; `0s` sends `Cache-Control: no-cache`
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "0s",
+        value_parser = humantime::parse_duration,
+        env = "CACHE_MAX_AGE"
+    )]
+    pub cache_max_age: Duration,
+
+    /// MCP requests handled at once; further requests are rejected as busy
+    #[arg(long, value_name = "REQUESTS", env = "MAX_CONCURRENT_REQUESTS")]
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Accept messages with a missing or numeric `jsonrpc`, params sent as a
+    /// JSON string or wrapped in an array, logging a warning for each
+    #[arg(long, env = "LENIENT", value_parser = BoolishValueParser::new())]
+    pub lenient: bool,
+
+    /// Append a JSONL audit record for every tool call and resource read
+    #[arg(long, value_name = "PATH", env = "AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    /// JSON access policy restricting which authenticated principals may call
+    /// which tools and read which resources
+    #[arg(long, value_name = "PATH", env = "ACCESS_POLICY")]
+    pub access_policy: Option<PathBuf>,
+
+    /// Browser origin allowed to call the bridge, such as
+    /// `https://app.example.com`; repeat for more. Any origin is allowed when
+    /// none is given
+    #[arg(
+        long,
+        value_name = "ORIGIN",
+        env = "CORS_ALLOW_ORIGIN",
+        value_delimiter = ','
+    )]
+    pub cors_allow_origin: Vec<HeaderValue>,
+
+    /// How long browsers may cache a CORS preflight answer, sent as
+    /// `Access-Control-Max-Age`; `0s` leaves the header out
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "10m",
+        value_parser = humantime::parse_duration,
+        env = "CORS_MAX_AGE"
+    )]
+    pub cors_max_age: Duration,
+
+    /// Send responses uncompressed even when the client accepts gzip, br or zstd
+    #[arg(long, env = "NO_COMPRESSION", value_parser = BoolishValueParser::new())]
+    pub no_compression: bool,
+}
+
+impl ServerArgs {
+    /// Opens the `--audit-log` file, if one was given.
+    pub fn audit_sink(&self) -> std::io::Result<Option<Arc<dyn AuditSink>>> {
+        match &self.audit_log {
+            Some(path) => Ok(Some(Arc::new(JsonlAuditLog::open(path)?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the `--access-policy` file, if one was given.
+    pub fn access_policy(&self) -> anyhow::Result<Option<Arc<AccessPolicy>>> {
+        let Some(path) = &self.access_policy else {
+            return Ok(None);
+        };
+        let policy = AccessPolicy::load(path)
+            .map_err(|e| anyhow::anyhow!("failed to load {}: {e}", path.display()))?;
+        if !self.authenticates() {
+            tracing::warn!(
+                "--access-policy without authentication: only items no rule names are reachable"
+            );
+        }
+        Ok(Some(Arc::new(policy)))
+    }
+
+    /// Whether the MCP endpoints require callers to authenticate, with a
+    /// bearer token, a request signature or an API key.
+    pub fn authenticates(&self) -> bool {
+        self.bearer_auth()
+            || !self.signature.hmac_key.is_empty()
+            || !self.api_keys.api_key.is_empty()
+    }
+
+    /// Whether `--auth-issuer` makes the MCP endpoints accept bearer tokens.
+    fn bearer_auth(&self) -> bool {
+        #[cfg(feature = "auth")]
+        return self.auth.auth_issuer.is_some();
+        #[cfg(not(feature = "auth"))]
+        false
+    }
+
+    /// Wraps the MCP message and event routes in the layers that only apply
+    /// to MCP traffic, such as rate limiting.
+    pub fn apply_mcp(&self, router: Router) -> Router {
+        let caching = etag::Caching {
+            max_age: self.cache_max_age,
+            private: self.authenticates(),
+        };
+        let mut router = router.layer(middleware::from_fn_with_state(caching, etag::conditional));
+        if !self.paths.locales.is_empty() {
+            router = router.layer(middleware::from_fn(locale::scope));
+        }
+        if self.lenient {
+            router = router.layer(Extension(body::Lenient));
+        }
+        router = router.layer(middleware::from_fn(protocol::header));
+        // Inside authentication, so callers are limited per principal.
+        if let Some(limiter) = self.rate_limit.limiter() {
+            router = router.layer(middleware::from_fn_with_state(limiter, rate_limit::limit));
+        }
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = self.auth.authenticator() {
+            router = router.layer(middleware::from_fn_with_state(authenticator, auth::require));
+        }
+        if let Some(checker) = self.api_keys.checker(self.bearer_auth()) {
+            router = router.layer(middleware::from_fn_with_state(checker, api_key::require));
+        }
+        let signature_optional = self.bearer_auth() || !self.api_keys.api_key.is_empty();
+        if let Some(verifier) = self.signature.verifier(signature_optional) {
+            router = router.layer(middleware::from_fn_with_state(verifier, signature::require));
+        }
+        router = router.layer(middleware::from_fn_with_state(
+            self.request_timeout,
+            timeout::limit,
+        ));
+        if let Some(max) = self.max_concurrent_requests {
+            router = router.layer(middleware::from_fn_with_state(
+                Arc::new(Semaphore::new(max)),
+                concurrency::limit,
+            ));
+        }
+        router
+    }
+
+    /// Wraps `router`, with every route merged in, in the shared middleware
+    /// stack, nested under `--base-path` if one was given.
+    pub fn apply(&self, router: Router) -> Router {
+        let mut router = match &self.base_path {
+            Some(base_path) if !base_path.is_empty() => {
+                nest(Router::new().fallback(status::not_found), base_path, router)
+            }
+            _ => router,
+        };
+        #[cfg(feature = "auth")]
+        if let Some(metadata) = self.auth.metadata_routes() {
+            router = router.merge(metadata.layer(CorsLayer::permissive()));
+        }
+        let client_ip_rules = self.client_ip.rules();
+        if let Some(rules) = client_ip_rules.clone().filter(|rules| rules.filters()) {
+            router = router.layer(middleware::from_fn_with_state(rules, client_ip::filter));
+        }
+        if self.access_log.access_log {
+            router = router.layer(middleware::from_fn_with_state(
+                self.access_log.access_log_format,
+                access_log::log,
+            ));
+        }
+        if !self.no_compression {
+            router = router.layer(compression());
+        }
+        if let Some(rules) = client_ip_rules {
+            router = router.layer(middleware::from_fn_with_state(rules, client_ip::resolve));
+        }
+        router
+            .layer(middleware::from_fn(envelope::capture))
+            .layer(middleware::from_fn(request_id::assign))
+    }
+
+    /// CORS for the bridge routes: open to every origin unless
+    /// `--cors-allow-origin` narrows it, for [`methods::with_cors`].
+    pub fn cors(&self) -> CorsLayer {
+        let mut cors = CorsLayer::permissive();
+        if !self.cors_allow_origin.is_empty() {
+            cors = cors.allow_origin(AllowOrigin::list(self.cors_allow_origin.clone()));
+        }
+        if !self.cors_max_age.is_zero() {
+            cors = cors.max_age(self.cors_max_age);
+        }
+        cors
+    }
+
+    /// The `--base-path` prefix, or `""` when routes are served from the root.
+    pub fn base_path(&self) -> &str {
+        self.base_path.as_deref().unwrap_or_default()
+    }
+
+    /// The `host:port` to listen on: `--bind` if given, else `--host` with
+    /// the binary's port.
+    pub fn address(&self, port: u16) -> String {
+        match &self.bind {
+            Some(bind) => bind.clone(),
+            None if self.host.contains(':') && !self.host.starts_with('[') => {
+                format!("[{}]:{port}", self.host)
+            }
+            None => format!("{}:{port}", self.host),
+        }
+    }
+
+    /// Absolute URL of `path` on this server, for startup logs.
+    pub fn url(&self, port: u16, path: &str) -> String {
+        #[cfg(feature = "acme")]
+        if let Some(domain) = self.acme.acme_domain.first() {
+            let base_path = self.base_path();
+            return match self.acme.https_port {
+                443 => format!("https://{domain}{base_path}{path}"),
+                https_port => format!("https://{domain}:{https_port}{base_path}{path}"),
+            };
+        }
+
+        let address = self.address(port);
+        let authority = match address.rsplit_once(':') {
+            Some(("0.0.0.0" | "[::]", port)) => format!("localhost:{port}"),
+            _ => address,
+        };
+        format!("http://{authority}{}{path}", self.base_path())
+    }
+
+    /// Serves `app` on [`Self::address`] until the listener fails, or over
+    /// HTTPS on the ACME ports when `--acme-domain` is given.
+    pub async fn serve(&self, app: Router, port: u16) -> anyhow::Result<()> {
+        #[cfg(feature = "acme")]
+        if self.acme.enabled() {
+            return self.acme.serve(app).await;
+        }
+
+        let listener = tokio::net::TcpListener::bind(self.address(port)).await?;
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Mounts `inner` under `prefix` in `router`.
+///
+/// `nest` serves the inner `/` at the bare prefix only, so `{prefix}/` is
+/// redirected there, keeping any prefix `router` is itself nested under.
+pub fn nest(router: Router, prefix: &str, inner: Router) -> Router {
+    let target = prefix.to_string();
+    router.nest(prefix, inner).route(
+        &format!("{prefix}/"),
+        get(move |outer: Option<NestedPath>| async move {
+            let outer = outer.as_ref().map_or("", NestedPath::as_str);
+            Redirect::permanent(&format!("{outer}{target}"))
+        }),
+    )
+}
+
+/// Normalizes `--base-path` to a leading slash and no trailing one; `/` alone
+/// means no prefix, given as `""`.
+fn parse_base_path(value: &str) -> Result<String, String> {
+    let trimmed = value.trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("/{trimmed}"))
+}
+
+/// Compression negotiated from `Accept-Encoding`.
+///
+/// tower-http's default predicate skips `text/event-stream`; it is allowed
+/// here because the encoder flushes whenever the stream goes idle, so each
+/// event still reaches the client as soon as it is sent.
+fn compression() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(
+        SizeAbove::default()
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_paths_get_one_leading_slash() {
+        assert_eq!(parse_base_path("mcp-bridge").unwrap(), "/mcp-bridge");
+        assert_eq!(parse_base_path("/mcp-bridge/").unwrap(), "/mcp-bridge");
+        assert_eq!(parse_base_path("//a/b//").unwrap(), "/a/b");
+    }
+
+    #[test]
+    fn a_slash_alone_is_no_base_path() {
+        assert_eq!(parse_base_path("/").unwrap(), "");
+        assert_eq!(parse_base_path("").unwrap(), "");
+    }
+}
@@ -0,0 +1,245 @@
+//! Flags choosing how requests map to the site's files, how those files
+//! answer them, and what answers when no file does.
+
+use crate::MCPBridgeBuilder;
+use crate::resolver::{ArgumentHash, ArgumentOrder, DefaultPathResolver, PathResolver};
+use crate::rewrite::{self, Rewrite, RewriteRule};
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use serde_json::Value;
+use std::sync::Arc;
+#[cfg(any(feature = "rhai", feature = "wasm"))]
+use std::time::Duration;
+
+#[derive(Debug, Clone, Args)]
+pub struct PathArgs {
+    /// How tools called with more than two arguments name their file, unless
+    /// their manifest entry sets `argumentHash`
+    #[arg(
+        long,
+        value_name = "HASH",
+        value_enum,
+        default_value_t = ArgumentHash::Base64,
+        env = "ARGUMENT_HASH"
+    )]
+    pub argument_hash: ArgumentHash,
+
+    /// How tools called with two arguments order them in their file's path,
+    /// unless their manifest entry sets `argumentOrder`
+    #[arg(
+        long,
+        value_name = "ORDER",
+        value_enum,
+        default_value_t = ArgumentOrder::Value,
+        env = "ARGUMENT_ORDER"
+    )]
+    pub argument_order: ArgumentOrder,
+
+    /// Trim, lowercase and NFC-normalize tool names and string arguments
+    /// before looking them up, so `Search` calls `search`
+    #[arg(long, env = "NORMALIZE", value_parser = BoolishValueParser::new())]
+    pub normalize: bool,
+
+    /// Answer calls with arguments that have no file of their own from
+    /// `tools/{name}/index.json`, when the site has one
+    #[arg(long, env = "TOOL_INDEX_FALLBACK", value_parser = BoolishValueParser::new())]
+    pub tool_index_fallback: bool,
+
+    /// Answer calls with arguments that have no file of their own from
+    /// `tools/{name}/{FILE}.json`, placeholders such as `{{args.query}}`
+    /// filled in; without a value, `_default`
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = "_default",
+        env = "TOOL_DEFAULT_FILE"
+    )]
+    pub tool_default_file: Option<String>,
+
+    /// Fill in `{{args.NAME}}`, `{{args}}` and `{{uri}}` placeholders in
+    /// every tool and resource file from the request it answers
+    #[arg(long, env = "RENDER_TEMPLATES", value_parser = BoolishValueParser::new())]
+    pub render_templates: bool,
+
+    /// List `__list_available_paths` and `__describe_tool` tools showing
+    /// which files a local site has for each tool
+    #[arg(long, env = "META_TOOLS", value_parser = BoolishValueParser::new())]
+    pub meta_tools: bool,
+
+    /// List every tool the site answers with this annotation, such as
+    /// `readOnlyHint=true`, unless its manifest entry sets it; repeat for
+    /// more. VALUE is read as JSON when it parses, otherwise as a string
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        value_delimiter = ',',
+        value_parser = parse_annotation,
+        env = "TOOL_ANNOTATIONS"
+    )]
+    pub tool_annotation: Vec<(String, Value)>,
+
+    /// Locale, such as `fr` or `pt-BR`, whose translations the site keeps
+    /// in `resources/{LOCALE}/`; repeat for more. Reads asking for one
+    /// through a `locale` parameter, `?locale=` or `Accept-Language` get
+    /// its translation when there is one
+    #[arg(
+        long = "locale",
+        value_name = "LOCALE",
+        value_delimiter = ',',
+        value_parser = crate::locale::parse,
+        env = "LOCALES"
+    )]
+    pub locales: Vec<String>,
+
+    /// Read resources asked for by a URI matching FROM as the URI TO
+    /// instead, such as `legacy://{+page}=docs://{page}`; repeat for more.
+    /// FROM is a `{name}` template, or a regex when it starts with `^`
+    #[arg(
+        long,
+        value_name = "FROM=TO",
+        value_parser = |value: &str| rewrite::parse(Rewrite::UriToUri, value),
+        env = "REWRITE_URI"
+    )]
+    pub rewrite_uri: Vec<RewriteRule>,
+
+    /// Read resources whose URI matches FROM from the path TO, unless the
+    /// manifest gives them a `pathTemplate`, such as
+    /// `docs://{+page}=resources/documentation/{page}.json`; repeat for more
+    #[arg(
+        long,
+        value_name = "FROM=TO",
+        value_parser = |value: &str| rewrite::parse(Rewrite::UriToPath, value),
+        env = "REWRITE_URI_PATH"
+    )]
+    pub rewrite_uri_path: Vec<RewriteRule>,
+
+    /// Read the files of resources, tool calls and prompts that resolve to
+    /// a path matching FROM from TO instead, such as `^v1/(.*)$=$1`;
+    /// repeat for more
+    #[arg(
+        long,
+        value_name = "FROM=TO",
+        value_parser = |value: &str| rewrite::parse(Rewrite::PathToPath, value),
+        env = "REWRITE_PATH"
+    )]
+    pub rewrite_path: Vec<RewriteRule>,
+
+    /// Live MCP server (Streamable HTTP) answering undeclared tools and
+    /// resources, those marked `upstream`, and those whose file is missing
+    #[cfg(feature = "remote")]
+    #[arg(long, value_name = "URL", env = "UPSTREAM")]
+    pub upstream: Option<String>,
+
+    /// Time a tool's Rhai or WASM `handler` may run before its call fails
+    #[cfg(any(feature = "rhai", feature = "wasm"))]
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "1s",
+        value_parser = humantime::parse_duration,
+        env = "HANDLER_TIMEOUT"
+    )]
+    pub handler_timeout: Duration,
+
+    /// Rhai operations, such as expressions evaluated and loop iterations,
+    /// a tool's `handler` may perform per call; `0` for no limit
+    #[cfg(feature = "rhai")]
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1_000_000,
+        env = "HANDLER_MAX_OPERATIONS"
+    )]
+    pub handler_max_operations: u64,
+
+    /// Fuel a tool's WASM `handler` may burn per call, about one per
+    /// instruction it runs
+    #[cfg(feature = "wasm")]
+    #[arg(
+        long,
+        value_name = "FUEL",
+        default_value_t = 100_000_000,
+        env = "HANDLER_FUEL"
+    )]
+    pub handler_fuel: u64,
+
+    /// Bytes of memory a tool's WASM `handler` may grow to per call
+    #[cfg(feature = "wasm")]
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 16 << 20,
+        env = "HANDLER_MAX_MEMORY"
+    )]
+    pub handler_max_memory: usize,
+}
+
+impl PathArgs {
+    /// `builder` mapping requests to files as these flags say.
+    pub fn apply(&self, builder: MCPBridgeBuilder) -> MCPBridgeBuilder {
+        #[cfg(feature = "remote")]
+        let builder = match &self.upstream {
+            Some(url) => builder.upstream(Arc::new(crate::upstream::Upstream::new(url.clone()))),
+            None => builder,
+        };
+        #[cfg(any(feature = "rhai", feature = "wasm"))]
+        let builder = builder.handler_limits(self.handler_limits());
+        builder
+            .path_resolver(self.resolver())
+            .normalize(self.normalize)
+            .tool_index_fallback(self.tool_index_fallback)
+            .tool_default_file(self.tool_default_file.clone())
+            .render_templates(self.render_templates)
+            .meta_tools(self.meta_tools)
+            .tool_annotations(self.tool_annotation.iter().cloned().collect())
+            .locales(self.locales.clone())
+            .rewrites(
+                [
+                    &self.rewrite_uri,
+                    &self.rewrite_uri_path,
+                    &self.rewrite_path,
+                ]
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect(),
+            )
+    }
+
+    /// The bounds on tool handlers these flags set.
+    #[cfg(any(feature = "rhai", feature = "wasm"))]
+    pub fn handler_limits(&self) -> crate::handler::HandlerLimits {
+        let mut limits = crate::handler::HandlerLimits {
+            timeout: self.handler_timeout,
+            ..Default::default()
+        };
+        #[cfg(feature = "rhai")]
+        {
+            limits.max_operations = self.handler_max_operations;
+        }
+        #[cfg(feature = "wasm")]
+        {
+            limits.fuel = self.handler_fuel;
+            limits.max_memory = self.handler_max_memory;
+        }
+        limits
+    }
+
+    pub fn resolver(&self) -> Arc<dyn PathResolver> {
+        Arc::new(DefaultPathResolver {
+            argument_hash: self.argument_hash,
+            argument_order: self.argument_order,
+        })
+    }
+}
+
+fn parse_annotation(value: &str) -> Result<(String, Value), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => {
+            let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+            Ok((key.to_string(), value))
+        }
+        _ => Err("expected KEY=VALUE, such as readOnlyHint=true".into()),
+    }
+}
@@ -0,0 +1,21 @@
+//! The authenticated caller of the request being handled, whichever layer
+//! authenticated it.
+
+use std::future::Future;
+
+pub use crate::policy::Principal;
+
+tokio::task_local! {
+    static CURRENT: Principal;
+}
+
+/// Principal of the request whose handler is currently running, if it was
+/// authenticated.
+pub fn current() -> Option<Principal> {
+    CURRENT.try_with(Principal::clone).ok()
+}
+
+/// Runs `future` with `principal` available through [`current`].
+pub async fn scope<F: Future>(principal: Principal, future: F) -> F::Output {
+    CURRENT.scope(principal, future).await
+}
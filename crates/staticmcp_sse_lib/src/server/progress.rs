@@ -0,0 +1,91 @@
+//! Counts for a batch of files fetched a few at a time, such as `--preload`
+//! and `mirror`, reported every [`Progress::INTERVAL`] instead of per file,
+//! since large sites have tens of thousands of them.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Files in the batch.
+    pub total: usize,
+    /// Files fetched so far.
+    pub loaded: usize,
+    /// Files that couldn't be fetched so far.
+    pub failed: usize,
+    /// Bytes fetched so far, when the fetches count them.
+    pub bytes: u64,
+    started: Instant,
+    reported: Instant,
+}
+
+impl Progress {
+    /// How often [`Self::record`] says a report is due.
+    pub const INTERVAL: Duration = Duration::from_secs(2);
+
+    pub fn new(total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            total,
+            loaded: 0,
+            failed: 0,
+            bytes: 0,
+            started: now,
+            reported: now,
+        }
+    }
+
+    /// Counts one file, fetched with `Some(bytes)` or failed with `None`,
+    /// and whether a report is due. None is due for the last file, which
+    /// the caller's summary covers.
+    pub fn record(&mut self, fetched: Option<u64>) -> bool {
+        match fetched {
+            Some(bytes) => {
+                self.loaded += 1;
+                self.bytes += bytes;
+            }
+            None => self.failed += 1,
+        }
+        if self.finished() || self.reported.elapsed() < Self::INTERVAL {
+            return false;
+        }
+        self.reported = Instant::now();
+        true
+    }
+
+    pub fn finished(&self) -> bool {
+        self.loaded + self.failed >= self.total
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The time the rest of the batch would take at the rate so far.
+    pub fn remaining(&self) -> Option<Duration> {
+        let done = self.loaded + self.failed;
+        (done > 0).then(|| {
+            self.elapsed()
+                .mul_f64(self.total.saturating_sub(done) as f64 / done as f64)
+        })
+    }
+}
+
+/// `1200/30000 files, 3 failed, 14.2 MB, about 48s left`.
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} files", self.loaded + self.failed, self.total)?;
+        if self.failed > 0 {
+            write!(f, ", {} failed", self.failed)?;
+        }
+        match self.bytes {
+            0 => {}
+            bytes if bytes < 1_000_000 => write!(f, ", {} kB", bytes.div_ceil(1000))?,
+            bytes => write!(f, ", {:.1} MB", bytes as f64 / 1e6)?,
+        }
+        if let Some(remaining) = self.remaining() {
+            write!(f, ", about {}s left", remaining.as_secs())?;
+        }
+        Ok(())
+    }
+}
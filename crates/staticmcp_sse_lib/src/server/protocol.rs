@@ -0,0 +1,59 @@
+//! The MCP protocol version of the client whose request is being handled:
+//! the one its `/events` session agreed at `initialize`, or else the one
+//! its `MCP-Protocol-Version` header names.
+
+use super::envelope;
+use crate::{MCPResponse, protocol};
+use axum::{
+    extract::Request,
+    http::{HeaderName, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{Value, json};
+use std::future::Future;
+
+pub const PROTOCOL_VERSION_HEADER: HeaderName = HeaderName::from_static("mcp-protocol-version");
+
+tokio::task_local! {
+    static CURRENT: &'static str;
+}
+
+/// Protocol version of the request whose handler is currently running,
+/// when its client said.
+pub fn current() -> Option<&'static str> {
+    CURRENT.try_with(|version| *version).ok()
+}
+
+/// Runs `future` with `version` available through [`current`].
+pub async fn scope<F: Future>(version: &'static str, future: F) -> F::Output {
+    CURRENT.scope(version, future).await
+}
+
+/// The version `response` agreed on, when it answers an `initialize`.
+pub fn agreed(response: &MCPResponse) -> Option<&'static str> {
+    response
+        .result
+        .as_ref()?
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .and_then(protocol::supported)
+}
+
+/// Runs the rest with the version the `MCP-Protocol-Version` header names,
+/// refusing versions the bridge doesn't speak with `400`.
+pub async fn header(request: Request, next: Next) -> Response {
+    let Some(value) = request.headers().get(&PROTOCOL_VERSION_HEADER) else {
+        return next.run(request).await;
+    };
+    let requested = value.to_str().unwrap_or_default();
+    match protocol::supported(requested) {
+        Some(version) => scope(version, next.run(request)).await,
+        None => envelope::of(&request).reject(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            format!("Unsupported protocol version: {requested}"),
+            Some(json!({ "supported": protocol::SUPPORTED })),
+        ),
+    }
+}
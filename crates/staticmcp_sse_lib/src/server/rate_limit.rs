@@ -0,0 +1,171 @@
+//! Token-bucket rate limiting for the MCP endpoints.
+
+use super::client_ip::client_ip;
+use super::{envelope, principal};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use clap::Args;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Buckets are swept once the table grows past this many clients.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Args)]
+pub struct RateLimitArgs {
+    /// Sustained requests per second allowed per client on the MCP endpoints
+    #[arg(long, value_name = "PER_SECOND", env = "RATE_LIMIT")]
+    pub rate_limit: Option<f64>,
+
+    /// Requests a client may burst above the sustained rate [default: rate limit, at least 1]
+    #[arg(long, value_name = "REQUESTS", env = "RATE_LIMIT_BURST")]
+    pub rate_limit_burst: Option<f64>,
+}
+
+impl RateLimitArgs {
+    pub fn limiter(&self) -> Option<Arc<RateLimiter>> {
+        let rate = self.rate_limit.filter(|rate| *rate > 0.0)?;
+        let burst = self.rate_limit_burst.unwrap_or(rate).max(1.0);
+        Some(Arc::new(RateLimiter::new(rate, burst)))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64, burst: f64) -> Self {
+        Self {
+            rate,
+            burst,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes one token for `key`, or returns how long until one is available.
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() > SWEEP_THRESHOLD {
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+
+    /// Authenticated callers share one bucket per principal wherever they
+    /// connect from; anonymous ones are limited per client address.
+    fn key(request: &Request) -> String {
+        if let Some(caller) = principal::current() {
+            return format!("principal:{}", caller.subject);
+        }
+        match client_ip(request) {
+            Some(ip) => format!("ip:{ip}"),
+            None => "unknown".to_string(),
+        }
+    }
+}
+
+pub async fn limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Err(retry_after) = limiter.check(&RateLimiter::key(&request)) else {
+        return next.run(request).await;
+    };
+
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    tracing::debug!(retry_after_secs, "rate limit exceeded");
+
+    let mut response = envelope::of(&request).reject(
+        StatusCode::TOO_MANY_REQUESTS,
+        -32000,
+        "Rate limit exceeded",
+        Some(json!({
+            "retryAfterMs": retry_after.as_millis() as u64,
+        })),
+    );
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+
+    fn request(peer: &str) -> Request {
+        let mut request = Request::new(Body::empty());
+        let addr: SocketAddr = peer.parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    fn caller(subject: &str) -> principal::Principal {
+        principal::Principal {
+            subject: subject.into(),
+            scopes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn refuses_past_the_burst() {
+        let limiter = RateLimiter::new(1.0, 2.0);
+        assert!(limiter.check("ip:192.0.2.1").is_ok());
+        assert!(limiter.check("ip:192.0.2.1").is_ok());
+        let retry_after = limiter.check("ip:192.0.2.1").unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+        assert!(limiter.check("ip:192.0.2.2").is_ok());
+    }
+
+    #[test]
+    fn keys_anonymous_callers_by_address() {
+        assert_eq!(RateLimiter::key(&request("192.0.2.1:5000")), "ip:192.0.2.1");
+        assert_eq!(RateLimiter::key(&Request::new(Body::empty())), "unknown");
+    }
+
+    #[tokio::test]
+    async fn keys_authenticated_callers_by_principal() {
+        let key = principal::scope(caller("ci"), async {
+            RateLimiter::key(&request("192.0.2.1:5000"))
+        })
+        .await;
+        assert_eq!(key, "principal:ci");
+    }
+}
@@ -0,0 +1,55 @@
+//! Per-request correlation ids.
+//!
+//! Every request gets an id, either the caller's `X-Request-Id` or a fresh
+//! UUID. It is echoed in the response header, recorded on the request span,
+//! added to JSON-RPC error `data`, and forwarded on upstream fetches.
+
+use super::telemetry;
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Incoming ids longer than this, or with non-printable characters, are replaced.
+const MAX_LEN: usize = 128;
+
+/// The id of the request being handled, available as an extension on the request.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Id of the request whose handler is currently running, if any.
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+fn incoming(request: &Request) -> Option<String> {
+    let value = request.headers().get(&REQUEST_ID_HEADER)?.to_str().ok()?;
+    let valid =
+        !value.is_empty() && value.len() <= MAX_LEN && value.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+pub async fn assign(mut request: Request, next: Next) -> Response {
+    let id = incoming(&request).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = telemetry::http_span(request.headers(), &id);
+    let mut response = CURRENT
+        .scope(id.clone(), next.run(request))
+        .instrument(span)
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
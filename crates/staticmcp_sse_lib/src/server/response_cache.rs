@@ -0,0 +1,200 @@
+//! `--response-cache-ttl`: whole `resources/read` and `tools/call` results,
+//! as they were sent, so a repeated request skips loading and reshaping its
+//! file as well as serializing the answer.
+//!
+//! Results are kept by method and parameters, ignoring `_meta`, along with
+//! everything else that changes the answer: the protocol version, the
+//! locales asked for and the caller the access policy judged. Errors and
+//! `isError` results aren't kept, so a failing file is retried.
+
+use crate::{MCPRequest, MCPResponse};
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The methods whose results are kept.
+pub const METHODS: [&str; 2] = ["resources/read", "tools/call"];
+
+struct Entry {
+    result: Box<RawValue>,
+    stored: Instant,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_key: HashMap<String, Entry>,
+    /// Keys oldest first, with when each was stored, for evicting the
+    /// oldest once `max_bytes` is reached. A key stored again is listed
+    /// again, its earlier listing skipped when it comes up.
+    order: VecDeque<(String, Instant)>,
+    bytes: usize,
+}
+
+impl Entries {
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.by_key.remove(key) {
+            self.bytes -= entry.result.get().len();
+        }
+    }
+}
+
+/// Results sent for one bridge, by request.
+pub struct ResponseCache {
+    ttl: Duration,
+    max_bytes: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ResponseCache {
+    /// Results kept for `ttl`, the oldest dropped to stay within
+    /// `max_bytes` of JSON.
+    pub fn new(ttl: Duration, max_bytes: usize) -> Self {
+        Self {
+            ttl,
+            max_bytes,
+            entries: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// What identifies `request`'s answer for the request being handled,
+    /// answered for protocol `version`; `None` when its method's results
+    /// aren't kept.
+    pub fn key(request: &MCPRequest, version: &str) -> Option<String> {
+        if !METHODS.contains(&request.method.as_str()) {
+            return None;
+        }
+        let mut params = request.params.clone().unwrap_or(Value::Null);
+        if let Some(params) = params.as_object_mut() {
+            params.remove("_meta");
+        }
+        let principal = super::principal::current()
+            .map(|principal| format!("{}:{}", principal.subject, principal.scopes.join(",")));
+        // Object keys serialize sorted, so equal parameters give equal keys.
+        Some(
+            serde_json::json!([
+                request.method,
+                params,
+                version,
+                super::locale::current(),
+                principal,
+            ])
+            .to_string(),
+        )
+    }
+
+    /// The result kept for `key`, while fresh.
+    pub fn get(&self, key: &str) -> Option<Box<RawValue>> {
+        let mut entries = self.entries();
+        let found = match entries.by_key.get(key) {
+            Some(entry) if entry.stored.elapsed() < self.ttl => Some(entry.result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// Keeps `response`'s result for `key`, unless it failed.
+    pub fn insert_response(&self, key: String, response: &MCPResponse) {
+        let Some(result) = response
+            .result
+            .as_ref()
+            .filter(|_| response.error.is_none())
+        else {
+            return;
+        };
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            return;
+        }
+        if let Ok(result) = serde_json::value::to_raw_value(result) {
+            self.insert(key, result);
+        }
+    }
+
+    /// Keeps `result`, a file answered as it was read, for `key`.
+    pub fn insert(&self, key: String, result: Box<RawValue>) {
+        let bytes = result.get().len();
+        if bytes > self.max_bytes {
+            return;
+        }
+        let stored = Instant::now();
+        let mut entries = self.entries();
+        entries.remove(&key);
+        while entries.bytes + bytes > self.max_bytes {
+            let Some((oldest, when)) = entries.order.pop_front() else {
+                break;
+            };
+            if entries
+                .by_key
+                .get(&oldest)
+                .is_some_and(|entry| entry.stored == when)
+            {
+                entries.remove(&oldest);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        entries.bytes += bytes;
+        entries.order.push_back((key.clone(), stored));
+        entries.by_key.insert(key, Entry { result, stored });
+        // Listings of keys stored again pile up behind them; drop them once
+        // they outnumber the entries.
+        if entries.order.len() > 2 * entries.by_key.len() + 64 {
+            let Entries { by_key, order, .. } = &mut *entries;
+            order.retain(|(key, when)| by_key.get(key).is_some_and(|entry| entry.stored == *when));
+        }
+    }
+
+    /// Drops every result, returning how many there were.
+    pub fn purge(&self) -> usize {
+        let mut entries = self.entries();
+        let purged = entries.by_key.len();
+        *entries = Entries::default();
+        purged
+    }
+
+    /// Counts of the results kept and how often they answered.
+    pub fn report(&self) -> Value {
+        let entries = self.entries();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        serde_json::json!({
+            "ttlSeconds": self.ttl.as_secs_f64(),
+            "maxBytes": self.max_bytes,
+            "entries": entries.by_key.len(),
+            "bytes": entries.bytes,
+            "hits": hits,
+            "misses": misses,
+            "hitRate": hit_rate(hits, misses),
+            "evictions": self.evictions.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// The share of lookups that were hits, to a thousandth.
+pub fn hit_rate(hits: u64, misses: u64) -> f64 {
+    match hits + misses {
+        0 => 0.0,
+        lookups => (hits as f64 / lookups as f64 * 1000.0).round() / 1000.0,
+    }
+}
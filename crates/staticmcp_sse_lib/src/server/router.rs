@@ -0,0 +1,265 @@
+//! The routes serving one bridge, as an axum [`Router`] without a listener:
+//! its info page, the `/sse` messages and `/events` streams, and the
+//! `/healthz` and `/readyz` probes. Applications already running axum can
+//! mount it, with `nest` or `merge`, alongside their own routes.
+
+use super::body::{self, JsonRpc};
+use super::envelope::{self, McpEnvelope};
+use super::fixtures::Fixtures;
+use super::session::Sessions;
+use super::{chunked, health, methods, protocol, request_id, status};
+use crate::client::ClientInfo;
+use crate::{MCPBridge, MCPReply, MCPRequest};
+use axum::response::sse::Event;
+use axum::{
+    Extension, Json, Router,
+    extract::{NestedPath, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Response, Sse},
+    routing::get,
+};
+use futures::stream::Stream;
+use serde::Deserialize;
+use serde_json::json;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::CorsLayer;
+use tracing::debug;
+
+/// The fixtures a bridge's answers are recorded in or replayed from, and
+/// the name they are kept under there.
+pub type Recording = Option<(Arc<Fixtures>, Arc<str>)>;
+
+/// How [`router`] serves its bridge.
+#[derive(Clone)]
+pub struct RouterOptions {
+    /// The sessions of the bridge's `/events` streams, with their replay
+    /// and keep-alive settings.
+    pub sessions: Arc<Sessions>,
+    /// Answers to record or replay instead of asking the bridge.
+    pub recording: Recording,
+    /// Accept message bodies without a JSON content type, as `--lenient`
+    /// does.
+    pub lenient: bool,
+    /// CORS for the routes, answering preflights itself; `None` leaves CORS
+    /// to the application.
+    pub cors: Option<CorsLayer>,
+}
+
+impl Default for RouterOptions {
+    /// The `serve` defaults: `/events` sessions keeping their last 100
+    /// messages and pinging every 15 seconds, and no CORS.
+    fn default() -> Self {
+        Self {
+            sessions: Arc::new(Sessions::new(100, Duration::from_secs(15))),
+            recording: None,
+            lenient: false,
+            cors: None,
+        }
+    }
+}
+
+/// Every route serving `bridge`, relative to wherever it is mounted, with
+/// the layers those routes rely on: request ids, protocol version headers
+/// and JSON-RPC envelopes. Other paths are left to the application's
+/// fallback.
+pub fn router(bridge: Arc<MCPBridge>, options: RouterOptions) -> Router {
+    let lenient = options.lenient;
+    let cors = options.cors.clone();
+    let router = routes(bridge, options, |mut router| {
+        if lenient {
+            router = router.layer(Extension(body::Lenient));
+        }
+        router.layer(middleware::from_fn(protocol::header))
+    });
+    let router = match cors {
+        Some(cors) => methods::with_cors(router, cors),
+        None => router,
+    };
+    router
+        .layer(middleware::from_fn(envelope::capture))
+        .layer(middleware::from_fn(request_id::assign))
+}
+
+/// The routes of [`router`] without its layers, the MCP message and event
+/// routes wrapped in `mcp_layers`, for binaries bringing their own stack
+/// such as [`ServerArgs::apply_mcp`] and [`ServerArgs::apply`].
+/// `options.lenient` and `options.cors` are left to that stack.
+///
+/// [`ServerArgs::apply_mcp`]: super::ServerArgs::apply_mcp
+/// [`ServerArgs::apply`]: super::ServerArgs::apply
+pub fn routes(
+    bridge: Arc<MCPBridge>,
+    options: RouterOptions,
+    mcp_layers: impl FnOnce(Router) -> Router,
+) -> Router {
+    let RouterOptions {
+        sessions,
+        recording,
+        ..
+    } = options;
+    let replaying = recording
+        .as_ref()
+        .is_some_and(|(fixtures, _)| fixtures.replaying());
+    let keep_alive = sessions.keep_alive();
+    let messages = sessions.clone();
+    let mcp_routes = mcp_layers(
+        Router::new()
+            .route(
+                "/sse",
+                methods::messages(move |state, query, headers, request| {
+                    message_endpoint(state, query, headers, messages, recording, request)
+                }),
+            )
+            .route(
+                "/events",
+                methods::events(move |nested, headers| events_endpoint(nested, headers, sessions)),
+            )
+            .with_state(bridge.clone()),
+    );
+    // Nothing is read while replaying, so being up is being ready.
+    let readyz = if replaying {
+        get(health::healthz)
+    } else {
+        get(health::readyz)
+    };
+    Router::new()
+        .route(
+            "/",
+            get(move |state, nested| info_endpoint(state, nested, keep_alive)),
+        )
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", readyz)
+        .with_state(bridge)
+        .merge(mcp_routes)
+}
+
+/// Query of a message posted to an `/events` session.
+#[derive(Deserialize)]
+struct MessageQuery {
+    session: Option<String>,
+}
+
+async fn message_endpoint(
+    State(bridge): State<Arc<MCPBridge>>,
+    Query(query): Query<MessageQuery>,
+    headers: HeaderMap,
+    sessions: Arc<Sessions>,
+    recording: Recording,
+    JsonRpc(request): JsonRpc,
+) -> Response {
+    debug!("MCP message received");
+    if let Some(id) = query.session {
+        if !sessions.known(&id).await {
+            let envelope = McpEnvelope {
+                id: request.id,
+                method: Some(request.method),
+            };
+            return envelope.reject(StatusCode::NOT_FOUND, -32600, "Unknown session", None);
+        }
+        // Notifications get no answer.
+        let notification = request.id.is_none();
+        let reply = match sessions.get(&id) {
+            Some(session) => {
+                let client = ClientInfo::from_initialize(&request);
+                let reply = session.scope(reply(&bridge, request, &recording)).await;
+                if let MCPReply::Response(response) = &reply {
+                    session.record(client, response);
+                }
+                reply
+            }
+            // Another replica's session.
+            None => reply(&bridge, request, &recording).await,
+        };
+        let json = reply.into_json().await;
+        if !notification {
+            let json = String::from_utf8(Vec::from(json)).unwrap_or_default();
+            sessions.send(&id, json).await;
+        }
+        return StatusCode::ACCEPTED.into_response();
+    }
+    let reply = reply(&bridge, request, &recording).await;
+    let status = match &reply {
+        MCPReply::Response(response) => status::of(response.error.as_ref()),
+        _ => StatusCode::OK,
+    };
+    let json = match reply {
+        MCPReply::Chunked { id, contents } if chunked::accepted(&headers) => {
+            return chunked::sse(id, contents);
+        }
+        reply => reply.into_json().await,
+    };
+    (status, [(header::CONTENT_TYPE, "application/json")], json).into_response()
+}
+
+/// `bridge`'s reply to `request`, or the one recorded for it. Recorded
+/// answers are read whole rather than sent in chunks.
+async fn reply(bridge: &MCPBridge, request: MCPRequest, recording: &Recording) -> MCPReply {
+    match recording {
+        Some((fixtures, scope)) => MCPReply::Response(
+            fixtures
+                .answer(scope, request, |request| bridge.handle_request(request))
+                .await,
+        ),
+        None => bridge.reply(request).await,
+    }
+}
+
+async fn events_endpoint(
+    nested: Option<NestedPath>,
+    headers: HeaderMap,
+    sessions: Arc<Sessions>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    debug!("SSE connection requested");
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok());
+    let endpoint = format!("{}/sse", base_path(&nested));
+    Sse::new(sessions.connect(last_event_id, &endpoint).await)
+}
+
+/// Describes the bridge to anyone, unauthenticated: the manifest's tools and
+/// resources are only listed over MCP, where authentication and the access
+/// policy apply.
+async fn info_endpoint(
+    State(bridge): State<Arc<MCPBridge>>,
+    nested: Option<NestedPath>,
+    keep_alive: Duration,
+) -> Json<serde_json::Value> {
+    let base = base_path(&nested);
+    let manifest_info = if let Some(manifest) = bridge.get_manifest() {
+        json!({ "serverInfo": manifest.server_info })
+    } else {
+        json!({ "error": "Manifest not loaded" })
+    };
+
+    Json(json!({
+        "bridge": "SSE Static MCP Bridge (Fixed Path)",
+        "version": "1.0.0",
+        "type": "fixed",
+        "manifest": manifest_info,
+        "endpoints": {
+            "info": format!("GET {base}/"),
+            "mcp_sse": format!("POST {base}/sse"),
+            "mcp_sse_events": format!("GET {base}/events"),
+            "liveness": format!("GET {base}/healthz"),
+            "readiness": format!("GET {base}/readyz"),
+        },
+        "sse_keep_alive": (!keep_alive.is_zero())
+            .then(|| humantime::format_duration(keep_alive).to_string()),
+        "usage": {
+            "mcp_clients": format!("Point MCP client to: http://localhost:PORT{base}/"),
+            "standard_endpoints": [
+                format!("GET {base}/ (for info)"),
+                format!("POST {base}/sse (for SSE messages)")
+            ]
+        }
+    }))
+}
+
+/// Prefix the routes are mounted under, or `""`.
+fn base_path(nested: &Option<NestedPath>) -> &str {
+    nested.as_ref().map_or("", NestedPath::as_str)
+}
@@ -0,0 +1,47 @@
+use clap::Args;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How the binaries' tokio runtime is built, read before it starts so
+/// small containers and busy proxies alike can be sized to fit.
+#[derive(Debug, Clone, Args)]
+pub struct RuntimeArgs {
+    /// Threads running requests; defaults to one per CPU core
+    #[arg(long, value_name = "N", global = true, env = "WORKER_THREADS")]
+    pub worker_threads: Option<NonZeroUsize>,
+
+    /// Most threads for blocking work such as file reads, started as needed
+    /// [default: 512]
+    #[arg(long, value_name = "N", global = true, env = "MAX_BLOCKING_THREADS")]
+    pub max_blocking_threads: Option<NonZeroUsize>,
+
+    /// Name of the runtime's threads, numbered as `NAME-1`, `NAME-2`, …, as
+    /// shown by `top -H` and in debuggers
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_value = "staticmcp",
+        global = true,
+        env = "THREAD_NAME"
+    )]
+    pub thread_name: String,
+}
+
+impl RuntimeArgs {
+    /// A multi-threaded runtime with every driver enabled, sized as the
+    /// flags say.
+    pub fn build(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(threads) = self.worker_threads {
+            builder.worker_threads(threads.get());
+        }
+        if let Some(threads) = self.max_blocking_threads {
+            builder.max_blocking_threads(threads.get());
+        }
+        let name = self.thread_name.clone();
+        let next = AtomicUsize::new(1);
+        builder.thread_name_fn(move || format!("{name}-{}", next.fetch_add(1, Ordering::Relaxed)));
+        builder.build()
+    }
+}
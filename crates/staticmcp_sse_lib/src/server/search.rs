@@ -0,0 +1,54 @@
+//! Flags enabling the synthetic `search` tool, and the directory its index
+//! may be kept in between runs.
+
+use crate::search::SearchIndex;
+use crate::{MCPBridge, MCPBridgeBuilder};
+use clap::Args;
+use clap::builder::BoolishValueParser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Debug, Clone, Args)]
+pub struct SearchArgs {
+    /// Index the text of every resource at startup and answer a `search`
+    /// tool with the resources best matching its query
+    #[arg(long, env = "SEARCH", value_parser = BoolishValueParser::new())]
+    pub search: bool,
+
+    /// Read the search index from this directory, or write the one built at
+    /// startup to it when it doesn't exist yet; implies `--search`
+    #[arg(long, value_name = "DIR", env = "SEARCH_INDEX")]
+    pub search_index: Option<PathBuf>,
+}
+
+impl SearchArgs {
+    /// `builder` answering the `search` tool as these flags say, with the
+    /// index read from `--search-index` when the directory exists.
+    pub fn apply(&self, builder: MCPBridgeBuilder) -> anyhow::Result<MCPBridgeBuilder> {
+        match &self.search_index {
+            Some(path) if path.exists() => {
+                let index = SearchIndex::open(path)
+                    .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+                info!(path = %path.display(), documents = index.len(), "loaded search index");
+                Ok(builder.search_index(Arc::new(index)))
+            }
+            Some(_) => Ok(builder.search(true)),
+            None => Ok(builder.search(self.search)),
+        }
+    }
+
+    /// Writes the index `bridge` built at startup to `--search-index`, when
+    /// it wasn't read from there.
+    pub fn save(&self, bridge: &MCPBridge) -> anyhow::Result<()> {
+        if let (Some(path), Some(index)) = (&self.search_index, &bridge.search)
+            && !path.exists()
+        {
+            index
+                .save(path)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+            info!(path = %path.display(), "saved search index");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//! The bridge as a [`tower::Service`], for composing tower middleware such
+//! as `retry`, `buffer` or `timeout` around it, or handing it to hyper.
+//!
+//! [`BridgeService`] answers MCP requests directly, and HTTP requests
+//! through the routes [`router`](super::router::router) serves.
+
+use super::router::{self, RouterOptions};
+use crate::{MCPBridge, MCPRequest, MCPResponse};
+use axum::{
+    BoxError, Router,
+    body::{Bytes, HttpBody},
+    extract::Request,
+    response::Response,
+    routing::future::RouteFuture,
+};
+use futures::future::BoxFuture;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::Service;
+
+/// A bridge answering `MCPRequest`s with `MCPResponse`s, and HTTP requests
+/// as its routes would. It never fails: errors are JSON-RPC errors in the
+/// response.
+#[derive(Clone)]
+pub struct BridgeService {
+    bridge: Arc<MCPBridge>,
+    http: Router,
+}
+
+impl BridgeService {
+    /// `bridge`, serving HTTP with the default [`RouterOptions`].
+    pub fn new(bridge: Arc<MCPBridge>) -> Self {
+        Self::with_options(bridge, RouterOptions::default())
+    }
+
+    /// `bridge`, serving HTTP as `options` say.
+    pub fn with_options(bridge: Arc<MCPBridge>, options: RouterOptions) -> Self {
+        let http = router::router(bridge.clone(), options);
+        Self { bridge, http }
+    }
+
+    pub fn bridge(&self) -> &Arc<MCPBridge> {
+        &self.bridge
+    }
+}
+
+/// Requests are answered for the latest protocol version, there being no
+/// session or header to name another, and chunked reads are read whole.
+impl Service<MCPRequest> for BridgeService {
+    type Response = MCPResponse;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<MCPResponse, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: MCPRequest) -> Self::Future {
+        let bridge = self.bridge.clone();
+        Box::pin(async move { Ok(bridge.handle_request(request).await) })
+    }
+}
+
+impl<B> Service<Request<B>> for BridgeService
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = RouteFuture<Infallible>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Service::<Request<B>>::poll_ready(&mut self.http, cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        self.http.call(request)
+    }
+}
@@ -0,0 +1,703 @@
+//! Sessions of the `GET /events` stream, resumable after a dropped
+//! connection.
+//!
+//! Each stream opens a session whose `endpoint` event names the URL to post
+//! its messages to, `…/sse?session=ID`. Answers to those messages are sent
+//! on the stream instead of in the POST's response, as the HTTP+SSE
+//! transport expects. Every message carries an id, `ID:N`, and the session
+//! keeps its last `--sse-replay` messages, so a client reconnecting with
+//! `Last-Event-ID` is sent those it missed and carries on in the same
+//! session. A session no stream is reading is forgotten after
+//! [`IDLE_TIMEOUT`]. Sessions belong to the principal that opened them:
+//! other callers can neither post to nor resume them.
+//!
+//! With `--session-redis`, each session is also recorded in Redis while a
+//! stream reads it, so a replica behind the same load balancer accepts
+//! messages posted to another's sessions, publishing the answers for the
+//! replica holding the stream to send. A client reconnecting to a replica
+//! not holding its session has that replica take it over, and the one that
+//! held it publishes the messages the client missed before forgetting it.
+
+#[cfg(feature = "redis")]
+use super::redis::{Redis, RedisConfig, Reply};
+use super::{client, principal, protocol};
+use crate::MCPResponse;
+use crate::client::ClientInfo;
+use axum::response::sse::Event;
+use clap::Args;
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// How long a session outlives its last stream, for its client to
+/// reconnect.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Messages a slow stream may fall behind by before it skips some.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// The message opening every session.
+const READY: &str = r#"{"jsonrpc":"2.0","method":"ready","params":{}}"#;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Id of the session whose message is currently being answered, when it
+/// was posted to one.
+pub fn current() -> Option<String> {
+    CURRENT.try_with(String::clone).ok()
+}
+
+/// Subject of the authenticated caller, whose sessions are its own.
+fn caller() -> Option<String> {
+    principal::current().map(|caller| caller.subject)
+}
+
+/// Flags for the `GET /events` streams.
+#[derive(Debug, Clone, Args)]
+pub struct SessionArgs {
+    /// How often open `/events` streams send a `ping` event, so proxies
+    /// don't close them as idle; `0s` sends none
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "15s",
+        value_parser = humantime::parse_duration,
+        env = "SSE_KEEP_ALIVE"
+    )]
+    pub sse_keep_alive: Duration,
+
+    /// Messages each `/events` session keeps for a client reconnecting with
+    /// `Last-Event-ID`; `0` keeps none
+    #[arg(long, value_name = "EVENTS", default_value_t = 100, env = "SSE_REPLAY")]
+    pub sse_replay: usize,
+
+    /// Redis server recording `/events` sessions, so replicas accept
+    /// messages for each other's, such as `redis://cache:6379/0`
+    #[cfg(feature = "redis")]
+    #[arg(long, value_name = "URL", value_parser = RedisConfig::parse, env = "SESSION_REDIS")]
+    pub session_redis: Option<RedisConfig>,
+}
+
+impl SessionArgs {
+    /// A new, empty set of sessions for one bridge.
+    pub fn sessions<T: Send + Sync + 'static>(&self) -> Arc<Sessions<T>> {
+        let sessions = Sessions::new(self.sse_replay, self.sse_keep_alive);
+        #[cfg(feature = "redis")]
+        if let Some(config) = &self.session_redis {
+            return sessions.shared(config.clone());
+        }
+        Arc::new(sessions)
+    }
+}
+
+/// The sessions of one bridge's event streams, each holding a `T` for the
+/// messages posted to it.
+pub struct Sessions<T = ()> {
+    sessions: Mutex<HashMap<String, Arc<Session<T>>>>,
+    replay: usize,
+    keep_alive: Duration,
+    #[cfg(feature = "redis")]
+    store: Option<Redis>,
+    /// Names these sessions' handoffs, so they ignore their own.
+    #[cfg(feature = "redis")]
+    replica: String,
+}
+
+impl<T: Send + Sync + 'static> Sessions<T> {
+    /// Sessions keeping their last `replay` messages, whose streams ping
+    /// every `keep_alive`.
+    pub fn new(replay: usize, keep_alive: Duration) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            replay,
+            keep_alive,
+            #[cfg(feature = "redis")]
+            store: None,
+            #[cfg(feature = "redis")]
+            replica: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// How often streams send a `ping`; zero when they send none.
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    /// The session `id`, while this bridge remembers it.
+    pub fn get(&self, id: &str) -> Option<Arc<Session<T>>> {
+        self.sessions.lock().unwrap().get(id).cloned()
+    }
+
+    /// The session `last_event_id` names, while it is remembered and
+    /// belongs to the caller, and the number of the message it names. With
+    /// `--session-redis`, another replica's session is taken over, holding
+    /// what `data` returns; one it returns nothing for isn't resumed.
+    pub async fn resume<F>(
+        &self,
+        last_event_id: Option<&str>,
+        data: impl FnOnce() -> F,
+    ) -> Option<(Arc<Session<T>>, u64)>
+    where
+        F: Future<Output = Option<T>>,
+    {
+        let (id, seq) = parse_event_id(last_event_id?)?;
+        if let Some(session) = self.get(id).filter(|session| session.owner == caller()) {
+            debug!(session = session.id, after = seq, "resuming event stream");
+            return Some((session, seq));
+        }
+        #[cfg(feature = "redis")]
+        if self.store.is_some() && self.known(id).await {
+            let session = Arc::new(Session::new(
+                id.to_string(),
+                seq + 1,
+                self.replay,
+                data().await?,
+                caller(),
+            ));
+            debug!(
+                session = session.id,
+                after = seq,
+                "taking over event stream"
+            );
+            self.insert(session.clone()).await;
+            let handoff = format!("{} {id} {seq}", self.replica);
+            self.publish(HANDOFFS, &handoff).await;
+            return Some((session, seq));
+        }
+        #[cfg(not(feature = "redis"))]
+        let _ = data;
+        None
+    }
+
+    /// A new session holding `data`, belonging to the caller, forgetting
+    /// those that expired.
+    pub async fn open(&self, data: T) -> Arc<Session<T>> {
+        let session = Arc::new(Session::new(
+            uuid::Uuid::new_v4().to_string(),
+            1,
+            self.replay,
+            data,
+            caller(),
+        ));
+        debug!(session = session.id, "opening event stream");
+        self.insert(session.clone()).await;
+        session
+    }
+
+    /// Remembers `session`, forgetting those that expired.
+    async fn insert(&self, session: Arc<Session<T>>) {
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            sessions.retain(|_, session| !session.expired());
+            sessions.insert(session.id.clone(), session.clone());
+        }
+        #[cfg(feature = "redis")]
+        self.record(&session.id, session.owner.as_deref()).await;
+    }
+
+    /// Whether the caller may post messages to the session `id`: one of
+    /// this bridge's, or with `--session-redis` one of another replica's,
+    /// that the caller opened.
+    pub async fn known(&self, id: &str) -> bool {
+        if let Some(session) = self.get(id) {
+            return session.owner == caller();
+        }
+        #[cfg(feature = "redis")]
+        if let Some(store) = &self.store {
+            let key = format!("{KEY_PREFIX}{id}");
+            let owner = owner_record(caller().as_deref());
+            return match store.command(&["GET", &key]).await {
+                Ok(Reply::Bulk(recorded)) => {
+                    recorded.is_some_and(|recorded| recorded == owner.as_bytes())
+                }
+                Ok(reply) => {
+                    warn!(?reply, "unexpected reply looking up session");
+                    false
+                }
+                Err(e) => {
+                    warn!(session = id, error = %e, "failed to look up session");
+                    false
+                }
+            };
+        }
+        false
+    }
+
+    /// Sends the JSON-RPC message `data` on the session `id`'s stream,
+    /// through Redis when another replica holds it.
+    pub async fn send(&self, id: &str, data: String) {
+        if let Some(session) = self.get(id) {
+            session.send(data);
+            return;
+        }
+        #[cfg(feature = "redis")]
+        if self.store.is_some() {
+            self.publish(CHANNEL, &format!("{id} {data}")).await;
+            return;
+        }
+        debug!(session = id, "dropped message for forgotten session");
+    }
+
+    /// A stream of `session` from the first message after `after`, or from
+    /// its `ready` message when it was just opened. Its `endpoint` event
+    /// carries the URL `endpoint` with the session's id added to the query.
+    pub fn stream(
+        &self,
+        session: Arc<Session<T>>,
+        after: Option<u64>,
+        endpoint: &str,
+    ) -> impl Stream<Item = Result<Event, Infallible>> + use<T> {
+        let separator = if endpoint.contains('?') { '&' } else { '?' };
+        let endpoint = format!("{endpoint}{separator}session={}", session.id);
+        let stream = session.clone().stream(endpoint, after, self.keep_alive);
+        // Sent once the stream is listening, so it arrives even when no
+        // messages are kept.
+        if after.is_none() {
+            session.send(READY.to_string());
+        }
+        stream
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> Sessions<T> {
+    /// A stream of the session `last_event_id` names, or of a new one when
+    /// it names none still remembered, as [`Self::stream`] describes.
+    pub async fn connect(
+        &self,
+        last_event_id: Option<&str>,
+        endpoint: &str,
+    ) -> impl Stream<Item = Result<Event, Infallible>> + use<T> {
+        let resumed = self
+            .resume(last_event_id, || async { Some(T::default()) })
+            .await;
+        let (session, after) = match resumed {
+            Some((session, seq)) => (session, Some(seq)),
+            None => (self.open(T::default()).await, None),
+        };
+        self.stream(session, after, endpoint)
+    }
+}
+
+/// Redis keys recording sessions, followed by their ids. Each holds
+/// [`owner_record`] of the session's owner.
+#[cfg(feature = "redis")]
+const KEY_PREFIX: &str = "staticmcp:session:";
+
+/// How the principal a session belongs to is recorded in Redis.
+#[cfg(feature = "redis")]
+fn owner_record(owner: Option<&str>) -> String {
+    match owner {
+        Some(subject) => format!("principal:{subject}"),
+        None => "anonymous".to_string(),
+    }
+}
+
+/// Redis channel of messages for sessions another replica holds, each the
+/// session id, a space, and the message.
+#[cfg(feature = "redis")]
+const CHANNEL: &str = "staticmcp:sessions";
+
+/// Redis channel of sessions taken over by a replica, each its replica id,
+/// the session id and the number of the last message the client saw.
+#[cfg(feature = "redis")]
+const HANDOFFS: &str = "staticmcp:handoffs";
+
+#[cfg(feature = "redis")]
+impl<T: Send + Sync + 'static> Sessions<T> {
+    /// These sessions recorded in the Redis server `config` names, relaying
+    /// the messages other replicas publish for them.
+    fn shared(mut self, config: RedisConfig) -> Arc<Self> {
+        self.store = Some(Redis::new(config.clone()));
+        let sessions = Arc::new(self);
+        tokio::spawn(relay(config, Arc::downgrade(&sessions)));
+        tokio::spawn(refresh(Arc::downgrade(&sessions)));
+        sessions
+    }
+
+    /// Records the session `id`, belonging to `owner`, for [`IDLE_TIMEOUT`].
+    async fn record(&self, id: &str, owner: Option<&str>) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let key = format!("{KEY_PREFIX}{id}");
+        let owner = owner_record(owner);
+        let ttl = IDLE_TIMEOUT.as_secs().to_string();
+        if let Err(e) = store.command(&["SET", &key, &owner, "EX", &ttl]).await {
+            warn!(session = id, error = %e, "failed to record session");
+        }
+    }
+
+    async fn publish(&self, channel: &str, message: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.command(&["PUBLISH", channel, message]).await {
+            warn!(channel, error = %e, "failed to publish to Redis");
+        }
+    }
+
+    /// Forgets the session `id` another replica took over, publishing the
+    /// messages after `after` for it to send.
+    async fn hand_off(&self, id: &str, after: u64) {
+        let Some(session) = self.sessions.lock().unwrap().remove(id) else {
+            return;
+        };
+        let missed: Vec<String> = session
+            .state
+            .lock()
+            .unwrap()
+            .kept
+            .iter()
+            .filter(|message| message.seq > after)
+            .map(|message| message.data.clone())
+            .collect();
+        debug!(
+            session = id,
+            missed = missed.len(),
+            "handing off event stream"
+        );
+        for data in missed {
+            self.publish(CHANNEL, &format!("{id} {data}")).await;
+        }
+    }
+}
+
+/// Sends the messages published on [`CHANNEL`] on the sessions of
+/// `sessions` they name, and hands off those [`HANDOFFS`] names,
+/// reconnecting when the connection fails, until the sessions are dropped.
+#[cfg(feature = "redis")]
+async fn relay<T: Send + Sync + 'static>(
+    config: RedisConfig,
+    sessions: std::sync::Weak<Sessions<T>>,
+) {
+    loop {
+        let result: anyhow::Result<()> = async {
+            let mut connection = config.connect().await?;
+            connection
+                .command(&["SUBSCRIBE", CHANNEL, HANDOFFS])
+                .await?;
+            loop {
+                let Reply::Array(items) = connection.read().await? else {
+                    continue;
+                };
+                let [
+                    Reply::Bulk(Some(kind)),
+                    Reply::Bulk(Some(channel)),
+                    Reply::Bulk(Some(payload)),
+                ] = items.as_slice()
+                else {
+                    continue;
+                };
+                if kind != b"message" {
+                    continue;
+                }
+                let Some(sessions) = sessions.upgrade() else {
+                    return Ok(());
+                };
+                let payload = String::from_utf8_lossy(payload);
+                if channel == HANDOFFS.as_bytes() {
+                    let mut fields = payload.splitn(3, ' ');
+                    if let (Some(replica), Some(id), Some(Ok(after))) =
+                        (fields.next(), fields.next(), fields.next().map(str::parse))
+                        && replica != sessions.replica
+                    {
+                        sessions.hand_off(id, after).await;
+                    }
+                } else if let Some((id, data)) = payload.split_once(' ')
+                    && let Some(session) = sessions.get(id)
+                {
+                    session.send(data.to_string());
+                }
+            }
+        }
+        .await;
+        match result {
+            Ok(()) => return,
+            Err(e) => warn!(error = %e, "session relay disconnected from Redis"),
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Records again, every third of [`IDLE_TIMEOUT`], the sessions of
+/// `sessions` a stream is reading, until the sessions are dropped.
+#[cfg(feature = "redis")]
+async fn refresh<T: Send + Sync + 'static>(sessions: std::sync::Weak<Sessions<T>>) {
+    let mut interval = tokio::time::interval(IDLE_TIMEOUT / 3);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let Some(sessions) = sessions.upgrade() else {
+            return;
+        };
+        let read: Vec<Arc<Session<T>>> = sessions
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|session| session.state.lock().unwrap().streams > 0)
+            .cloned()
+            .collect();
+        for session in read {
+            sessions.record(&session.id, session.owner.as_deref()).await;
+        }
+    }
+}
+
+/// One client's event stream, the messages it was sent, and its `T`.
+pub struct Session<T = ()> {
+    id: String,
+    data: T,
+    /// Subject of the principal that opened the session, if authenticated.
+    owner: Option<String>,
+    state: Mutex<State>,
+    sender: broadcast::Sender<Message>,
+}
+
+struct State {
+    next: u64,
+    /// The messages kept for replay, oldest first.
+    kept: VecDeque<Message>,
+    replay: usize,
+    /// Streams currently reading the session.
+    streams: usize,
+    idle_since: Instant,
+    /// The MCP protocol version `initialize` agreed on, once it has.
+    protocol_version: Option<&'static str>,
+    /// The client `initialize` described, once it has.
+    client: Option<Arc<ClientInfo>>,
+}
+
+#[derive(Clone)]
+struct Message {
+    seq: u64,
+    data: String,
+}
+
+impl<T: Send + Sync + 'static> Session<T> {
+    /// The session `id` of `owner`, whose next message is numbered `next`.
+    fn new(id: String, next: u64, replay: usize, data: T, owner: Option<String>) -> Self {
+        Self {
+            id,
+            data,
+            owner,
+            state: Mutex::new(State {
+                next,
+                kept: VecDeque::new(),
+                replay,
+                streams: 0,
+                idle_since: Instant::now(),
+                protocol_version: None,
+                client: None,
+            }),
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// What the session was opened with.
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    /// Runs `future`, answering a message posted to the session, with the
+    /// session's id, and the protocol version and the client its
+    /// `initialize` agreed on.
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        let (version, client) = {
+            let state = self.state.lock().unwrap();
+            (state.protocol_version, state.client.clone())
+        };
+        let future = CURRENT.scope(self.id.clone(), future);
+        let future = async move {
+            match version {
+                Some(version) => protocol::scope(version, future).await,
+                None => future.await,
+            }
+        };
+        match client {
+            Some(client) => client::scope(client, future).await,
+            None => future.await,
+        }
+    }
+
+    /// Records what an `initialize` posted to the session agreed on: the
+    /// client it described and the protocol version of its `response`.
+    /// Other messages change nothing.
+    pub fn record(&self, client: Option<ClientInfo>, response: &MCPResponse) {
+        let Some(version) = protocol::agreed(response) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state.protocol_version = Some(version);
+        if let Some(client) = client {
+            debug!(session = self.id, %client, "client initialized session");
+            state.client = Some(Arc::new(client));
+        }
+    }
+
+    /// Sends the JSON-RPC message `data` on the session's stream, keeping it
+    /// for a client that reconnects having missed it.
+    pub fn send(&self, data: String) {
+        let mut state = self.state.lock().unwrap();
+        let message = Message {
+            seq: state.next,
+            data,
+        };
+        state.next += 1;
+        if state.replay > 0 {
+            if state.kept.len() == state.replay {
+                state.kept.pop_front();
+            }
+            state.kept.push_back(message.clone());
+        }
+        // Sent under the lock, so a stream subscribing meanwhile sees each
+        // message exactly once, from the kept ones or live.
+        let _ = self.sender.send(message);
+    }
+
+    fn expired(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.streams == 0 && state.idle_since.elapsed() > IDLE_TIMEOUT
+    }
+
+    fn event(&self, message: &Message) -> Event {
+        Event::default()
+            .id(format!("{}:{}", self.id, message.seq))
+            .event("message")
+            .data(&message.data)
+    }
+
+    /// The `endpoint` event, the kept messages after `after`, or all of them
+    /// for a new stream, then each message as it is sent.
+    fn stream(
+        self: Arc<Self>,
+        endpoint: String,
+        after: Option<u64>,
+        keep_alive: Duration,
+    ) -> impl Stream<Item = Result<Event, Infallible>> + use<T> {
+        let (missed, receiver) = {
+            let mut state = self.state.lock().unwrap();
+            state.streams += 1;
+            let missed: Vec<Event> = state
+                .kept
+                .iter()
+                .filter(|message| after.is_none_or(|after| message.seq > after))
+                .map(|message| self.event(message))
+                .collect();
+            if let (Some(after), Some(oldest)) = (after, state.kept.front())
+                && oldest.seq > after + 1
+            {
+                warn!(
+                    session = self.id,
+                    lost = oldest.seq - after - 1,
+                    "reconnecting client missed messages no longer kept"
+                );
+            }
+            (missed, self.sender.subscribe())
+        };
+        // No id, so a client reconnecting still names the last message.
+        let head = std::iter::once(Event::default().event("endpoint").data(endpoint))
+            .chain(missed)
+            .map(Ok);
+        let live = stream::unfold(
+            (receiver, Connected(self)),
+            move |(mut receiver, connected)| async move {
+                loop {
+                    let received = if keep_alive.is_zero() {
+                        Some(receiver.recv().await)
+                    } else {
+                        tokio::time::timeout(keep_alive, receiver.recv()).await.ok()
+                    };
+                    let event = match received {
+                        None => Event::default().event("ping").data(""),
+                        Some(Ok(message)) => connected.0.event(&message),
+                        Some(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                            warn!(
+                                session = connected.0.id,
+                                skipped, "event stream fell behind"
+                            );
+                            continue;
+                        }
+                        Some(Err(broadcast::error::RecvError::Closed)) => return None,
+                    };
+                    return Some((Ok(event), (receiver, connected)));
+                }
+            },
+        );
+        stream::iter(head).chain(live)
+    }
+}
+
+/// Marks a session as read by a stream until the stream is dropped.
+struct Connected<T>(Arc<Session<T>>);
+
+impl<T> Drop for Connected<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.streams -= 1;
+        state.idle_since = Instant::now();
+    }
+}
+
+/// The session and message number of an event id, `ID:N`.
+fn parse_event_id(value: &str) -> Option<(&str, u64)> {
+    let (id, seq) = value.rsplit_once(':')?;
+    Some((id, seq.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_caller<F: Future>(subject: &str, future: F) -> impl Future<Output = F::Output> {
+        let caller = principal::Principal {
+            subject: subject.into(),
+            scopes: Vec::new(),
+        };
+        principal::scope(caller, future)
+    }
+
+    async fn resumes(sessions: &Sessions, last_event_id: &str) -> bool {
+        sessions
+            .resume(Some(last_event_id), || async { Some(()) })
+            .await
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn sessions_belong_to_their_opener() {
+        let sessions = Sessions::<()>::new(10, Duration::ZERO);
+        let session = as_caller("alice", sessions.open(())).await;
+        let last_event_id = format!("{}:1", session.id());
+
+        assert!(as_caller("alice", sessions.known(session.id())).await);
+        assert!(as_caller("alice", resumes(&sessions, &last_event_id)).await);
+
+        assert!(!as_caller("bob", sessions.known(session.id())).await);
+        assert!(!as_caller("bob", resumes(&sessions, &last_event_id)).await);
+        assert!(!sessions.known(session.id()).await);
+        assert!(!resumes(&sessions, &last_event_id).await);
+    }
+
+    #[tokio::test]
+    async fn anonymous_sessions_are_not_open_to_principals() {
+        let sessions = Sessions::<()>::new(10, Duration::ZERO);
+        let session = sessions.open(()).await;
+        let last_event_id = format!("{}:1", session.id());
+
+        assert!(sessions.known(session.id()).await);
+        assert!(resumes(&sessions, &last_event_id).await);
+        assert!(!as_caller("alice", sessions.known(session.id())).await);
+        assert!(!as_caller("alice", resumes(&sessions, &last_event_id)).await);
+    }
+}
@@ -0,0 +1,321 @@
+//! Shared-secret HMAC request signatures, for machine callers that can't
+//! obtain OAuth tokens.
+//!
+//! A signed request carries three headers:
+//!
+//! - `X-Signature-Key`: the id of a key given with `--hmac-key ID=SECRET`
+//! - `X-Signature-Timestamp`: the Unix time in seconds when it was signed
+//! - `X-Signature`: hex HMAC-SHA256, under that key's secret, of the
+//!   timestamp, a `.`, and the raw request body
+//!
+//! Timestamps outside `--hmac-max-skew` of the server clock are refused, and
+//! each signature is accepted once within that window to stop replays. The
+//! key id becomes the request's principal for access policies and the audit
+//! log.
+
+use super::{envelope, principal};
+use crate::policy::Principal;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use clap::Args;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SIGNATURE_HEADER: HeaderName = HeaderName::from_static("x-signature");
+pub const KEY_HEADER: HeaderName = HeaderName::from_static("x-signature-key");
+pub const TIMESTAMP_HEADER: HeaderName = HeaderName::from_static("x-signature-timestamp");
+
+/// Seen signatures are swept once the table grows past this many.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Flags enabling signature verification.
+#[derive(Debug, Clone, Args)]
+pub struct SignatureArgs {
+    /// Accept requests signed with this shared secret, as `ID=SECRET`; repeat
+    /// to give several callers their own keys or to rotate one
+    #[arg(
+        long,
+        value_name = "ID=SECRET",
+        env = "HMAC_KEYS",
+        value_delimiter = ',',
+        value_parser = parse_key,
+        hide_env_values = true
+    )]
+    pub hmac_key: Vec<(String, String)>,
+
+    /// How far a signature's timestamp may be from the server clock
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "5m",
+        value_parser = humantime::parse_duration,
+        env = "HMAC_MAX_SKEW"
+    )]
+    pub hmac_max_skew: Duration,
+}
+
+impl SignatureArgs {
+    /// The signature verifier, when any `--hmac-key` is set. With
+    /// `optional`, unsigned requests are passed on for another layer to
+    /// authenticate instead of being refused.
+    pub fn verifier(&self, optional: bool) -> Option<Arc<Verifier>> {
+        if self.hmac_key.is_empty() {
+            return None;
+        }
+        Some(Arc::new(Verifier {
+            keys: self.hmac_key.iter().cloned().collect(),
+            max_skew: self.hmac_max_skew,
+            optional,
+            seen: Mutex::new(HashMap::new()),
+        }))
+    }
+}
+
+fn parse_key(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((id, secret)) if !id.is_empty() && !secret.is_empty() => {
+            Ok((id.to_string(), secret.to_string()))
+        }
+        _ => Err("expected ID=SECRET".into()),
+    }
+}
+
+/// Checks signatures and remembers the ones already used.
+pub struct Verifier {
+    keys: HashMap<String, String>,
+    max_skew: Duration,
+    optional: bool,
+    /// Accepted signatures, with the Unix time after which their timestamp
+    /// is too old to replay anyway.
+    seen: Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+impl Verifier {
+    fn verify(
+        &self,
+        key_id: &str,
+        timestamp: &str,
+        signature: &str,
+        body: &[u8],
+    ) -> Result<(), &'static str> {
+        let secret = self.keys.get(key_id).ok_or("unknown key")?;
+        let signed_at: u64 = timestamp.parse().map_err(|_| "invalid timestamp")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let skew = self.max_skew.as_secs();
+        if signed_at.abs_diff(now) > skew {
+            return Err("timestamp outside the allowed skew");
+        }
+        let signature = decode_hex(signature).ok_or("invalid signature encoding")?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| "signature mismatch")?;
+
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.len() > SWEEP_THRESHOLD {
+            seen.retain(|_, expires| *expires >= now);
+        }
+        if seen.insert(signature, signed_at + skew).is_some() {
+            return Err("signature already used");
+        }
+        Ok(())
+    }
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            let pair = value.get(i..i + 2)?;
+            if !pair.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return None;
+            }
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
+
+fn header<'a>(request: &'a Request, name: &HeaderName) -> Option<&'a str> {
+    request.headers().get(name)?.to_str().ok()
+}
+
+/// Rejects requests without a valid signature and runs the rest as the
+/// signing key's [`Principal`].
+pub async fn require(
+    State(verifier): State<Arc<Verifier>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(signature) = header(&request, &SIGNATURE_HEADER).map(str::to_string) else {
+        if verifier.optional {
+            return next.run(request).await;
+        }
+        return reject(&request, "Signature required");
+    };
+    let key_id = header(&request, &KEY_HEADER)
+        .unwrap_or_default()
+        .to_string();
+    let timestamp = header(&request, &TIMESTAMP_HEADER)
+        .unwrap_or_default()
+        .to_string();
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, envelope::MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let mut request = Request::from_parts(parts, Body::from(bytes.clone()));
+
+    if let Err(reason) = verifier.verify(&key_id, &timestamp, &signature, &bytes) {
+        tracing::debug!(%reason, key = %key_id, "rejected request signature");
+        return reject(&request, "Invalid signature");
+    }
+
+    let caller = Principal {
+        subject: key_id,
+        scopes: Vec::new(),
+    };
+    request.extensions_mut().insert(caller.clone());
+    principal::scope(caller, next.run(request)).await
+}
+
+fn reject(request: &Request, message: &str) -> Response {
+    envelope::of(request).reject(StatusCode::UNAUTHORIZED, -32000, message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier() -> Verifier {
+        SignatureArgs {
+            hmac_key: vec![("ci".into(), "s3cret".into())],
+            hmac_max_skew: Duration::from_secs(300),
+        }
+        .verifier(false)
+        .map(Arc::into_inner)
+        .unwrap()
+        .unwrap()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        let timestamp = now().to_string();
+        let body = br#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let signature = sign("s3cret", &timestamp, body);
+        assert_eq!(
+            verifier().verify("ci", &timestamp, &signature, body),
+            Ok(())
+        );
+        let upper = signature.to_uppercase();
+        assert_eq!(verifier().verify("ci", &timestamp, &upper, body), Ok(()));
+    }
+
+    #[test]
+    fn refuses_other_keys_and_bodies() {
+        let timestamp = now().to_string();
+        let signature = sign("wrong", &timestamp, b"{}");
+        let verifier = verifier();
+        assert_eq!(
+            verifier.verify("ci", &timestamp, &signature, b"{}"),
+            Err("signature mismatch")
+        );
+        let signature = sign("s3cret", &timestamp, b"{}");
+        assert_eq!(
+            verifier.verify("other", &timestamp, &signature, b"{}"),
+            Err("unknown key")
+        );
+        assert_eq!(
+            verifier.verify("ci", &timestamp, &signature, b"{ }"),
+            Err("signature mismatch")
+        );
+    }
+
+    #[test]
+    fn refuses_timestamps_outside_the_skew() {
+        let verifier = verifier();
+        for timestamp in [now() - 301, now() + 301] {
+            let timestamp = timestamp.to_string();
+            let signature = sign("s3cret", &timestamp, b"{}");
+            assert_eq!(
+                verifier.verify("ci", &timestamp, &signature, b"{}"),
+                Err("timestamp outside the allowed skew")
+            );
+        }
+        let timestamp = (now() - 200).to_string();
+        let signature = sign("s3cret", &timestamp, b"{}");
+        assert_eq!(verifier.verify("ci", &timestamp, &signature, b"{}"), Ok(()));
+        assert_eq!(
+            verifier.verify("ci", "soon", &signature, b"{}"),
+            Err("invalid timestamp")
+        );
+    }
+
+    #[test]
+    fn refuses_replays() {
+        let verifier = verifier();
+        let timestamp = now().to_string();
+        let signature = sign("s3cret", &timestamp, b"{}");
+        assert_eq!(verifier.verify("ci", &timestamp, &signature, b"{}"), Ok(()));
+        assert_eq!(
+            verifier.verify("ci", &timestamp, &signature, b"{}"),
+            Err("signature already used")
+        );
+        let upper = signature.to_uppercase();
+        assert_eq!(
+            verifier.verify("ci", &timestamp, &upper, b"{}"),
+            Err("signature already used")
+        );
+    }
+
+    #[test]
+    fn refuses_signatures_that_are_not_hex() {
+        let verifier = verifier();
+        let timestamp = now().to_string();
+        let signature = sign("s3cret", &timestamp, b"{}");
+        for bad in [&signature[1..], "zz", "+1", "é0"] {
+            assert_eq!(
+                verifier.verify("ci", &timestamp, bad, b"{}"),
+                Err("invalid signature encoding"),
+                "{bad}"
+            );
+        }
+        assert_eq!(decode_hex("00ff7A"), Some(vec![0x00, 0xff, 0x7a]));
+    }
+}
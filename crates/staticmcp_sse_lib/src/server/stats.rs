@@ -0,0 +1,251 @@
+//! Requests, errors and latency per site the proxy answers from, for its
+//! `/stats` and `/metrics` endpoints.
+
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Sites tracked by URL; those seen after are counted together as
+/// [`OTHER`], so arbitrary `url` parameters can't grow the stats without
+/// bound.
+pub const MAX_TARGETS: usize = 1000;
+
+/// The target the sites past [`MAX_TARGETS`] are counted under.
+pub const OTHER: &str = "other";
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// What each site was asked and how it answered.
+#[derive(Default)]
+pub struct TargetStats {
+    targets: Mutex<BTreeMap<String, Target>>,
+}
+
+#[derive(Default)]
+struct Target {
+    requests: u64,
+    /// Error answers, by JSON-RPC code.
+    errors: BTreeMap<i32, u64>,
+    latency: Histogram,
+    connects: u64,
+    connect_failures: u64,
+    connect_latency: Histogram,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket, not cumulative; the last counts those
+    /// above every bound.
+    counts: [u64; BUCKETS.len() + 1],
+    count: u64,
+    sum: f64,
+    max: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += seconds;
+        self.max = self.max.max(seconds);
+    }
+
+    fn json(&self) -> Value {
+        let mean = if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        };
+        json!({ "mean": millis(mean), "max": millis(self.max) })
+    }
+
+    fn prometheus(&self, out: &mut String, name: &str, target: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(self.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{name}_bucket{{{target},le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{target},le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum{{{target}}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{{target}}} {}", self.count);
+    }
+}
+
+/// `seconds` in milliseconds, to a tenth.
+fn millis(seconds: f64) -> f64 {
+    (seconds * 10_000.0).round() / 10.0
+}
+
+impl TargetStats {
+    /// Records a request the site `url` answered in `elapsed`, with the
+    /// code of its error when it failed.
+    pub fn request(&self, url: &str, elapsed: Duration, error: Option<i32>) {
+        self.update(url, |target| {
+            target.requests += 1;
+            if let Some(code) = error {
+                *target.errors.entry(code).or_default() += 1;
+            }
+            target.latency.observe(elapsed);
+        });
+    }
+
+    /// Records connecting to the site `url`, which loads its manifest.
+    pub fn connect(&self, url: &str, elapsed: Duration, ok: bool) {
+        self.update(url, |target| {
+            target.connects += 1;
+            if !ok {
+                target.connect_failures += 1;
+            }
+            target.connect_latency.observe(elapsed);
+        });
+    }
+
+    fn update(&self, url: &str, record: impl FnOnce(&mut Target)) {
+        let mut targets = self.targets.lock().unwrap();
+        let key = if targets.contains_key(url) || targets.len() < MAX_TARGETS {
+            url
+        } else {
+            OTHER
+        };
+        record(targets.entry(key.to_string()).or_default());
+    }
+
+    /// `GET /stats`: every site, the most asked first.
+    pub fn json(&self) -> Value {
+        let targets = self.targets.lock().unwrap();
+        let mut entries: Vec<(&String, &Target)> = targets.iter().collect();
+        entries.sort_by(|a, b| b.1.requests.cmp(&a.1.requests).then(a.0.cmp(b.0)));
+        let targets: Vec<Value> = entries
+            .into_iter()
+            .map(|(url, target)| {
+                let errors: u64 = target.errors.values().sum();
+                let error_rate = if target.requests == 0 {
+                    0.0
+                } else {
+                    (errors as f64 / target.requests as f64 * 1000.0).round() / 1000.0
+                };
+                json!({
+                    "url": url,
+                    "requests": target.requests,
+                    "errors": errors,
+                    "errorRate": error_rate,
+                    "errorsByCode": target
+                        .errors
+                        .iter()
+                        .map(|(code, count)| (code.to_string(), json!(count)))
+                        .collect::<serde_json::Map<_, _>>(),
+                    "latencyMs": target.latency.json(),
+                    "connects": target.connects,
+                    "connectFailures": target.connect_failures,
+                    "connectLatencyMs": target.connect_latency.json(),
+                })
+            })
+            .collect();
+        json!({ "targets": targets })
+    }
+
+    /// `GET /metrics`: the stats in the Prometheus text format, labelled by
+    /// `target`.
+    pub fn prometheus(&self) -> String {
+        let targets = self.targets.lock().unwrap();
+        let labelled: Vec<(String, &Target)> = targets
+            .iter()
+            .map(|(url, target)| (format!("target=\"{}\"", escape(url)), target))
+            .collect();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "staticmcp_proxy_requests_total",
+            "counter",
+            "MCP requests sent to each site.",
+        );
+        for (target, stats) in &labelled {
+            let _ = writeln!(
+                out,
+                "staticmcp_proxy_requests_total{{{target}}} {}",
+                stats.requests
+            );
+        }
+        header(
+            &mut out,
+            "staticmcp_proxy_errors_total",
+            "counter",
+            "Error answers from each site, by JSON-RPC code.",
+        );
+        for (target, stats) in &labelled {
+            for (code, count) in &stats.errors {
+                let _ = writeln!(
+                    out,
+                    "staticmcp_proxy_errors_total{{{target},code=\"{code}\"}} {count}"
+                );
+            }
+        }
+        header(
+            &mut out,
+            "staticmcp_proxy_request_duration_seconds",
+            "histogram",
+            "Time each site took to answer a request.",
+        );
+        for (target, stats) in &labelled {
+            stats
+                .latency
+                .prometheus(&mut out, "staticmcp_proxy_request_duration_seconds", target);
+        }
+        header(
+            &mut out,
+            "staticmcp_proxy_connects_total",
+            "counter",
+            "Connections to each site, loading its manifest, by outcome.",
+        );
+        for (target, stats) in &labelled {
+            let ok = stats.connects - stats.connect_failures;
+            let _ = writeln!(
+                out,
+                "staticmcp_proxy_connects_total{{{target},outcome=\"ok\"}} {ok}"
+            );
+            let _ = writeln!(
+                out,
+                "staticmcp_proxy_connects_total{{{target},outcome=\"error\"}} {}",
+                stats.connect_failures
+            );
+        }
+        header(
+            &mut out,
+            "staticmcp_proxy_connect_duration_seconds",
+            "histogram",
+            "Time connecting to each site took.",
+        );
+        for (target, stats) in &labelled {
+            stats.connect_latency.prometheus(
+                &mut out,
+                "staticmcp_proxy_connect_duration_seconds",
+                target,
+            );
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// `value` as a Prometheus label value, between its quotes.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,69 @@
+//! HTTP statuses for JSON-RPC answers, so monitoring and client retry logic
+//! can tell failures apart without reading the body, which stays the same
+//! JSON-RPC message whatever the status.
+
+use super::envelope::{self, McpEnvelope};
+use crate::MCPError;
+use axum::{
+    extract::{OriginalUri, Request},
+    http::StatusCode,
+    response::Response,
+};
+use serde_json::json;
+
+/// The status of an answer carrying `error`. Errors about what was asked,
+/// such as an unknown tool or a missing resource, are answers like any
+/// other and keep `200`; only a request the bridge couldn't read, a caller
+/// it refused, and a site or upstream that failed it get another status.
+pub fn of(error: Option<&MCPError>) -> StatusCode {
+    match error.map(|error| error.code) {
+        // Parse error and invalid request.
+        Some(-32700 | -32600) => StatusCode::BAD_REQUEST,
+        Some(-32003) => StatusCode::FORBIDDEN,
+        Some(-32001) => StatusCode::GATEWAY_TIMEOUT,
+        // The site's files or the upstream server couldn't be read.
+        Some(-32603) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::OK,
+    }
+}
+
+/// The status for failing to reach a site: `504` when it timed out,
+/// otherwise `502`.
+pub fn upstream(error: &anyhow::Error) -> StatusCode {
+    let timed_out = error.chain().any(|cause| {
+        #[cfg(feature = "remote")]
+        if cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout)
+        {
+            return true;
+        }
+        cause.is::<tokio::time::error::Elapsed>()
+    });
+    if timed_out {
+        StatusCode::GATEWAY_TIMEOUT
+    } else {
+        StatusCode::BAD_GATEWAY
+    }
+}
+
+/// Fallback for paths no route serves: `404`, with a JSON-RPC error for
+/// clients posting to the wrong URL.
+pub async fn not_found(request: Request) -> Response {
+    let uri = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri,
+        None => request.uri(),
+    };
+    no_such_endpoint(&envelope::of(&request), uri.path())
+}
+
+/// The `404` [`not_found`] answers, for handlers whose route matched a
+/// path they don't serve after all.
+pub fn no_such_endpoint(envelope: &McpEnvelope, path: &str) -> Response {
+    envelope.reject(
+        StatusCode::NOT_FOUND,
+        -32600,
+        "No such endpoint",
+        Some(json!({ "path": path })),
+    )
+}
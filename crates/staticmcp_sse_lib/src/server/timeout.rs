@@ -0,0 +1,46 @@
+//! Deadline for producing a response on the MCP endpoints.
+
+use super::envelope;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// When the server gives up on the request whose handler is currently
+/// running.
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Fails the request with a JSON-RPC error once `timeout` passes without a
+/// response. Only the time to the response head counts, so established SSE
+/// streams are not cut off.
+pub async fn limit(State(timeout): State<Duration>, request: Request, next: Next) -> Response {
+    let envelope = envelope::of(&request);
+
+    let deadline = Instant::now() + timeout;
+    match tokio::time::timeout(timeout, DEADLINE.scope(deadline, next.run(request))).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                timeout_ms = timeout.as_millis() as u64,
+                method = envelope.method.as_deref(),
+                "request timed out"
+            );
+            envelope.reject(
+                StatusCode::GATEWAY_TIMEOUT,
+                -32001,
+                "Request timed out",
+                Some(json!({ "timeoutMs": timeout.as_millis() as u64 })),
+            )
+        }
+    }
+}
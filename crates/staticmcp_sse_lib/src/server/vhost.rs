@@ -0,0 +1,69 @@
+//! Picks one of several routers by the host name a request was sent to.
+
+use axum::{Router, extract::Request, http::header, response::Response};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Routers keyed by host name, with an optional default for unknown hosts.
+#[derive(Clone, Default)]
+pub struct VirtualHosts {
+    hosts: HashMap<String, Router>,
+    default: Option<Router>,
+}
+
+impl VirtualHosts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves requests for `name` (matched case-insensitively, port ignored)
+    /// with `router`.
+    pub fn host(mut self, name: &str, router: Router) -> Self {
+        self.hosts.insert(name.to_ascii_lowercase(), router);
+        self
+    }
+
+    /// Serves requests whose host matches no [`Self::host`] entry; without
+    /// one they get `404`.
+    pub fn default_router(mut self, router: Router) -> Self {
+        self.default = Some(router);
+        self
+    }
+
+    /// A router handing every request to the router for its host.
+    pub fn into_router(self) -> Router {
+        let hosts = Arc::new(self);
+        Router::new().fallback(move |request: Request| {
+            let hosts = hosts.clone();
+            async move { hosts.dispatch(request).await }
+        })
+    }
+
+    async fn dispatch(&self, request: Request) -> Response {
+        let router = host_name(&request)
+            .and_then(|host| self.hosts.get(&host))
+            .or(self.default.as_ref());
+        match router {
+            Some(router) => match router.clone().oneshot(request).await {
+                Ok(response) => response,
+                Err(never) => match never {},
+            },
+            None => super::status::not_found(request).await,
+        }
+    }
+}
+
+/// Lowercased host the request was addressed to, from the URI authority
+/// (HTTP/2) or the `Host` header, without the port.
+fn host_name(request: &Request) -> Option<String> {
+    let host = match request.uri().host() {
+        Some(host) => host,
+        None => request.headers().get(header::HOST)?.to_str().ok()?,
+    };
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    Some(host.to_ascii_lowercase())
+}
@@ -0,0 +1,136 @@
+//! Coalescing of concurrent identical loads: while one load of a path is in
+//! flight, others of the same path wait for its result instead of starting
+//! their own, so fifty agents calling the same tool at once cost one
+//! upstream GET.
+//!
+//! Waiting loads share the first one's outcome, errors included, and the
+//! request it was sent with, such as its headers. When the first load is
+//! dropped before finishing, as a timed-out request drops it, a waiting load
+//! starts over in its place.
+
+use futures::channel::oneshot;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+
+type Waiters<T> = Vec<oneshot::Sender<Result<T, SharedError>>>;
+
+/// Loads in flight, by key, each with the loads waiting for it.
+pub struct Flights<T> {
+    in_flight: Mutex<HashMap<String, Waiters<T>>>,
+}
+
+impl<T> Default for Flights<T> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> Flights<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn in_flight(&self) -> MutexGuard<'_, HashMap<String, Waiters<T>>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The result of `load`, or of the load of `key` already in flight.
+    pub async fn run<F, Fut>(&self, key: &str, load: F) -> anyhow::Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        loop {
+            let receiver = {
+                let mut in_flight = self.in_flight();
+                match in_flight.get_mut(key) {
+                    Some(waiters) => {
+                        let (sender, receiver) = oneshot::channel();
+                        waiters.push(sender);
+                        receiver
+                    }
+                    None => {
+                        in_flight.insert(key.to_string(), Vec::new());
+                        break;
+                    }
+                }
+            };
+            match receiver.await {
+                Ok(result) => return result.map_err(SharedError::into_error),
+                // The load was dropped; start over, perhaps leading.
+                Err(oneshot::Canceled) => continue,
+            }
+        }
+
+        let mut leader = Leader {
+            flights: self,
+            key: Some(key),
+        };
+        let result = load().await;
+        let waiters = leader.finish();
+        if !waiters.is_empty() {
+            tracing::debug!(key, waiters = waiters.len(), "shared load");
+            let shared = result.as_ref().map(Clone::clone).map_err(SharedError::of);
+            for waiter in waiters {
+                let _ = waiter.send(shared.clone());
+            }
+        }
+        result
+    }
+}
+
+/// The load in flight, whose key is cleared however it ends, so its waiters
+/// never wait on a load that was dropped.
+struct Leader<'a, T> {
+    flights: &'a Flights<T>,
+    key: Option<&'a str>,
+}
+
+impl<T> Leader<'_, T> {
+    fn finish(&mut self) -> Waiters<T> {
+        let Some(key) = self.key.take() else {
+            return Vec::new();
+        };
+        let mut in_flight = self
+            .flights
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        in_flight.remove(key).unwrap_or_default()
+    }
+}
+
+impl<T> Drop for Leader<'_, T> {
+    fn drop(&mut self) {
+        // Dropping the senders wakes the waiters to start over.
+        self.finish();
+    }
+}
+
+/// An error shared with waiting loads, which keeps whether the file was
+/// missing along with the message.
+#[derive(Clone)]
+struct SharedError {
+    not_found: bool,
+    message: String,
+}
+
+impl SharedError {
+    fn of(error: &anyhow::Error) -> Self {
+        Self {
+            not_found: crate::is_not_found(error),
+            message: format!("{error:#}"),
+        }
+    }
+
+    fn into_error(self) -> anyhow::Error {
+        if self.not_found {
+            std::io::Error::new(std::io::ErrorKind::NotFound, self.message).into()
+        } else {
+            anyhow::anyhow!(self.message)
+        }
+    }
+}
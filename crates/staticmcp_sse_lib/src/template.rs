@@ -0,0 +1,60 @@
+//! `{{name}}` placeholders in response files, filled in from the request
+//! they answer: `{{args.query}}` is a tool call's `query` argument,
+//! `{{args}}` all of them as a JSON object, and `{{uri}}` the URI of a
+//! resource read. Placeholders with no value are left as they are.
+
+use crate::resolver::path_value;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Replaces the placeholders in every string within `value`.
+pub fn render(value: &mut Value, lookup: &impl Fn(&str) -> Option<String>) {
+    match value {
+        Value::String(text) => {
+            if let Some(rendered) = render_text(text, lookup) {
+                *text = rendered;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| render(item, lookup)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| render(field, lookup)),
+        _ => {}
+    }
+}
+
+/// `text` with its placeholders replaced, or `None` when it has none.
+pub(crate) fn render_text(text: &str, lookup: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    if !text.contains("{{") {
+        return None;
+    }
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start..start + length + 2];
+        match lookup(placeholder[2..length].trim()) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(placeholder),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    rendered.push_str(rest);
+    Some(rendered)
+}
+
+/// The values of a tool call's placeholders: `args` and `args.{name}`,
+/// strings as they are and other values as JSON.
+pub fn arguments(args: &HashMap<String, Value>) -> impl Fn(&str) -> Option<String> + '_ {
+    |name| match name.strip_prefix("args") {
+        Some("") => serde_json::to_string(args).ok(),
+        Some(rest) => args.get(rest.strip_prefix('.')?).map(path_value),
+        None => None,
+    }
+}
+
+/// The values of a resource read's placeholders: `uri`.
+pub fn resource(uri: &str) -> impl Fn(&str) -> Option<String> + '_ {
+    move |name| (name == "uri").then(|| uri.to_string())
+}
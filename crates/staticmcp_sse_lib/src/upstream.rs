@@ -0,0 +1,152 @@
+//! A live MCP server behind the bridge, reached over the Streamable HTTP
+//! transport, answering the tools and resources the static files don't.
+
+use crate::MCPResponse;
+use futures::lock::Mutex;
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::debug;
+
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// An MCP client for one upstream server. The session is opened by the
+/// first request and reopened when the server forgets it.
+pub struct Upstream {
+    pub url: String,
+    client: reqwest::Client,
+    /// `None` until a session is open, then the id the server assigned to
+    /// it, if any.
+    session: Mutex<Option<Option<String>>>,
+    next_id: AtomicU64,
+}
+
+impl Upstream {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+            session: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Sends `method` with `params` and returns the server's response, with
+    /// its own request id.
+    pub async fn request(&self, method: &str, params: Value) -> anyhow::Result<MCPResponse> {
+        let mut reopened = false;
+        loop {
+            let session = self.session().await?;
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+            let response = self.post(&body, session.as_deref()).await?;
+            // Servers answer `404` to a session they no longer know.
+            if response.status() == reqwest::StatusCode::NOT_FOUND && session.is_some() && !reopened
+            {
+                debug!(url = %self.url, "upstream session expired, reopening");
+                *self.session.lock().await = None;
+                reopened = true;
+                continue;
+            }
+            return Self::response(response, id).await;
+        }
+    }
+
+    /// The id of the open session, opening one first when there is none.
+    async fn session(&self) -> anyhow::Result<Option<String>> {
+        let mut session = self.session.lock().await;
+        if let Some(id) = &*session {
+            return Ok(id.clone());
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "staticmcp-bridge",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            },
+        });
+        let response = self.post(&initialize, None).await?;
+        let session_id = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        if let Some(error) = Self::response(response, id).await?.error {
+            anyhow::bail!("upstream refused to initialize: {}", error.message);
+        }
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        let response = self.post(&initialized, session_id.as_deref()).await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "upstream HTTP {} to notifications/initialized",
+                response.status()
+            );
+        }
+        *session = Some(session_id.clone());
+        Ok(session_id)
+    }
+
+    async fn post(&self, body: &Value, session: Option<&str>) -> anyhow::Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .json(body);
+        if let Some(session) = session {
+            request = request.header(SESSION_HEADER, session);
+        }
+        let started = Instant::now();
+        let response = request.send().await?;
+        debug!(
+            url = %self.url,
+            method = body["method"].as_str().unwrap_or_default(),
+            status = response.status().as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "upstream request"
+        );
+        Ok(response)
+    }
+
+    /// The response to request `id`, sent as JSON or as an event of an SSE
+    /// stream.
+    async fn response(response: reqwest::Response, id: u64) -> anyhow::Result<MCPResponse> {
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("upstream HTTP {status}");
+        }
+        let event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let body = response.text().await?;
+        if !event_stream {
+            return Ok(serde_json::from_str(&body)?);
+        }
+        for event in body.replace("\r\n", "\n").split("\n\n") {
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            let Ok(message) = serde_json::from_str::<Value>(&data.join("\n")) else {
+                continue;
+            };
+            if message.get("id") == Some(&json!(id)) {
+                return Ok(serde_json::from_value(message)?);
+            }
+        }
+        anyhow::bail!("upstream stream ended without a response")
+    }
+}
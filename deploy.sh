@@ -45,6 +45,7 @@ targets=(
 
 
 PACKAGE_NAMES=(
+    "staticmcp-bridge"
     "staticmcp_sse_dynamic"
     "staticmcp_sse_fixed"
 )